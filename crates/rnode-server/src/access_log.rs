@@ -0,0 +1,329 @@
+use crate::client_ip::ClientIp;
+use axum::body::HttpBody;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use http::header::{self, HeaderName, HeaderValue};
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::time::Instant;
+
+const DEFAULT_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_FILES: u32 = 5;
+
+/// Correlation ID of a request, passed to JS as `req.requestId`
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Access log line format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessLogFormat {
+    #[default]
+    Json,
+    Common,
+    Combined,
+}
+
+/// `accessLog` option of listen()
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccessLogOptions {
+    pub format: AccessLogFormat,
+    pub path: Option<String>, // Log file, stdout when unset
+    pub max_size: Option<u64>, // Bytes before the file is rotated
+    pub max_files: Option<u32>, // Rotated files kept: access.log.1 ... access.log.N
+}
+
+/// What is logged about a request
+#[derive(Debug)]
+pub struct AccessLogEntry {
+    pub request_id: String,
+    pub ip: String,
+    pub method: String,
+    pub uri: String,
+    pub protocol: String,
+    pub status: u16,
+    pub bytes: Option<u64>, // None for streams of unknown length
+    pub duration_ms: f64,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+// Quotes and backslashes would break the quoted CLF fields
+fn escape_clf(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl AccessLogEntry {
+    fn format(&self, format: AccessLogFormat, time: chrono::DateTime<chrono::Local>) -> String {
+        match format {
+            AccessLogFormat::Json => json!({
+                "time": time.to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+                "requestId": self.request_id,
+                "ip": self.ip,
+                "method": self.method,
+                "url": self.uri,
+                "protocol": self.protocol,
+                "status": self.status,
+                "bytes": self.bytes,
+                "durationMs": (self.duration_ms * 1000.0).round() / 1000.0,
+                "referer": self.referer,
+                "userAgent": self.user_agent,
+            })
+            .to_string(),
+            AccessLogFormat::Common | AccessLogFormat::Combined => {
+                let mut line = format!(
+                    "{} - - [{}] \"{} {} {}\" {} {}",
+                    self.ip,
+                    time.format("%d/%b/%Y:%H:%M:%S %z"),
+                    self.method,
+                    escape_clf(&self.uri),
+                    self.protocol,
+                    self.status,
+                    self.bytes.map(|bytes| bytes.to_string()).unwrap_or_else(|| "-".to_string()),
+                );
+                if format == AccessLogFormat::Combined {
+                    line.push_str(&format!(
+                        " \"{}\" \"{}\"",
+                        self.referer.as_deref().map(escape_clf).unwrap_or_else(|| "-".to_string()),
+                        self.user_agent.as_deref().map(escape_clf).unwrap_or_else(|| "-".to_string()),
+                    ));
+                }
+                line
+            }
+        }
+    }
+}
+
+// Log file renamed to .1, .2, ... once it grows past max_size
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: u32,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_size: u64, max_files: u32) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        Ok(Self { path, file, size, max_size, max_files })
+    }
+
+    fn rotated(&self, index: u32) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let _ = std::fs::rename(self.rotated(index), self.rotated(index + 1));
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// Writes access log lines on a background thread so requests never wait for I/O
+#[derive(Debug)]
+pub struct AccessLogWriter {
+    format: AccessLogFormat,
+    sender: Sender<String>,
+}
+
+impl AccessLogWriter {
+    pub fn new(options: &AccessLogOptions) -> Self {
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        let mut file = options.path.as_ref().and_then(|path| {
+            match RotatingFile::open(
+                PathBuf::from(path),
+                options.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+                options.max_files.unwrap_or(DEFAULT_MAX_FILES),
+            ) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!("⚠️ Cannot open access log {}: {}, logging to stdout", path, e);
+                    None
+                }
+            }
+        });
+
+        std::thread::spawn(move || {
+            for line in receiver {
+                let result = match file.as_mut() {
+                    Some(file) => file.write_line(&line),
+                    None => writeln!(std::io::stdout().lock(), "{}", line),
+                };
+                if let Err(e) = result {
+                    warn!("⚠️ Failed to write access log: {}", e);
+                }
+            }
+        });
+
+        Self { format: options.format, sender }
+    }
+
+    pub fn log(&self, entry: &AccessLogEntry) {
+        let _ = self.sender.send(entry.format(self.format, chrono::Local::now()));
+    }
+}
+
+/// Request ID and access log settings of a server
+#[derive(Debug)]
+pub struct AccessLogConfig {
+    header: HeaderName,
+    writer: Option<AccessLogWriter>,
+}
+
+impl AccessLogConfig {
+    pub fn new(header: Option<&str>, options: Option<&AccessLogOptions>) -> Self {
+        let header = header
+            .and_then(|name| match HeaderName::from_bytes(name.to_ascii_lowercase().as_bytes()) {
+                Ok(name) => Some(name),
+                Err(e) => {
+                    warn!("⚠️ Invalid request ID header '{}': {}", name, e);
+                    None
+                }
+            })
+            .unwrap_or(HeaderName::from_static(DEFAULT_HEADER));
+
+        Self {
+            header,
+            writer: options.map(AccessLogWriter::new),
+        }
+    }
+}
+
+// Client IDs are kept if they are short printable ASCII, otherwise replaced
+fn accept_request_id(value: Option<&HeaderValue>) -> Option<String> {
+    let value = value?.to_str().ok()?.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value.bytes().all(|byte| byte.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+/// Assign every request an ID, echo it in the response and write the access log line
+pub async fn access_log_layer(config: Arc<AccessLogConfig>, mut req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let request_id = accept_request_id(req.headers().get(&config.header))
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    debug!("🆔 Request {} {} {}", request_id, req.method(), req.uri());
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    // Captured before the request is consumed
+    let entry = config.writer.as_ref().map(|_| {
+        let header = |name: header::HeaderName| {
+            req.headers().get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string())
+        };
+        AccessLogEntry {
+            request_id: request_id.clone(),
+            ip: req
+                .extensions()
+                .get::<ClientIp>()
+                .map(|client| client.ip.clone())
+                .unwrap_or_else(|| "-".to_string()),
+            method: req.method().to_string(),
+            uri: req.uri().to_string(),
+            protocol: format!("{:?}", req.version()),
+            status: 0,
+            bytes: None,
+            duration_ms: 0.0,
+            referer: header(header::REFERER),
+            user_agent: header(header::USER_AGENT),
+        }
+    });
+
+    let mut response = next.run(req).await;
+
+    if !response.headers().contains_key(&config.header)
+        && let Ok(value) = HeaderValue::from_str(&request_id)
+    {
+        response.headers_mut().insert(config.header.clone(), value);
+    }
+
+    if let (Some(writer), Some(mut entry)) = (&config.writer, entry) {
+        entry.status = response.status().as_u16();
+        entry.bytes = response.body().size_hint().exact();
+        entry.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+        writer.log(&entry);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_access_log_formats() {
+        let entry = AccessLogEntry {
+            request_id: "abc".to_string(),
+            ip: "10.0.0.1".to_string(),
+            method: "GET".to_string(),
+            uri: "/items?page=2".to_string(),
+            protocol: "HTTP/1.1".to_string(),
+            status: 200,
+            bytes: Some(512),
+            duration_ms: 1.23456,
+            referer: None,
+            user_agent: Some("curl/8.0 \"test\"".to_string()),
+        };
+        let time = chrono::Local.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let date = time.format("%d/%b/%Y:%H:%M:%S %z");
+
+        assert_eq!(
+            entry.format(AccessLogFormat::Common, time),
+            format!("10.0.0.1 - - [{}] \"GET /items?page=2 HTTP/1.1\" 200 512", date)
+        );
+        assert_eq!(
+            entry.format(AccessLogFormat::Combined, time),
+            format!("10.0.0.1 - - [{}] \"GET /items?page=2 HTTP/1.1\" 200 512 \"-\" \"curl/8.0 \\\"test\\\"\"", date)
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&entry.format(AccessLogFormat::Json, time)).unwrap();
+        assert_eq!(json["requestId"], "abc");
+        assert_eq!(json["status"], 200);
+        assert_eq!(json["durationMs"], 1.235);
+    }
+
+    #[test]
+    fn test_accept_request_id() {
+        assert_eq!(accept_request_id(Some(&HeaderValue::from_static("req-42"))), Some("req-42".to_string()));
+        assert_eq!(accept_request_id(Some(&HeaderValue::from_static("has space"))), None);
+        assert_eq!(accept_request_id(Some(&HeaderValue::from_str(&"a".repeat(200)).unwrap())), None);
+        assert_eq!(accept_request_id(None), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::handlers::middleware::MiddlewareChain;
+use crate::route_table::RouteTable;
+use crate::shutdown::ServerShutdown;
+use crate::sse::SseRoute;
+use crate::static_files::types::{StaticFile, StaticFolder};
+use crate::types::{DownloadRouteConfig, RouteInfo, UploadRouteConfig};
+use crate::websocket::WebSocketHandler;
+use arc_swap::ArcSwapOption;
+use log::debug;
+use neon::event::Channel;
+use neon::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use tera::Tera;
+
+/// Identifier of an application instance
+pub type AppId = u32;
+
+/// App used when no app handle is passed (single-app setups)
+pub const DEFAULT_APP_ID: AppId = 0;
+
+/// All state belonging to one application instance
+pub struct App {
+    pub id: AppId,
+    // Dynamic routes by "METHOD:path"
+    pub routes: RwLock<HashMap<String, RouteInfo>>,
+    // Middleware in registration order
+    pub middleware: RwLock<MiddlewareChain>,
+    // JS response hooks, matched like middleware
+    pub after_hooks: RwLock<MiddlewareChain>,
+    pub download_routes: RwLock<HashMap<String, DownloadRouteConfig>>,
+    pub upload_routes: RwLock<HashMap<String, UploadRouteConfig>>,
+    pub websocket_routes: tokio::sync::RwLock<HashMap<String, WebSocketHandler>>,
+    pub sse_routes: RwLock<HashMap<String, SseRoute>>,
+    pub static_folders: RwLock<Vec<StaticFolder>>,
+    pub static_cache: RwLock<HashMap<String, StaticFile>>,
+    pub templates: Mutex<Option<Tera>>,
+    // Channel to the JavaScript thread, set on listen()
+    pub event_queue: RwLock<Option<Channel>>,
+    // Router currently serving requests
+    pub route_table: ArcSwapOption<RouteTable>,
+    // Shutdown state of the running server
+    pub shutdown: RwLock<Option<Arc<ServerShutdown>>>,
+    // Certificates of the running HTTPS server, for reloadTls()
+    pub tls: RwLock<Option<Arc<crate::tls::CertificateStore>>>,
+}
+
+impl App {
+    fn new(id: AppId) -> Self {
+        Self {
+            id,
+            routes: RwLock::new(HashMap::new()),
+            middleware: RwLock::new(MiddlewareChain::default()),
+            after_hooks: RwLock::new(MiddlewareChain::default()),
+            download_routes: RwLock::new(HashMap::new()),
+            upload_routes: RwLock::new(HashMap::new()),
+            websocket_routes: tokio::sync::RwLock::new(HashMap::new()),
+            sse_routes: RwLock::new(HashMap::new()),
+            static_folders: RwLock::new(Vec::new()),
+            static_cache: RwLock::new(HashMap::new()),
+            templates: Mutex::new(None),
+            event_queue: RwLock::new(None),
+            route_table: ArcSwapOption::empty(),
+            shutdown: RwLock::new(None),
+            tls: RwLock::new(None),
+        }
+    }
+
+    /// Channel to the JavaScript thread, if the app is listening
+    pub fn channel(&self) -> Option<Channel> {
+        self.event_queue.read().unwrap().clone()
+    }
+}
+
+// Registry of application instances
+static APPS: OnceLock<RwLock<HashMap<AppId, Arc<App>>>> = OnceLock::new();
+
+static NEXT_APP_ID: AtomicU32 = AtomicU32::new(DEFAULT_APP_ID + 1);
+
+pub fn get_apps() -> &'static RwLock<HashMap<AppId, Arc<App>>> {
+    // The default app exists from the start; every other app comes from create_app_instance()
+    APPS.get_or_init(|| RwLock::new(HashMap::from([(DEFAULT_APP_ID, Arc::new(App::new(DEFAULT_APP_ID)))])))
+}
+
+/// Create a new application instance
+pub fn create_app_instance() -> Arc<App> {
+    let id = NEXT_APP_ID.fetch_add(1, Ordering::SeqCst);
+    let app = Arc::new(App::new(id));
+    get_apps().write().unwrap().insert(id, app.clone());
+    debug!("🧩 Created app instance {}", id);
+    app
+}
+
+/// Get a registered application instance
+pub fn find_app(id: AppId) -> Option<Arc<App>> {
+    get_apps().read().unwrap().get(&id).cloned()
+}
+
+/// All application instances
+pub fn all_apps() -> Vec<Arc<App>> {
+    get_apps().read().unwrap().values().cloned().collect()
+}
+
+/// Resolve the app handle passed as an optional numeric argument, throwing for unknown ids
+pub fn app_from_arg(cx: &mut FunctionContext, index: usize) -> NeonResult<Arc<App>> {
+    let id = cx
+        .argument_opt(index)
+        .and_then(|arg| arg.downcast::<JsNumber, _>(cx).ok())
+        .map(|num| num.value(cx) as AppId)
+        .unwrap_or(DEFAULT_APP_ID);
+    match find_app(id) {
+        Some(app) => Ok(app),
+        None => cx.throw_error(format!("Unknown app id {}", id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_created_apps_are_registered() {
+        assert!(find_app(DEFAULT_APP_ID).is_some());
+        let app = create_app_instance();
+        assert!(find_app(app.id).is_some_and(|found| Arc::ptr_eq(&found, &app)));
+        assert!(find_app(AppId::MAX).is_none());
+        assert!(!get_apps().read().unwrap().contains_key(&AppId::MAX));
+    }
+}
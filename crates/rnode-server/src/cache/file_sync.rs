@@ -232,6 +232,21 @@ impl FileCacheSync {
         Ok(())
     }
     
+    pub fn flush(&self) -> CacheResult<()> {
+        let index_guard = self.index.lock()
+            .map_err(|e| CacheError::FileError(format!("Failed to read index: {}", e)))?;
+        
+        self.save_index(&index_guard)?;
+        
+        // Make sure the index reaches the disk
+        File::open(&self.index_file)
+            .and_then(|file| file.sync_all())
+            .map_err(|e| CacheError::FileError(format!("Failed to sync index file: {}", e)))?;
+        
+        debug!("💾 File cache index flushed");
+        Ok(())
+    }
+    
     pub fn flush_by_tags(&self, tags: &[String]) -> CacheResult<usize> {
         debug!("🏷️ File cache flush by tags: {:?}", tags);
         
@@ -275,4 +275,21 @@ impl CacheManagerSync {
         
         Ok(())
     }
+    
+    /// Redis backend, if configured
+    pub fn redis(&self) -> Option<Arc<RedisCacheSync>> {
+        self.redis_cache.clone()
+    }
+    
+    pub fn flush(&self) -> CacheResult<()> {
+        debug!("💾 Flushing cache backends");
+        
+        // Memory cache has nothing to persist and Redis writes are synchronous,
+        // so only the file cache index needs to be written out
+        if let Some(ref file_cache) = self.file_cache {
+            file_cache.flush()?;
+        }
+        
+        Ok(())
+    }
 }
@@ -227,4 +227,15 @@ pub fn cache_flush_by_tags(tags: &[String]) -> CacheResult<usize> {
         record_cache_error("not_initialized", "memory", "flush_by_tags");
         Err(CacheError::RedisError("Cache system not initialized".to_string()))
     }
-}
\ No newline at end of file
+}
+
+pub fn cache_flush() -> CacheResult<()> {
+    // Nothing to flush if the cache system was never initialized
+    match get_cache_manager() {
+        Some(cache_manager) => {
+            debug!("💾 Cache flush called");
+            cache_manager.flush()
+        }
+        None => Ok(()),
+    }
+}
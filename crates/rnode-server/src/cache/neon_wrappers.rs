@@ -1,6 +1,7 @@
 use neon::prelude::*;
 use crate::cache::{init_cache_system, cache_get, cache_set, cache_delete, cache_exists, cache_clear, cache_flush_by_tags};
 use crate::cache::types::*;
+use crate::telemetry::{Span, SpanKind};
 use log::{debug, error};
 
 pub fn init_cache_system_wrapper(mut cx: FunctionContext) -> JsResult<JsUndefined> {
@@ -53,10 +54,16 @@ pub fn cache_get_wrapper(mut cx: FunctionContext) -> JsResult<JsValue> {
     }
     
     let options = CacheOptions { ttl: None, tags: tags.unwrap_or_default() };
+    let mut span = Span::from_arg(&mut cx, 2, "cache get", SpanKind::Client);
     
     debug!("🔍 Cache get request for key: {}", key);
     
-    match cache_get::<String>(&key, &options) {
+    let result = cache_get::<String>(&key, &options);
+    if let Some(span) = span.as_mut() {
+        span.set_attribute("rnode.cache.key", key.clone());
+        span.set_attribute("rnode.cache.hit", matches!(result, Ok(Some(_))));
+    }
+    match result {
         Ok(Some(value)) => {
             debug!("✅ Cache hit for key: {}", key);
             Ok(cx.string(value).upcast())
@@ -93,6 +100,10 @@ pub fn cache_set_wrapper(mut cx: FunctionContext) -> JsResult<JsBoolean> {
     };
     
     let options = CacheOptions { ttl, tags };
+    let _span = Span::from_arg(&mut cx, 4, "cache set", SpanKind::Client).map(|mut span| {
+        span.set_attribute("rnode.cache.key", key.clone());
+        span
+    });
     
     debug!("💾 Cache set request for key: {}", key);
     
@@ -125,6 +136,10 @@ pub fn cache_delete_wrapper(mut cx: FunctionContext) -> JsResult<JsBoolean> {
     }
     
     let options = CacheOptions { ttl: None, tags: tags.unwrap_or_default() };
+    let _span = Span::from_arg(&mut cx, 2, "cache delete", SpanKind::Client).map(|mut span| {
+        span.set_attribute("rnode.cache.key", key.clone());
+        span
+    });
     
     debug!("🗑️ Cache delete request for key: {}", key);
     
@@ -157,6 +172,10 @@ pub fn cache_exists_wrapper(mut cx: FunctionContext) -> JsResult<JsBoolean> {
     }
     
     let options = CacheOptions { ttl: None, tags: tags.unwrap_or_default() };
+    let _span = Span::from_arg(&mut cx, 2, "cache exists", SpanKind::Client).map(|mut span| {
+        span.set_attribute("rnode.cache.key", key.clone());
+        span
+    });
     
     debug!("🔍 Cache exists request for key: {}", key);
     
@@ -174,6 +193,7 @@ pub fn cache_exists_wrapper(mut cx: FunctionContext) -> JsResult<JsBoolean> {
 
 pub fn cache_clear_wrapper(mut cx: FunctionContext) -> JsResult<JsBoolean> {
     debug!("🗑️ Cache clear request");
+    let _span = Span::from_arg(&mut cx, 0, "cache clear", SpanKind::Client);
     
     match cache_clear() {
         Ok(_) => {
@@ -197,6 +217,10 @@ pub fn cache_flush_by_tags_wrapper(mut cx: FunctionContext) -> JsResult<JsNumber
         .collect();
     
     debug!("🏷️ Tags to flush: {:?}", tags);
+    let _span = Span::from_arg(&mut cx, 1, "cache flush_by_tags", SpanKind::Client).map(|mut span| {
+        span.set_attribute("rnode.cache.tags", tags.join(","));
+        span
+    });
     
     match cache_flush_by_tags(&tags) {
         Ok(count) => {
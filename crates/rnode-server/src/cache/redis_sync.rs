@@ -194,6 +194,18 @@ impl RedisCacheSync {
         Ok(())
     }
     
+    /// Run a Lua script atomically against a prefixed key
+    pub fn eval_script<T: redis::FromRedisValue>(&self, script: &redis::Script, key: &str, args: &[u64]) -> CacheResult<T> {
+        let mut conn = self.get_connection()?;
+        let mut invocation = script.key(self.get_full_key(key));
+        for arg in args {
+            invocation.arg(*arg);
+        }
+        invocation
+            .invoke(&mut conn)
+            .map_err(|e| CacheError::RedisError(format!("Redis script error: {}", e)))
+    }
+    
     fn get_connection(&self) -> CacheResult<Connection> {
         self.client.get_connection()
             .map_err(|e| CacheError::RedisError(format!("Failed to get Redis connection: {}", e)))
@@ -0,0 +1,227 @@
+//! Client certificates of mutual TLS connections, exposed to JS as `req.clientCert`
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const OID: u8 = 0x06;
+const OCTET_STRING: u8 = 0x04;
+const BMP_STRING: u8 = 0x1e;
+const VERSION: u8 = 0xa0; // [0] EXPLICIT
+const EXTENSIONS: u8 = 0xa3; // [3] EXPLICIT
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11]; // 2.5.29.17
+
+/// Identity of a verified client certificate
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCertificate {
+    pub subject: String, // RFC 4514, e.g. "CN=billing,O=Acme"
+    pub issuer: String,
+    pub subject_alt_names: Vec<String>, // "DNS:…", "URI:…", "IP:…", "email:…"
+    pub fingerprint: String, // SHA-256 of the DER, "AB:CD:…"
+}
+
+// One DER element
+struct Der<'a> {
+    tag: u8,
+    contents: &'a [u8],
+}
+
+// Read the element at the start of `input` and advance past it
+fn read<'a>(input: &mut &'a [u8]) -> Option<Der<'a>> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, mut rest) = rest.split_first()?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let (bytes, tail) = rest.split_at(count);
+        rest = tail;
+        bytes.iter().fold(0usize, |len, byte| (len << 8) | *byte as usize)
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let (contents, tail) = rest.split_at(len);
+    *input = tail;
+    Some(Der { tag, contents })
+}
+
+fn elements(mut input: &[u8]) -> impl Iterator<Item = Der<'_>> {
+    std::iter::from_fn(move || read(&mut input))
+}
+
+fn oid_to_string(oid: &[u8]) -> String {
+    let mut arcs = Vec::new();
+    let mut value: u64 = 0;
+    for byte in oid {
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter().map(|arc| arc.to_string()).collect::<Vec<_>>().join(".")
+}
+
+// Short names OpenSSL and RFC 4514 use for common attributes
+fn attribute_name(oid: &[u8]) -> String {
+    let oid = oid_to_string(oid);
+    let name = match oid.as_str() {
+        "2.5.4.3" => "CN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.9" => "STREET",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "0.9.2342.19200300.100.1.1" => "UID",
+        "0.9.2342.19200300.100.1.25" => "DC",
+        "1.2.840.113549.1.9.1" => "emailAddress",
+        _ => return oid,
+    };
+    name.to_string()
+}
+
+fn string_value(value: &Der) -> String {
+    if value.tag == BMP_STRING {
+        let units: Vec<u16> = value.contents.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    String::from_utf8_lossy(value.contents).into_owned()
+}
+
+// Escape an attribute value as RFC 4514 requires
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, ch) in value.chars().enumerate() {
+        let edge = (i == 0 && (ch == '#' || ch == ' ')) || (i == value.chars().count() - 1 && ch == ' ');
+        if edge || matches!(ch, '"' | '+' | ',' | ';' | '<' | '>' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+// Distinguished name in RFC 4514 form: last RDN first, multi-valued RDNs joined with '+'
+fn distinguished_name(name: &[u8]) -> String {
+    let rdns: Vec<String> = elements(name)
+        .filter(|rdn| rdn.tag == SET)
+        .map(|rdn| {
+            elements(rdn.contents)
+                .filter_map(|attribute| {
+                    let mut parts = elements(attribute.contents);
+                    let oid = parts.next().filter(|oid| oid.tag == OID)?;
+                    let value = parts.next()?;
+                    Some(format!("{}={}", attribute_name(oid.contents), escape(&string_value(&value))))
+                })
+                .collect::<Vec<_>>()
+                .join("+")
+        })
+        .collect();
+    rdns.into_iter().rev().collect::<Vec<_>>().join(",")
+}
+
+fn general_name(name: &Der) -> Option<String> {
+    let text = || String::from_utf8_lossy(name.contents).into_owned();
+    match name.tag {
+        0x81 => Some(format!("email:{}", text())),
+        0x82 => Some(format!("DNS:{}", text())),
+        0x86 => Some(format!("URI:{}", text())),
+        0x87 => match name.contents.len() {
+            4 => Some(format!("IP:{}", Ipv4Addr::from(<[u8; 4]>::try_from(name.contents).ok()?))),
+            16 => Some(format!("IP:{}", Ipv6Addr::from(<[u8; 16]>::try_from(name.contents).ok()?))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn subject_alt_names(extensions: &[u8]) -> Vec<String> {
+    let Some(list) = elements(extensions).find(|list| list.tag == SEQUENCE) else {
+        return Vec::new();
+    };
+    for extension in elements(list.contents) {
+        let mut parts = elements(extension.contents);
+        if !parts.next().is_some_and(|oid| oid.tag == OID && oid.contents == SUBJECT_ALT_NAME) {
+            continue;
+        }
+        // The optional `critical` flag comes before the value
+        let Some(value) = parts.find(|part| part.tag == OCTET_STRING) else {
+            break;
+        };
+        return elements(value.contents)
+            .filter(|names| names.tag == SEQUENCE)
+            .flat_map(|names| elements(names.contents).filter_map(|name| general_name(&name)).collect::<Vec<_>>())
+            .collect();
+    }
+    Vec::new()
+}
+
+impl ClientCertificate {
+    /// Read the identity fields of a DER certificate
+    pub fn parse(der: &[u8]) -> Option<Self> {
+        let certificate = read(&mut &der[..]).filter(|certificate| certificate.tag == SEQUENCE)?;
+        let tbs = elements(certificate.contents).next().filter(|tbs| tbs.tag == SEQUENCE)?;
+
+        let mut fields = elements(tbs.contents).peekable();
+        if fields.peek()?.tag == VERSION {
+            fields.next();
+        }
+        let _serial = fields.next()?;
+        let _signature = fields.next()?;
+        let issuer = fields.next()?;
+        let _validity = fields.next()?;
+        let subject = fields.next()?;
+        let subject_alt_names = fields
+            .find(|field| field.tag == EXTENSIONS)
+            .map(|extensions| subject_alt_names(extensions.contents))
+            .unwrap_or_default();
+
+        let fingerprint = Sha256::digest(der).iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(":");
+
+        Some(Self {
+            subject: distinguished_name(subject.contents),
+            issuer: distinguished_name(issuer.contents),
+            subject_alt_names,
+            fingerprint,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_client_certificate() {
+        let pem = include_bytes!("../../../tests/fixtures/tls/client.crt");
+        let der = rustls_pemfile::certs(&mut &pem[..]).next().unwrap().unwrap();
+        let certificate = ClientCertificate::parse(&der).unwrap();
+
+        assert_eq!(certificate.subject, "CN=billing-service,OU=Billing,O=RNode Test");
+        assert_eq!(certificate.issuer, "CN=RNode Test Client CA,O=RNode Test");
+        assert_eq!(
+            certificate.subject_alt_names,
+            vec!["DNS:billing.internal", "URI:spiffe://example.test/billing", "IP:10.0.0.7"]
+        );
+        assert_eq!(
+            certificate.fingerprint,
+            "98:59:DD:F3:05:65:12:76:97:5E:92:28:77:1D:39:E9:5A:63:FE:7C:26:BA:E1:4E:68:43:69:4B:67:BB:16:76"
+        );
+        assert_eq!(escape("#a,b "), "\\#a\\,b\\ ");
+    }
+}
@@ -0,0 +1,244 @@
+use axum::extract::{ConnectInfo, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use ipnet::IpNet;
+use log::warn;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+// Networks behind the keywords accepted in `trustedProxies`
+const LOOPBACK: &[&str] = &["127.0.0.0/8", "::1/128"];
+const PRIVATE: &[&str] = &["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "fc00::/7"];
+const LINK_LOCAL: &[&str] = &["169.254.0.0/16", "fe80::/10"];
+
+// Reported when neither a peer nor headers give an address
+const DEFAULT_IP: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+// Single-address headers honored from a trusted peer without a forwarding chain
+const CLIENT_IP_HEADERS: &[&str] = &["cf-connecting-ip", "x-real-ip"];
+
+/// Proxies allowed to report the client address
+#[derive(Debug, Clone)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl Default for TrustedProxies {
+    // A reverse proxy on the same host
+    fn default() -> Self {
+        Self::new(&["loopback".to_string()])
+    }
+}
+
+impl TrustedProxies {
+    /// Parse CIDRs, plain addresses and the `loopback`, `private` and `linklocal` keywords
+    pub fn new(entries: &[String]) -> Self {
+        let mut networks = Vec::new();
+        for entry in entries {
+            let entry = entry.trim();
+            let keyword = match entry {
+                "loopback" => Some(LOOPBACK),
+                "private" => Some(PRIVATE),
+                "linklocal" => Some(LINK_LOCAL),
+                _ => None,
+            };
+            if let Some(cidrs) = keyword {
+                networks.extend(cidrs.iter().filter_map(|cidr| cidr.parse::<IpNet>().ok()));
+                continue;
+            }
+
+            match entry.parse::<IpNet>() {
+                Ok(network) => networks.push(network),
+                Err(_) => match entry.parse::<IpAddr>() {
+                    Ok(ip) => networks.push(IpNet::from(ip)),
+                    Err(_) => warn!("⚠️ Invalid trusted proxy '{}', ignoring", entry),
+                },
+            }
+        }
+        Self { networks }
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+}
+
+/// Client address resolved for a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientIp {
+    pub ip: String,
+    // Client first, then the proxies the request passed through
+    pub ips: Vec<String>,
+    // Where the address came from: socket, forwarded, x-forwarded-for, x-real-ip...
+    pub source: String,
+}
+
+impl ClientIp {
+    fn new(ip: IpAddr, chain: &[IpAddr], source: &str) -> Self {
+        Self {
+            ip: ip.to_string(),
+            ips: chain.iter().map(|ip| ip.to_string()).collect(),
+            source: source.to_string(),
+        }
+    }
+}
+
+// Address of a forwarding hop: "1.2.3.4", "1.2.3.4:80", "[::1]:80" or "::1", optionally quoted
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip.to_canonical());
+    }
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse::<IpAddr>().ok().map(|ip| ip.to_canonical());
+    }
+    node.parse::<SocketAddr>().ok().map(|addr| addr.ip().to_canonical())
+}
+
+// `for=` values of an RFC 7239 Forwarded header, client first
+fn forwarded_for(value: &str) -> Vec<Option<IpAddr>> {
+    value
+        .split(',')
+        .map(|element| {
+            element.split(';').find_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                name.trim().eq_ignore_ascii_case("for").then(|| parse_node(value))
+            })?
+        })
+        .collect()
+}
+
+/// Resolve the client address of a request.
+///
+/// Forwarding headers are only read when the socket peer is a trusted proxy
+/// (a missing peer, such as a Unix socket client, counts as trusted). The
+/// chain is walked right to left and the first hop that is not a trusted
+/// proxy is the client.
+pub fn resolve<'a>(
+    peer: Option<IpAddr>,
+    header: impl Fn(&str) -> Option<&'a str>,
+    trusted: &TrustedProxies,
+) -> ClientIp {
+    let peer = peer.map(|ip| ip.to_canonical());
+    if let Some(peer) = peer
+        && !trusted.contains(&peer)
+    {
+        return ClientIp::new(peer, &[peer], "socket");
+    }
+
+    // Forwarded supersedes the de facto X-Forwarded-For
+    let (hops, source) = if let Some(forwarded) = header("forwarded") {
+        (forwarded_for(forwarded), "forwarded")
+    } else if let Some(forwarded_for) = header("x-forwarded-for") {
+        (forwarded_for.split(',').map(parse_node).collect(), "x-forwarded-for")
+    } else {
+        let single = CLIENT_IP_HEADERS
+            .iter()
+            .find_map(|name| header(name).and_then(parse_node).map(|ip| (ip, *name)));
+        return match (single, peer) {
+            (Some((ip, name)), _) => {
+                let chain: Vec<IpAddr> = [Some(ip), peer].into_iter().flatten().collect();
+                ClientIp::new(ip, &chain, name)
+            }
+            (None, Some(peer)) => ClientIp::new(peer, &[peer], "socket"),
+            (None, None) => ClientIp::new(DEFAULT_IP, &[DEFAULT_IP], "default"),
+        };
+    };
+
+    let mut chain: Vec<IpAddr> = peer.into_iter().collect();
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        // An unparseable hop cannot be trusted: the hop that reported it is the client
+        let Some(hop) = hop else { break };
+        chain.push(*hop);
+        client = Some(*hop);
+        if !trusted.contains(hop) {
+            break;
+        }
+    }
+    chain.reverse();
+
+    match client {
+        Some(client) => ClientIp::new(client, &chain, source),
+        None => ClientIp::new(DEFAULT_IP, &[DEFAULT_IP], "default"),
+    }
+}
+
+/// Resolve the client address once and store it in the request extensions
+pub async fn client_ip_layer(trusted: Arc<TrustedProxies>, mut req: Request, next: Next) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let headers = req.headers();
+    let client = resolve(peer, |name| headers.get(name).and_then(|value| value.to_str().ok()), &trusted);
+    req.extensions_mut().insert(client);
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve_with(peer: &str, headers: &[(&str, &str)], trusted: &[&str]) -> ClientIp {
+        let headers: HashMap<&str, &str> = headers.iter().copied().collect();
+        let trusted = TrustedProxies::new(&trusted.iter().map(|entry| entry.to_string()).collect::<Vec<_>>());
+        resolve(peer.parse().ok(), |name| headers.get(name).copied(), &trusted)
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let client = resolve_with("203.0.113.9", &[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "1.2.3.4")], &["loopback"]);
+        assert_eq!(client.ip, "203.0.113.9");
+        assert_eq!(client.source, "socket");
+    }
+
+    #[test]
+    fn test_forwarded_for_walks_trusted_hops() {
+        // The client spoofs a first entry; the proxies append the real address
+        let client = resolve_with(
+            "10.0.0.2",
+            &[("x-forwarded-for", "6.6.6.6, 198.51.100.7, 10.0.0.1")],
+            &["10.0.0.0/8"],
+        );
+        assert_eq!(client.ip, "198.51.100.7");
+        assert_eq!(client.ips, vec!["198.51.100.7", "10.0.0.1", "10.0.0.2"]);
+        assert_eq!(client.source, "x-forwarded-for");
+
+        // Every hop trusted: the leftmost one is the client
+        let client = resolve_with("::ffff:127.0.0.1", &[("x-forwarded-for", "10.1.1.1")], &["loopback", "private"]);
+        assert_eq!(client.ip, "10.1.1.1");
+
+        // A garbage hop stops the walk at the proxy that reported it
+        let client = resolve_with("127.0.0.1", &[("x-forwarded-for", "1.2.3.4, unknown, 10.0.0.5")], &["loopback", "10.0.0.5"]);
+        assert_eq!(client.ip, "10.0.0.5");
+    }
+
+    #[test]
+    fn test_rfc7239_forwarded() {
+        let client = resolve_with(
+            "127.0.0.1",
+            &[
+                ("forwarded", r#"for=192.0.2.60;proto=http;by=203.0.113.43, for="[2001:db8:cafe::17]:4711""#),
+                ("x-forwarded-for", "6.6.6.6"),
+            ],
+            &["loopback"],
+        );
+        assert_eq!(client.ip, "2001:db8:cafe::17");
+        assert_eq!(client.source, "forwarded");
+    }
+
+    #[test]
+    fn test_single_headers_and_defaults() {
+        let client = resolve_with("127.0.0.1", &[("x-real-ip", "198.51.100.1")], &["loopback"]);
+        assert_eq!((client.ip.as_str(), client.source.as_str()), ("198.51.100.1", "x-real-ip"));
+
+        let client = resolve_with("127.0.0.1", &[], &["loopback"]);
+        assert_eq!((client.ip.as_str(), client.source.as_str()), ("127.0.0.1", "socket"));
+
+        // No peer (Unix socket) and no headers
+        let client = resolve_with("", &[], &[]);
+        assert_eq!(client.source, "default");
+    }
+}
@@ -10,8 +10,22 @@ use brotli::enc::BrotliEncoderParams;
 /// # Returns
 /// * `Option<Vec<u8>>` - Compressed data or None if compression failed
 pub fn compress_brotli(data: &[u8]) -> Option<Vec<u8>> {
-    let mut params = BrotliEncoderParams::default();
-    params.quality = 11; // Maximum quality
+    compress_brotli_level(data, 11) // Maximum quality
+}
+
+/// Compress data using Brotli with the given quality (0-11)
+/// 
+/// # Arguments
+/// * `data` - Raw data to compress
+/// * `quality` - Compression quality, higher is smaller and slower
+/// 
+/// # Returns
+/// * `Option<Vec<u8>>` - Compressed data or None if compression failed
+pub fn compress_brotli_level(data: &[u8], quality: i32) -> Option<Vec<u8>> {
+    let params = BrotliEncoderParams {
+        quality: quality.clamp(0, 11),
+        ..Default::default()
+    };
     
     let mut output = Vec::new();
     let mut input = Cursor::new(data);
@@ -0,0 +1,302 @@
+use axum::body::{Body, HttpBody};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use http::header::{self, HeaderMap, HeaderValue};
+use http::{Method, StatusCode};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::{compress_brotli_level, compress_gzip_level, compress_zstd_level};
+
+const DEFAULT_MIN_SIZE: u64 = 1024;
+
+// Text-like types worth compressing
+const DEFAULT_CONTENT_TYPES: &[&str] = &[
+    "text/*",
+    "application/json",
+    "application/*+json",
+    "application/javascript",
+    "application/xml",
+    "application/*+xml",
+    "image/svg+xml",
+];
+
+/// Content codings used for dynamic responses, in default preference order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Brotli,
+    Zstd,
+    Gzip,
+}
+
+impl Encoding {
+    const ALL: [Encoding; 3] = [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip];
+
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|encoding| encoding.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Levels per coding; the defaults favour speed over size
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CompressionLevels {
+    pub gzip: u32, // 0-9
+    pub br: i32, // 0-11
+    pub zstd: i32, // 1-22
+}
+
+impl Default for CompressionLevels {
+    fn default() -> Self {
+        Self { gzip: 6, br: 4, zstd: 3 }
+    }
+}
+
+/// `compression` option of listen()
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompressionOptions {
+    pub min_size: Option<u64>, // Smaller bodies are sent as is
+    pub encodings: Option<Vec<String>>, // Codings offered, in preference order
+    pub content_types: Option<Vec<String>>, // MIME globs, e.g. "text/*"
+    pub level: CompressionLevels,
+}
+
+/// Compiled compression configuration of a server
+#[derive(Debug)]
+pub struct CompressionConfig {
+    min_size: u64,
+    encodings: Vec<Encoding>,
+    content_types: GlobSet,
+    level: CompressionLevels,
+}
+
+impl CompressionConfig {
+    pub fn new(options: &CompressionOptions) -> Self {
+        let encodings = match &options.encodings {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| {
+                    let encoding = Encoding::from_name(name);
+                    if encoding.is_none() {
+                        warn!("⚠️ Unsupported compression encoding '{}'", name);
+                    }
+                    encoding
+                })
+                .collect(),
+            None => Encoding::ALL.to_vec(),
+        };
+
+        let mut builder = GlobSetBuilder::new();
+        match &options.content_types {
+            Some(types) => {
+                for pattern in types {
+                    match Glob::new(&pattern.to_ascii_lowercase()) {
+                        Ok(glob) => {
+                            builder.add(glob);
+                        }
+                        Err(e) => warn!("⚠️ Invalid compression content type '{}': {}", pattern, e),
+                    }
+                }
+            }
+            None => {
+                for pattern in DEFAULT_CONTENT_TYPES {
+                    builder.add(Glob::new(pattern).expect("valid default content type"));
+                }
+            }
+        }
+
+        Self {
+            min_size: options.min_size.unwrap_or(DEFAULT_MIN_SIZE),
+            encodings,
+            content_types: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+            level: options.level.clone(),
+        }
+    }
+
+    // Whether the response is a candidate for compression, whatever the client accepts
+    fn compressible(&self, response: &Response) -> bool {
+        let status = response.status();
+        if !status.is_success() || status == http::StatusCode::NO_CONTENT || status == http::StatusCode::PARTIAL_CONTENT {
+            return false;
+        }
+
+        let headers = response.headers();
+        if headers.contains_key(header::CONTENT_ENCODING) || headers.contains_key(header::CONTENT_RANGE) {
+            return false;
+        }
+        let no_transform = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.to_ascii_lowercase().contains("no-transform"));
+        if no_transform {
+            return false;
+        }
+
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or("").trim().to_ascii_lowercase());
+        if !content_type.is_some_and(|content_type| self.content_types.is_match(content_type)) {
+            return false;
+        }
+
+        // Only bodies of known size: streams, SSE and downloads pass through
+        response.body().size_hint().exact().is_some_and(|size| size >= self.min_size)
+    }
+
+    fn compress(&self, encoding: Encoding, data: &[u8]) -> Option<Vec<u8>> {
+        match encoding {
+            Encoding::Brotli => compress_brotli_level(data, self.level.br),
+            Encoding::Zstd => compress_zstd_level(data, self.level.zstd),
+            Encoding::Gzip => compress_gzip_level(data, self.level.gzip),
+        }
+    }
+}
+
+/// Pick the coding for an Accept-Encoding header: highest q-value, ties go to the server's order
+pub fn negotiate(accept_encoding: &str, offered: &[Encoding]) -> Option<Encoding> {
+    let accepted: Vec<(&str, f32)> = accept_encoding
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let name = parts.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((name, q))
+        })
+        .collect();
+
+    let quality = |encoding: Encoding| {
+        accepted
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(encoding.name()))
+            .or_else(|| accepted.iter().find(|(name, _)| *name == "*"))
+            .map(|(_, q)| *q)
+            .unwrap_or(0.0)
+    };
+
+    let mut best: Option<(Encoding, f32)> = None;
+    for &encoding in offered {
+        let q = quality(encoding);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((encoding, q));
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+// Responses that vary by Accept-Encoding must say so to caches
+fn add_vary(headers: &mut HeaderMap) {
+    let varies = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| {
+            let value = value.trim();
+            value == "*" || value.eq_ignore_ascii_case("accept-encoding")
+        });
+    if !varies {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+/// Compress handler and template responses on the fly for clients that accept it
+pub async fn compression_layer(config: Arc<CompressionConfig>, req: Request, next: Next) -> Response {
+    let accept_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let is_head = req.method() == Method::HEAD;
+
+    let mut response = next.run(req).await;
+    if is_head || !config.compressible(&response) {
+        return response;
+    }
+    add_vary(response.headers_mut());
+
+    let Some(encoding) = accept_encoding.and_then(|accept| negotiate(&accept, &config.encodings)) else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // The body is gone; sending it empty under the old Content-Length would break the connection
+            warn!("⚠️ Failed to read response body for compression: {}", e);
+            return crate::html_templates::generate_error_page(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "The response body could not be read.",
+                None,
+                false,
+            );
+        }
+    };
+
+    let task_config = config.clone();
+    let data = bytes.clone();
+    let compressed = tokio::task::spawn_blocking(move || task_config.compress(encoding, &data))
+        .await
+        .ok()
+        .flatten();
+
+    match compressed {
+        Some(compressed) if compressed.len() < bytes.len() => {
+            debug!(
+                "🗜️  {} compression: {} -> {} bytes",
+                encoding.name(),
+                bytes.len(),
+                compressed.len()
+            );
+            parts.headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding.name()));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.remove(header::ACCEPT_RANGES);
+            // The compressed body is a different representation: a strong ETag becomes weak
+            if let Some(etag) = parts.headers.get(header::ETAG).and_then(|value| value.to_str().ok())
+                && !etag.starts_with("W/")
+                && let Ok(weak) = HeaderValue::from_str(&format!("W/{}", etag))
+            {
+                parts.headers.insert(header::ETAG, weak);
+            }
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        _ => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        let offered = Encoding::ALL;
+        assert_eq!(negotiate("gzip, deflate, br", &offered), Some(Encoding::Brotli));
+        assert_eq!(negotiate("gzip;q=1.0, br;q=0.5", &offered), Some(Encoding::Gzip));
+        assert_eq!(negotiate("br;q=0, gzip", &offered), Some(Encoding::Gzip));
+        assert_eq!(negotiate("*", &[Encoding::Gzip]), Some(Encoding::Gzip));
+        assert_eq!(negotiate("identity", &offered), None);
+        assert_eq!(negotiate("deflate, br", &[Encoding::Zstd, Encoding::Gzip]), None);
+    }
+}
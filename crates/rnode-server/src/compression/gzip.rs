@@ -10,7 +10,19 @@ use flate2::write::GzEncoder;
 /// # Returns
 /// * `Option<Vec<u8>>` - Compressed data or None if compression failed
 pub fn compress_gzip(data: &[u8]) -> Option<Vec<u8>> {
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    compress_gzip_level(data, Compression::default().level())
+}
+
+/// Compress data using Gzip with the given level (0-9)
+/// 
+/// # Arguments
+/// * `data` - Raw data to compress
+/// * `level` - Compression level, higher is smaller and slower
+/// 
+/// # Returns
+/// * `Option<Vec<u8>>` - Compressed data or None if compression failed
+pub fn compress_gzip_level(data: &[u8], level: u32) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level.min(9)));
     
     // Write data to encoder
     if encoder.write_all(data).is_err() {
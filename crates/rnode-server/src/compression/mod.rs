@@ -1,15 +1,17 @@
 //! Compression module for RNode Server
 //!
 //! This module provides various compression algorithms for static files and data.
-//! It includes Gzip, Brotli, Zstandard, and LZ4 compression methods.
+//! It includes Gzip, Brotli, Zstandard, and LZ4 compression methods, and the
+//! layer compressing dynamic responses on the fly.
 
+pub mod dynamic;
 pub mod gzip;
 pub mod brotli;
 pub mod zstd;
 pub mod lz4;
 
 // Re-export public functions for backward compatibility
-pub use gzip::compress_gzip;
-pub use brotli::compress_brotli;
-pub use zstd::compress_zstd;
+pub use gzip::{compress_gzip, compress_gzip_level};
+pub use brotli::{compress_brotli, compress_brotli_level};
+pub use zstd::{compress_zstd, compress_zstd_level};
 pub use lz4::compress_lz4;
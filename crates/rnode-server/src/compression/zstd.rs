@@ -9,7 +9,19 @@ use zstd::stream::Encoder;
 /// # Returns
 /// * `Option<Vec<u8>>` - Compressed data or None if compression failed
 pub fn compress_zstd(data: &[u8]) -> Option<Vec<u8>> {
-    let mut encoder = match Encoder::new(Vec::new(), 3) { // Level 3 for good balance
+    compress_zstd_level(data, 3) // Level 3 for good balance
+}
+
+/// Compress data using Zstandard with the given level (1-22)
+/// 
+/// # Arguments
+/// * `data` - Raw data to compress
+/// * `level` - Compression level, higher is smaller and slower
+/// 
+/// # Returns
+/// * `Option<Vec<u8>>` - Compressed data or None if compression failed
+pub fn compress_zstd_level(data: &[u8], level: i32) -> Option<Vec<u8>> {
+    let mut encoder = match Encoder::new(Vec::new(), level.clamp(1, 22)) {
         Ok(encoder) => encoder,
         Err(_) => return None,
     };
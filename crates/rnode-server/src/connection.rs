@@ -0,0 +1,371 @@
+use crate::client_cert::ClientCertificate;
+use crate::metrics::http::{add_pending_requests, record_connection_closed, record_connection_opened, record_connection_rejected};
+use crate::tls::TlsConnection;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::serve::Listener;
+use hyper::body::{Body as HttpBody, Bytes, Frame, Incoming, SizeHint};
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use log::debug;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_rustls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tower::ServiceExt;
+
+const DEFAULT_HEADER_READ_TIMEOUT_MS: u64 = 30000;
+const DEFAULT_IDLE_TIMEOUT_MS: u64 = 60000;
+// Connections over the limit are answered by at most this many tasks at once, the rest dropped
+const MAX_PENDING_REJECTIONS: usize = 64;
+const REJECT_TIMEOUT: Duration = Duration::from_secs(1);
+const SERVICE_UNAVAILABLE: &[u8] = b"HTTP/1.1 503 Service Unavailable\r\nContent-Type: text/plain; charset=utf-8\r\n\
+Content-Length: 19\r\nConnection: close\r\nRetry-After: 1\r\n\r\nService Unavailable";
+
+/// Connection-level limits of the TCP listeners, from listen() options
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionOptions {
+    pub max_connections: Option<usize>, // Over the limit, connections get a 503 and are closed
+    pub header_read_timeout_ms: Option<u64>, // TLS handshake and request head must arrive within it
+    pub idle_timeout_ms: Option<u64>, // Close connections without a request in progress for this long; 0 disables
+    pub max_header_size: Option<usize>, // Bytes of request head; HTTP/1.1 answers 431 beyond it
+    pub max_headers: Option<usize>, // Header count of HTTP/1.1 requests
+}
+
+/// Open connections of a server, shared by its listeners
+#[derive(Debug)]
+pub struct ConnectionLimits {
+    options: ConnectionOptions,
+    active: AtomicUsize,
+    rejecting: AtomicUsize,
+}
+
+impl ConnectionLimits {
+    pub fn new(options: ConnectionOptions) -> Self {
+        Self { options, active: AtomicUsize::new(0), rejecting: AtomicUsize::new(0) }
+    }
+
+    fn header_read_timeout(&self) -> Duration {
+        Duration::from_millis(self.options.header_read_timeout_ms.unwrap_or(DEFAULT_HEADER_READ_TIMEOUT_MS))
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        match self.options.idle_timeout_ms.unwrap_or(DEFAULT_IDLE_TIMEOUT_MS) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    // Count the connection as open unless the limit is reached
+    fn try_open(self: &Arc<Self>) -> Option<ConnectionGuard> {
+        let max = self.options.max_connections.unwrap_or(usize::MAX);
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| (active < max).then_some(active + 1))
+            .ok()?;
+        record_connection_opened();
+        Some(ConnectionGuard { limits: self.clone() })
+    }
+
+    // Reserve one of the rejection slots
+    fn try_reject(&self) -> bool {
+        self.rejecting
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |rejecting| {
+                (rejecting < MAX_PENDING_REJECTIONS).then_some(rejecting + 1)
+            })
+            .is_ok()
+    }
+
+    /// Apply the header limits to a connection builder
+    pub fn configure(&self, builder: &mut Builder<TokioExecutor>) {
+        let mut http1 = builder.http1();
+        http1.timer(TokioTimer::new()).header_read_timeout(self.header_read_timeout());
+        if let Some(size) = self.options.max_header_size {
+            // hyper's read buffer holds the request head; it cannot be smaller than 8 KiB
+            http1.max_buf_size(size.max(8192));
+        }
+        if let Some(count) = self.options.max_headers {
+            http1.max_headers(count);
+        }
+        if let Some(size) = self.options.max_header_size {
+            builder.http2().max_header_list_size(size as u32);
+        }
+    }
+}
+
+struct ConnectionGuard {
+    limits: Arc<ConnectionLimits>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.limits.active.fetch_sub(1, Ordering::SeqCst);
+        record_connection_closed();
+    }
+}
+
+/// Requests in progress on one connection, for the idle timeout
+struct ConnectionActivity {
+    started: Instant,
+    in_flight: AtomicUsize,
+    last_active_ms: AtomicU64,
+}
+
+impl ConnectionActivity {
+    fn new() -> Self {
+        Self { started: Instant::now(), in_flight: AtomicUsize::new(0), last_active_ms: AtomicU64::new(0) }
+    }
+
+    fn touch(&self) {
+        self.last_active_ms.store(self.started.elapsed().as_millis() as u64, Ordering::SeqCst);
+    }
+
+    fn request_started(self: &Arc<Self>) -> RequestGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        add_pending_requests(1);
+        RequestGuard { activity: self.clone() }
+    }
+
+    // Resolves once the connection has had no request in progress for `timeout`
+    async fn idle(&self, timeout: Duration) {
+        loop {
+            let last_active = self.started + Duration::from_millis(self.last_active_ms.load(Ordering::SeqCst));
+            tokio::time::sleep_until((last_active + timeout).into()).await;
+            if self.in_flight.load(Ordering::SeqCst) > 0 {
+                // Finishing the request moves the deadline; check again a period later
+                tokio::time::sleep(timeout).await;
+            } else if last_active.elapsed() >= timeout {
+                return;
+            }
+        }
+    }
+}
+
+// Held until the response body is fully sent
+struct RequestGuard {
+    activity: Arc<ConnectionActivity>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.activity.touch();
+        self.activity.in_flight.fetch_sub(1, Ordering::SeqCst);
+        add_pending_requests(-1);
+    }
+}
+
+/// Response body keeping its request counted as in progress while it streams
+struct TrackedBody {
+    inner: Body,
+    _guard: RequestGuard,
+}
+
+impl HttpBody for TrackedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Address a connection was accepted from
+pub trait PeerAddr: std::fmt::Debug + Send + 'static {
+    // IP address for the client IP layer; None for Unix sockets
+    fn socket_addr(&self) -> Option<SocketAddr>;
+}
+
+impl PeerAddr for SocketAddr {
+    fn socket_addr(&self) -> Option<SocketAddr> {
+        Some(*self)
+    }
+}
+
+#[cfg(unix)]
+impl PeerAddr for tokio::net::unix::SocketAddr {
+    fn socket_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// Serve connections with hyper's protocol-detecting builder: HTTP/1.1 and, if the builder
+/// allows it, HTTP/2 (via ALPN over TLS, or with prior knowledge on plain listeners)
+pub async fn serve<L>(
+    mut listener: L,
+    app: Router,
+    builder: Builder<TokioExecutor>,
+    tls: Option<TlsAcceptor>,
+    limits: Arc<ConnectionLimits>,
+    token: CancellationToken,
+) where
+    L: Listener,
+    L::Addr: PeerAddr,
+{
+    let builder = Arc::new(builder);
+    let tracker = TaskTracker::new();
+
+    loop {
+        // Accept errors are logged and backed off by the listener
+        let (io, peer) = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = token.cancelled() => break,
+        };
+
+        let Some(guard) = limits.try_open() else {
+            // Every refused connection counts, answered with a 503 or dropped
+            record_connection_rejected();
+            // A TLS handshake costs too much for a connection we refuse anyway; the client sees a reset
+            if tls.is_some() || !limits.try_reject() {
+                debug!("🚫 Connection limit reached, dropping {:?}", peer);
+                continue;
+            }
+            debug!("🚫 Connection limit reached, rejecting {:?}", peer);
+            let limits = limits.clone();
+            tracker.spawn(async move {
+                reject(io).await;
+                limits.rejecting.fetch_sub(1, Ordering::SeqCst);
+            });
+            continue;
+        };
+
+        let handshake_timeout = limits.header_read_timeout();
+        let connection = Connection {
+            app: app.clone(),
+            builder: builder.clone(),
+            remote_addr: peer.socket_addr(),
+            peer: format!("{:?}", peer),
+            limits: limits.clone(),
+            token: token.clone(),
+            _guard: guard,
+        };
+        let tls = tls.clone();
+        tracker.spawn(async move {
+            match tls {
+                Some(acceptor) => {
+                    // A client that stalls the handshake is dropped like one that stalls its headers
+                    let handshake = tokio::time::timeout(handshake_timeout, acceptor.accept(io));
+                    match handshake.await {
+                        Ok(Ok(stream)) => {
+                            let client_cert = stream
+                                .get_ref()
+                                .1
+                                .peer_certificates()
+                                .and_then(|chain| chain.first())
+                                .and_then(|der| ClientCertificate::parse(der))
+                                .map(Arc::new);
+                            connection.serve(stream, Some(TlsSession { client_cert })).await;
+                        }
+                        Ok(Err(e)) => debug!("🔒 TLS handshake with {:?} failed: {}", peer, e),
+                        Err(_) => debug!("⏰ TLS handshake with {:?} timed out", peer),
+                    }
+                }
+                None => connection.serve(io, None).await,
+            }
+        });
+    }
+
+    // Stop accepting, then let open connections finish their requests
+    drop(listener);
+    tracker.close();
+    tracker.wait().await;
+}
+
+// Per-connection TLS state
+#[derive(Clone)]
+struct TlsSession {
+    client_cert: Option<Arc<ClientCertificate>>,
+}
+
+// One accepted connection within the limits
+struct Connection {
+    app: Router,
+    builder: Arc<Builder<TokioExecutor>>,
+    remote_addr: Option<SocketAddr>,
+    peer: String, // For logs
+    limits: Arc<ConnectionLimits>,
+    token: CancellationToken,
+    _guard: ConnectionGuard,
+}
+
+impl Connection {
+    async fn serve<I>(self, io: I, tls: Option<TlsSession>)
+    where
+        I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let Connection { app, builder, remote_addr, peer, limits, token, _guard } = self;
+        let activity = Arc::new(ConnectionActivity::new());
+
+        let request_activity = activity.clone();
+        let service = tower::service_fn(move |mut req: http::Request<Incoming>| {
+            let guard = request_activity.request_started();
+            // What into_make_service_with_connect_info provides to the client IP layer
+            if let Some(remote_addr) = remote_addr {
+                req.extensions_mut().insert(ConnectInfo(remote_addr));
+            }
+            if let Some(tls) = &tls {
+                req.extensions_mut().insert(TlsConnection);
+                if let Some(client_cert) = &tls.client_cert {
+                    req.extensions_mut().insert(client_cert.clone());
+                }
+            }
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(req).await?;
+                Ok::<_, std::convert::Infallible>(response.map(|body| Body::new(TrackedBody { inner: body, _guard: guard })))
+            }
+        });
+
+        let connection = builder.serve_connection_with_upgrades(TokioIo::new(io), TowerToHyperService::new(service));
+        let mut connection = std::pin::pin!(connection);
+        let idle_timeout = limits.idle_timeout();
+        let mut closing = false;
+
+        loop {
+            tokio::select! {
+                result = connection.as_mut() => {
+                    if let Err(e) = result {
+                        debug!("🔌 Connection from {} closed with error: {}", peer, e);
+                    }
+                    break;
+                }
+                _ = activity.idle(idle_timeout.unwrap_or_default()), if idle_timeout.is_some() && !closing => {
+                    debug!("💤 Closing idle connection from {}", peer);
+                    connection.as_mut().graceful_shutdown();
+                    closing = true;
+                }
+                _ = token.cancelled(), if !closing => {
+                    connection.as_mut().graceful_shutdown();
+                    closing = true;
+                }
+            }
+        }
+    }
+}
+
+// Answer a connection over the limit with a fixed 503 and close it, without parsing the request
+async fn reject<I: AsyncRead + AsyncWrite + Unpin>(mut io: I) {
+    let _ = tokio::time::timeout(REJECT_TIMEOUT, async move {
+        io.write_all(SERVICE_UNAVAILABLE).await?;
+        io.shutdown().await?;
+        // Drain the request so closing does not reset the connection before the client reads the 503
+        let mut buf = [0u8; 1024];
+        while io.read(&mut buf).await? > 0 {}
+        Ok::<_, std::io::Error>(())
+    })
+    .await;
+}
@@ -0,0 +1,292 @@
+use crate::metrics::http::record_cors_rejected;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use globset::{Glob, GlobMatcher};
+use http::header::{self, HeaderMap, HeaderValue};
+use http::{Method, StatusCode};
+use log::{debug, warn};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const DEFAULT_METHODS: &str = "GET, HEAD, POST, PUT, DELETE, PATCH, OPTIONS";
+
+/// `cors` option of listen(); route entries override the top-level fields
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CorsOptions {
+    pub origins: Option<Vec<String>>, // Exact origins, "*" allows any
+    pub origin_patterns: Option<Vec<String>>, // Regular expressions matched against the origin
+    pub credentials: Option<bool>,
+    pub methods: Option<Vec<String>>,
+    pub allowed_headers: Option<Vec<String>>, // Defaults to the headers the preflight asks for
+    pub exposed_headers: Option<Vec<String>>,
+    pub max_age: Option<u64>, // Preflight cache lifetime in seconds
+    pub routes: HashMap<String, CorsOptions>, // Overrides by path glob
+}
+
+// Origins a policy accepts
+#[derive(Debug)]
+enum AllowedOrigins {
+    Any,
+    List { exact: Vec<String>, patterns: Vec<Regex> },
+}
+
+// Route list if set, otherwise the base list, as a header value
+fn joined(route: &Option<Vec<String>>, base: &Option<Vec<String>>) -> Option<String> {
+    route.as_ref().or(base.as_ref()).map(|values| values.join(", "))
+}
+
+/// CORS rules applied to a set of paths
+#[derive(Debug)]
+pub struct CorsPolicy {
+    origins: AllowedOrigins,
+    credentials: bool,
+    methods: String,
+    allowed_headers: Option<String>,
+    exposed_headers: Option<String>,
+    max_age: Option<u64>,
+}
+
+impl CorsPolicy {
+    // Build a policy from route options falling back to the top-level ones
+    fn new(options: &CorsOptions, base: &CorsOptions) -> Self {
+        // A route listing origins replaces both origin lists of the base
+        let (origins, patterns) = if options.origins.is_some() || options.origin_patterns.is_some() {
+            (options.origins.clone(), options.origin_patterns.clone())
+        } else {
+            (base.origins.clone(), base.origin_patterns.clone())
+        };
+        let origins = origins.unwrap_or_default();
+
+        let origins = if origins.iter().any(|origin| origin == "*") {
+            AllowedOrigins::Any
+        } else {
+            let patterns = patterns
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        warn!("⚠️ Invalid CORS origin pattern '{}': {}", pattern, e);
+                        None
+                    }
+                })
+                .collect();
+            AllowedOrigins::List { exact: origins, patterns }
+        };
+
+        Self {
+            origins,
+            credentials: options.credentials.or(base.credentials).unwrap_or(false),
+            methods: joined(&options.methods, &base.methods).unwrap_or_else(|| DEFAULT_METHODS.to_string()),
+            allowed_headers: joined(&options.allowed_headers, &base.allowed_headers),
+            exposed_headers: joined(&options.exposed_headers, &base.exposed_headers),
+            max_age: options.max_age.or(base.max_age),
+        }
+    }
+
+    /// Whether requests from `origin` may read responses
+    pub fn allows(&self, origin: &str) -> bool {
+        match &self.origins {
+            AllowedOrigins::Any => true,
+            AllowedOrigins::List { exact, patterns } => {
+                exact.iter().any(|allowed| allowed == origin)
+                    || patterns.iter().any(|pattern| pattern.is_match(origin))
+            }
+        }
+    }
+
+    // Headers shared by preflight and actual responses
+    fn apply_origin(&self, headers: &mut HeaderMap, origin: &str) {
+        // "*" cannot be combined with credentials, so echo the origin then
+        let allow_origin = match self.origins {
+            AllowedOrigins::Any if !self.credentials => HeaderValue::from_static("*"),
+            _ => match HeaderValue::from_str(origin) {
+                Ok(value) => value,
+                Err(_) => return,
+            },
+        };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.credentials {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+    }
+
+    // Whether responses differ by Origin, i.e. anything but a fixed "*"
+    fn varies_by_origin(&self) -> bool {
+        !matches!(self.origins, AllowedOrigins::Any) || self.credentials
+    }
+
+    fn apply_preflight(&self, headers: &mut HeaderMap, origin: &str, requested_headers: Option<HeaderValue>) {
+        self.apply_origin(headers, origin);
+        if let Ok(methods) = HeaderValue::from_str(&self.methods) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+        let allowed_headers = match &self.allowed_headers {
+            Some(allowed) => HeaderValue::from_str(allowed).ok(),
+            None => requested_headers,
+        };
+        if let Some(allowed_headers) = allowed_headers {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+        }
+        headers.append(header::VARY, HeaderValue::from_static("Access-Control-Request-Headers"));
+    }
+
+    fn apply_actual(&self, headers: &mut HeaderMap, origin: &str) {
+        self.apply_origin(headers, origin);
+        if let Some(exposed) = self.exposed_headers.as_deref().and_then(|exposed| HeaderValue::from_str(exposed).ok()) {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, exposed);
+        }
+    }
+}
+
+/// Compiled CORS configuration of a server
+#[derive(Debug)]
+pub struct CorsConfig {
+    default: CorsPolicy,
+    // Route overrides, most specific pattern first
+    routes: Vec<(GlobMatcher, CorsPolicy)>,
+}
+
+impl CorsConfig {
+    pub fn new(options: &CorsOptions) -> Self {
+        let mut routes: Vec<(String, GlobMatcher, CorsPolicy)> = options
+            .routes
+            .iter()
+            .filter_map(|(pattern, route)| match Glob::new(pattern) {
+                Ok(glob) => Some((pattern.clone(), glob.compile_matcher(), CorsPolicy::new(route, options))),
+                Err(e) => {
+                    warn!("⚠️ Invalid CORS route pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        routes.sort_by_key(|(pattern, _, _)| std::cmp::Reverse(pattern.len()));
+
+        Self {
+            default: CorsPolicy::new(options, options),
+            routes: routes.into_iter().map(|(_, matcher, policy)| (matcher, policy)).collect(),
+        }
+    }
+
+    /// Policy for a request path
+    pub fn policy_for(&self, path: &str) -> &CorsPolicy {
+        self.routes
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(path))
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Answer preflights and add CORS headers to responses, without calling into JS
+pub async fn cors_layer(config: Arc<CorsConfig>, req: Request, next: Next) -> Response {
+    let policy = config.policy_for(req.uri().path());
+    let mut response = cors_response(policy, req, next).await;
+    // Shared caches must not serve one origin's answer to another, allowed or not
+    if policy.varies_by_origin() {
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("Origin"));
+    }
+    response
+}
+
+async fn cors_response(policy: &CorsPolicy, req: Request, next: Next) -> Response {
+    let Some(origin) = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+    else {
+        return next.run(req).await;
+    };
+
+    let preflight = req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+
+    if !policy.allows(&origin) {
+        debug!("🚫 CORS origin rejected: {} for {}", origin, req.uri().path());
+        record_cors_rejected();
+        if preflight {
+            return Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Body::empty())
+                .unwrap();
+        }
+        // The browser hides the response without CORS headers
+        return next.run(req).await;
+    }
+
+    if preflight {
+        let requested_headers = req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS).cloned();
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap();
+        policy.apply_preflight(response.headers_mut(), &origin, requested_headers);
+        return response;
+    }
+
+    let mut response = next.run(req).await;
+    policy.apply_actual(response.headers_mut(), &origin);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(json: &str) -> CorsOptions {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_origin_matching() {
+        let config = CorsConfig::new(&options(
+            r#"{"origins": ["https://app.example.com"], "originPatterns": ["^https://[a-z]+\\.preview\\.example\\.com$"]}"#,
+        ));
+        let policy = config.policy_for("/api/users");
+        assert!(policy.allows("https://app.example.com"));
+        assert!(policy.allows("https://pr.preview.example.com"));
+        assert!(!policy.allows("https://evil.com"));
+        assert!(!policy.allows("https://app.example.com.evil.com"));
+    }
+
+    #[test]
+    fn test_route_overrides() {
+        let config = CorsConfig::new(&options(
+            r#"{
+                "origins": ["https://app.example.com"],
+                "credentials": true,
+                "maxAge": 600,
+                "routes": {
+                    "/public/*": {"origins": ["*"], "credentials": false},
+                    "/public/admin/*": {"methods": ["GET"]}
+                }
+            }"#,
+        ));
+
+        let public = config.policy_for("/public/feed");
+        assert!(public.allows("https://anyone.com"));
+        assert!(!public.credentials);
+        assert!(!public.varies_by_origin());
+        assert!(config.policy_for("/api").varies_by_origin());
+        assert_eq!(public.max_age, Some(600));
+
+        // The longer pattern wins and inherits top-level origins
+        let admin = config.policy_for("/public/admin/stats");
+        assert!(!admin.allows("https://anyone.com"));
+        assert_eq!(admin.methods, "GET");
+
+        let mut headers = HeaderMap::new();
+        config.policy_for("/api").apply_actual(&mut headers, "https://app.example.com");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+}
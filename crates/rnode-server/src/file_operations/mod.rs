@@ -94,14 +94,16 @@ pub fn download_file(mut cx: FunctionContext) -> JsResult<JsString> {
 pub fn register_download_route(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let path = cx.argument::<JsString>(0)?.value(&mut cx);
     let options_json = cx.argument::<JsString>(1)?.value(&mut cx);
+    let app = crate::app::app_from_arg(&mut cx, 2)?;
 
-    register_download_route_impl(&path, &options_json, cx)
+    register_download_route_impl(&app, &path, &options_json, cx)
 }
 
 // Function for registering file upload route (single or multiple)
 pub fn register_upload_route(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let path = cx.argument::<JsString>(0)?.value(&mut cx);
     let options_json = cx.argument::<JsString>(1)?.value(&mut cx);
+    let app = crate::app::app_from_arg(&mut cx, 2)?;
 
-    register_upload_route_impl(&path, &options_json, cx)
+    register_upload_route_impl(&app, &path, &options_json, cx)
 }
@@ -1,14 +1,22 @@
-use crate::types::{DownloadRouteConfig, UploadRouteConfig, get_download_routes, get_upload_routes};
+use crate::app::App;
+use crate::route_table::{RouteEntry, RouteSource, check_route};
+use crate::types::{DownloadRouteConfig, UploadRouteConfig};
+use std::sync::Arc;
 use log::{debug, info};
 use neon::prelude::*;
 use serde_json;
 
 // Function for registering file download route implementation
 pub fn register_download_route_impl<'a>(
+    app: &Arc<App>,
     path: &str,
     options_json: &str,
     mut _cx: FunctionContext<'a>,
 ) -> JsResult<'a, JsUndefined> {
+    if let Err(conflict) = check_route(app, RouteEntry::new(RouteSource::Download, path, Some("GET"))) {
+        return _cx.throw_error(conflict);
+    }
+
     // Parse options
     if let Ok(options) = serde_json::from_str::<serde_json::Value>(options_json) {
         let folder = options["folder"]
@@ -46,8 +54,8 @@ pub fn register_download_route_impl<'a>(
             allow_system_files: allow_system,
         };
 
-        // Save to global storage
-        let download_routes = get_download_routes();
+        // Save to the app's storage
+        let download_routes = &app.download_routes;
         download_routes
             .write()
             .unwrap()
@@ -68,15 +76,23 @@ pub fn register_download_route_impl<'a>(
         }
     }
 
+    // Apply to the running server
+    crate::route_table::rebuild(app);
+
     Ok(_cx.undefined())
 }
 
 // Function for registering file upload route implementation
 pub fn register_upload_route_impl<'a>(
+    app: &Arc<App>,
     path: &str,
     options_json: &str,
     mut _cx: FunctionContext<'a>,
 ) -> JsResult<'a, JsUndefined> {
+    if let Err(conflict) = check_route(app, RouteEntry::new(RouteSource::Upload, path, Some("POST"))) {
+        return _cx.throw_error(conflict);
+    }
+
     // Parse options
     if let Ok(options) = serde_json::from_str::<serde_json::Value>(options_json) {
         let folder = options["folder"]
@@ -123,8 +139,8 @@ pub fn register_upload_route_impl<'a>(
             overwrite,
         };
 
-        // Save to global storage
-        let upload_routes = get_upload_routes();
+        // Save to the app's storage
+        let upload_routes = &app.upload_routes;
         upload_routes.write().unwrap().insert(path.to_string(), config);
 
         info!("📤 Registering upload route: {} -> {}", path, folder);
@@ -144,5 +160,8 @@ pub fn register_upload_route_impl<'a>(
         }
     }
 
+    // Apply to the running server
+    crate::route_table::rebuild(app);
+
     Ok(_cx.undefined())
 }
@@ -0,0 +1,90 @@
+use crate::metrics::http::record_request_cancelled;
+use crate::request::Request;
+use log::{debug, warn};
+use neon::event::Channel;
+use neon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_CANCEL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Id under which JS keeps the AbortController of a request
+pub fn next_cancel_id() -> u64 {
+    NEXT_CANCEL_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Why Rust stopped waiting for a JS call
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CancelReason {
+    Timeout,
+    ClientDisconnected,
+}
+
+impl CancelReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CancelReason::Timeout => "timeout",
+            CancelReason::ClientDisconnected => "client_disconnected",
+        }
+    }
+}
+
+/// Request whose JS side is aborted if Rust gives up on it
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    pub id: u64,
+    pub route: String, // Metric label: the registered path
+}
+
+impl Cancellation {
+    pub fn for_request(request: &Request) -> Self {
+        Self { id: request.cancel_id, route: request.registered_path.clone() }
+    }
+}
+
+/// Aborts the request's AbortSignal in JS when dropped while armed.
+/// A future dropped mid-call means hyper dropped it: the client went away
+pub struct CancelGuard {
+    channel: Channel,
+    cancellation: Cancellation,
+    reason: Option<CancelReason>,
+}
+
+impl CancelGuard {
+    pub fn new(channel: Channel, cancellation: Cancellation) -> Self {
+        Self { channel, cancellation, reason: Some(CancelReason::ClientDisconnected) }
+    }
+
+    // The call settled, nothing to abort
+    pub fn disarm(&mut self) {
+        self.reason = None;
+    }
+
+    pub fn timed_out(&mut self) {
+        self.reason = Some(CancelReason::Timeout);
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        let Some(reason) = self.reason else {
+            return;
+        };
+        let id = self.cancellation.id;
+        debug!("🛑 Aborting request {} ({}): {}", id, self.cancellation.route, reason.as_str());
+        record_request_cancelled(&self.cancellation.route, reason.as_str());
+
+        let _join_handle = self.channel.send(move |mut cx| {
+            let global = cx.global_object();
+            let abort_request: Handle<JsFunction> = match global.get_opt(&mut cx, "abortRequest")? {
+                Some(function) => function,
+                None => {
+                    warn!("⚠️ abortRequest is not defined in JS");
+                    return Ok(());
+                }
+            };
+            let id = cx.number(id as f64);
+            let reason = cx.string(reason.as_str());
+            abort_request.call_with(&cx).arg(id).arg(reason).exec(&mut cx)
+        });
+    }
+}
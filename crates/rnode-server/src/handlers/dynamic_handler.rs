@@ -1,8 +1,13 @@
+use crate::app::App;
 use crate::request::Request;
+use crate::telemetry::{Span, SpanKind};
+use crate::types::RouteInfo;
 use log::{debug, info, warn};
 use serde_json;
 use axum::http::StatusCode;
+use std::sync::Arc;
 
+use super::cancellation::Cancellation;
 use super::javascript_bridge::JavaScriptBridge;
 use super::middleware::execute_middleware;
 use super::response_builder::ResponseBuilder;
@@ -10,22 +15,27 @@ use super::timeout_manager::TimeoutManager;
 
 // Dynamic handler for registered routes
 pub async fn dynamic_handler(
+    app: Arc<App>,
     req: axum::extract::Request,
     actual_path: String, // Actual requested path
-    registered_path: String,
-    method: String,
-    _handler_id: String,
+    route: RouteInfo,    // Matched route with its JS handler
     timeout: u64,   // Timeout from app options
     dev_mode: bool, // Dev mode from app options
 ) -> axum::response::Response<axum::body::Body> {
+    let registered_path = route.path.clone();
+    let method = route.method.clone();
+    let timeout = route.options.timeout.unwrap_or(timeout);
     info!("🚀 Dynamic handler called - START");
     debug!("🔍 Dynamic handler called:");
     debug!("  Method: {}", method);
     debug!("  Actual path: {}", actual_path);
     debug!("  Registered path: {}", registered_path);
-    debug!("  Handler ID: {}", _handler_id);
+    debug!("  Handler ID: {}", route.handler_id);
     debug!("  Timeout: {}ms", timeout);
 
+    // Keep the request counted as in-flight so close() can drain it
+    let _inflight = crate::shutdown::track_request(&app);
+
     // Start timing the request
     let timeout_manager = TimeoutManager::new(timeout);
     
@@ -68,7 +78,7 @@ pub async fn dynamic_handler(
         }
 
         // Execute middleware
-        match execute_middleware(&mut request, &mut remaining_timeout, dev_mode).await {
+        match execute_middleware(&app, &mut request, &mut remaining_timeout, dev_mode).await {
             Ok(()) => {
                 debug!("✅ Middleware executed successfully, continuing to handler");
                 debug!("⏱️ Remaining time after middleware: {}ms", remaining_timeout);
@@ -83,7 +93,18 @@ pub async fn dynamic_handler(
         log_additional_request_details(&request);
 
         // Create request_data for JavaScript handler
-        let request_data = request.to_json_map();
+        let mut request_data = request.to_json_map();
+        // Error middleware the JS side passes a failed handler's error to
+        let error_middleware_ids = app.middleware.read().unwrap().error_handlers(&actual_path, &method);
+        if !error_middleware_ids.is_empty() {
+            request_data.insert("errorMiddlewareIds".to_string(), serde_json::json!(error_middleware_ids));
+        }
+        // Handler span; JS sees it as the current trace context
+        let mut span = Span::child_of(request.traceparent.as_deref(), format!("handler {} {}", method, registered_path), SpanKind::Internal);
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("code.function", registered_path.clone());
+            request_data.insert("traceparent".to_string(), serde_json::json!(span.context().traceparent()));
+        }
         let request_json = serde_json::to_string(&request_data).unwrap();
         debug!("📝 Request JSON prepared: {} chars", request_json.len());
 
@@ -91,9 +112,12 @@ pub async fn dynamic_handler(
         let handler_remaining_timeout = timeout_manager.get_remaining_time();
         timeout_manager.log_timeout_status("Handler execution");
 
+        // Streaming routes hand the raw body to JS
+        let request_body = route.options.stream_body.then(|| req.into_body());
+
         // Call JavaScript handler through bridge
-        let result = match JavaScriptBridge::call_get_handler(request_json, handler_remaining_timeout) {
-            Ok(result) => result,
+        let (result, body) = match JavaScriptBridge::call_route_handler(&app, route.handler.clone(), request_json, request_body, handler_remaining_timeout, Cancellation::for_request(&request)).await {
+            Ok(output) => output.into_parts(),
             Err(_) => {
                 // Channel error - return proper JSON error response
                 let error_response = serde_json::json!({
@@ -102,7 +126,7 @@ pub async fn dynamic_handler(
                     "status": 500,
                     "error": "channel_error"
                 });
-                error_response.to_string()
+                (error_response.to_string(), None)
             }
         };
 
@@ -114,14 +138,24 @@ pub async fn dynamic_handler(
         });
         
         debug!("🔍 Parsed response JSON: {:?}", response_json_value);
+        if let Some(span) = span.as_mut()
+            && let Some(error) = response_json_value["error"].as_str()
+        {
+            span.set_error(error);
+        }
+        drop(span);
 
         info!(
-            "🚀 Dynamic handler completed - END: {:?}",
+            "🚀 Dynamic handler completed - END [{}]: {:?}",
+            request.request_id,
             request.get_headers()
         );
 
         // Build response using ResponseBuilder
-        ResponseBuilder::from_json_response(response_json_value, dev_mode)
+        match body {
+            Some(body) => ResponseBuilder::with_body(response_json_value, body),
+            None => ResponseBuilder::from_json_response(response_json_value, dev_mode),
+        }
     } else {
         // Request or Response not found in extensions, return error
         crate::html_templates::generate_generic_error_page(
@@ -132,7 +166,7 @@ pub async fn dynamic_handler(
 }
 
 // Extract path parameters from URL
-fn extract_path_parameters(request: &mut Request, actual_path: &str, registered_path: &str) {
+pub(crate) fn extract_path_parameters(request: &mut Request, actual_path: &str, registered_path: &str) {
     let actual_segments: Vec<&str> = actual_path.split('/').collect();
     let registered_segments: Vec<&str> = registered_path.split('/').collect();
 
@@ -198,7 +232,7 @@ fn extract_path_parameters(request: &mut Request, actual_path: &str, registered_
 }
 
 // Extract query parameters from URL
-fn extract_query_parameters(request: &mut Request, query: &str) {
+pub(crate) fn extract_query_parameters(request: &mut Request, query: &str) {
     for pair in query.split('&') {
         if let Some((key, value)) = pair.split_once('=') {
             let decoded_key = urlencoding::decode(key)
@@ -217,6 +251,7 @@ fn extract_query_parameters(request: &mut Request, query: &str) {
 // Log request details
 fn log_request_details(request: &Request) {
     debug!("🔍 Request details:");
+    debug!("  Request ID: {}", request.request_id);
     debug!("  Method: {}", request.method);
     debug!("  Path: {}", request.path);
     debug!("  IP: {} (source: {})", request.ip, request.ip_source);
@@ -1,211 +1,256 @@
+use crate::app::App;
+use axum::body::{Body, Bytes};
 use neon::prelude::*;
+use neon::types::JsFuture;
+use neon::types::buffer::TypedArray;
 use serde_json;
-use std::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+use super::cancellation::{CancelGuard, Cancellation};
+use super::request_stream::open_request_body;
+use super::response_stream::{ResponseChunks, open_response_stream, stream_body};
+
+/// Value returned by a JS handler
+pub enum HandlerOutput {
+    /// Response JSON with the body in `content`
+    Json(String),
+    /// Response JSON plus a binary body passed as a Buffer
+    Bytes(String, Bytes),
+    /// Response JSON plus a body streamed from a readable stream
+    Stream(String, ResponseChunks),
+}
+
+impl HandlerOutput {
+    /// Response JSON and, for binary or streamed output, the body
+    pub fn into_parts(self) -> (String, Option<Body>) {
+        match self {
+            HandlerOutput::Json(json) => (json, None),
+            HandlerOutput::Bytes(json, bytes) => (json, Some(Body::from(bytes))),
+            HandlerOutput::Stream(json, chunks) => (json, Some(stream_body(chunks))),
+        }
+    }
+}
+
+// Read a handler result: a JSON string, or `{ meta, body }` / `{ meta, stream }`
+fn read_output<'a, 'b>(
+    cx: &mut Cx<'a>,
+    value: Handle<'b, JsValue>,
+    runtime: &tokio::runtime::Handle,
+) -> NeonResult<HandlerOutput> {
+    if let Ok(result) = value.downcast::<JsObject, _>(cx)
+        && let Some(meta) = result.get_opt::<JsString, _, _>(cx, "meta")?
+    {
+        let meta = meta.value(cx);
+        if let Some(body) = result.get_opt::<JsBuffer, _, _>(cx, "body")? {
+            let bytes = Bytes::copy_from_slice(body.as_slice(cx));
+            return Ok(HandlerOutput::Bytes(meta, bytes));
+        }
+        if let Some(stream) = result.get_opt::<JsObject, _, _>(cx, "stream")? {
+            let chunks = open_response_stream(cx, stream, runtime.clone())?;
+            return Ok(HandlerOutput::Stream(meta, chunks));
+        }
+        return Ok(HandlerOutput::Json(meta));
+    }
+
+    let result_string = value
+        .to_string(cx)
+        .unwrap_or_else(|_| cx.string("Failed to convert result"));
+    Ok(HandlerOutput::Json(result_string.value(cx)))
+}
+
+// Result of a call into JavaScript: a ready value or a pending Promise
+enum JsCall {
+    Ready(HandlerOutput),
+    Pending(JsFuture<HandlerOutput>),
+}
+
+// Why a call into JavaScript did not produce a value
+enum BridgeError {
+    // No channel, or the JS callback threw before returning
+    Channel,
+    // The returned Promise was rejected
+    Promise(String),
+    // The call did not settle within the timeout
+    Timeout,
+}
 
 // Мост для взаимодействия с JavaScript
 pub struct JavaScriptBridge;
 
 impl JavaScriptBridge {
-    // Вызов JavaScript функции getHandler
-    pub fn call_get_handler(
-        request_json: String,
-        timeout: u64,
-    ) -> Result<String, String> {
-        let (tx, rx) = mpsc::channel();
-        
-        // Get event queue for JavaScript communication
-        let event_queue = crate::types::get_event_queue();
-        let channel = {
-            let event_queue_map = event_queue.read().unwrap();
-            event_queue_map.clone()
+    // Schedule `call` on the JS thread and await its result (or its Promise) without blocking.
+    // With a `cancellation`, JS is told to abort the request if Rust stops waiting
+    async fn call_js<F>(app: &App, timeout: u64, cancellation: Option<Cancellation>, call: F) -> Result<HandlerOutput, BridgeError>
+    where
+        F: for<'a> FnOnce(&mut Cx<'a>) -> JsResult<'a, JsValue> + Send + 'static,
+    {
+        let channel = app.channel().ok_or(BridgeError::Channel)?;
+        let mut guard = cancellation.map(|cancellation| CancelGuard::new(channel.clone(), cancellation));
+        let runtime = tokio::runtime::Handle::current();
+        let (tx, rx) = oneshot::channel();
+
+        let _join_handle = channel.send(move |mut cx| {
+            let result = call(&mut cx)?;
+
+            // Check if result is Promise
+            let js_call = if result.is_a::<JsPromise, _>(&mut cx) {
+                let promise: Handle<JsPromise> = result.downcast_or_throw(&mut cx)?;
+
+                // Convert JavaScript Promise to Rust Future, awaited on the Tokio side
+                let promise_future = promise.to_future(&mut cx, move |mut cx, result| {
+                    let value = result.or_throw(&mut cx)?;
+                    read_output(&mut cx, value, &runtime)
+                })?;
+                JsCall::Pending(promise_future)
+            } else {
+                // Not promise, convert directly
+                JsCall::Ready(read_output(&mut cx, result, &runtime)?)
+            };
+
+            let _ = tx.send(js_call);
+            Ok(())
+        });
+
+        let wait = async {
+            match rx.await.map_err(|_| BridgeError::Channel)? {
+                JsCall::Ready(result) => Ok(result),
+                JsCall::Pending(promise_future) => promise_future
+                    .await
+                    .map_err(|err| BridgeError::Promise(format!("{:?}", err))),
+            }
         };
 
-        if let Some(channel) = channel {
-            let request_json_clone = request_json.clone();
-            
-            let _join_handle = channel.send(move |mut cx| {
-                // Вызываем глобальную функцию getHandler
-                let global: Handle<JsObject> = cx.global("global")?;
-                let get_handler_fn: Handle<JsFunction> = global.get(&mut cx, "getHandler")?;
-
-                // Вызываем getHandler(requestJson, timeout) - возвращает Promise
-                let result: Handle<JsValue> = get_handler_fn
-                    .call_with(&mut cx)
-                    .arg(cx.string(&request_json_clone))
-                    .arg(cx.number(timeout as f64))
-                    .apply(&mut cx)?;
-
-                // Check if result is Promise
-                if result.is_a::<JsPromise, _>(&mut cx) {
-                    let promise: Handle<JsPromise> = result.downcast_or_throw(&mut cx)?;
-
-                    // Convert JavaScript Promise to Rust Future
-                    let promise_future = promise.to_future(&mut cx, |mut cx, result| {
-                        let value = result.or_throw(&mut cx)?;
-                        let result_string = value
-                            .to_string(&mut cx)
-                            .unwrap_or_else(|_| cx.string("Failed to convert promise result"));
-                        Ok(result_string.value(&mut cx))
-                    })?;
-
-                    // Run async task in separate thread with timeout control
-                    let _channel = cx.channel();
-                    let tx_clone = tx.clone();
-
-                    let _thread_handle = std::thread::spawn(move || {
-                        let rt = tokio::runtime::Builder::new_current_thread()
-                            .enable_all()
-                            .build()
-                            .unwrap();
-
-                        rt.block_on(async {
-                            match tokio::time::timeout(
-                                tokio::time::Duration::from_millis(timeout),
-                                promise_future
-                            ).await {
-                                Ok(result) => {
-                                    match result {
-                                        Ok(result_string) => {
-                                            let _ = tx_clone.send(result_string);
-                                        }
-                                        Err(err) => {
-                                            let error_response = serde_json::json!({
-                                                "content": format!("Promise failed: {:?}", err),
-                                                "contentType": "text/plain",
-                                                "status": 500,
-                                                "error": "promise_failed"
-                                            });
-                                            let _ = tx_clone.send(error_response.to_string());
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    let timeout_response = serde_json::json!({
-                                        "content": format!("Handler timeout after {}ms", timeout),
-                                        "contentType": "text/plain",
-                                        "status": 408,
-                                        "error": "timeout"
-                                    });
-                                    let _ = tx_clone.send(timeout_response.to_string());
-                                }
-                            }
-                        });
-                    });
-                } else {
-                    // Not promise, convert directly
-                    let result_string = result
-                        .to_string(&mut cx)
-                        .unwrap_or_else(|_| cx.string("Failed to convert result"));
-                    let _ = tx.send(result_string.value(&mut cx));
-                }
-
-                Ok(())
-            });
-
-            // Wait for result from JavaScript
-            match rx.recv() {
-                Ok(result) => Ok(result),
-                Err(_) => {
-                    let error_response = serde_json::json!({
-                        "content": format!("Failed to receive result from JavaScript handler"),
-                        "contentType": "text/plain",
-                        "status": 500,
-                        "error": "channel_error"
-                    });
-                    Ok(error_response.to_string())
-                }
+        let result = tokio::time::timeout(tokio::time::Duration::from_millis(timeout), wait)
+            .await
+            .unwrap_or(Err(BridgeError::Timeout));
+        if let Some(guard) = guard.as_mut() {
+            match result {
+                Err(BridgeError::Timeout) => guard.timed_out(),
+                _ => guard.disarm(),
             }
-        } else {
-            Err("No channel available".to_string())
         }
+        result
+    }
+
+    // Call the route's JS handler through executeHandler, with the raw body for streaming routes
+    pub async fn call_route_handler(
+        app: &App,
+        handler: Arc<Root<JsFunction>>,
+        request_json: String,
+        body: Option<Body>,
+        timeout: u64,
+        cancellation: Cancellation,
+    ) -> Result<HandlerOutput, String> {
+        if app.channel().is_none() {
+            return Err("No channel available".to_string());
+        }
+
+        let runtime = tokio::runtime::Handle::current();
+        let result = Self::call_js(app, timeout, Some(cancellation), move |cx| {
+            // The route's handler is passed directly, no lookup on the JS side
+            let handler = handler.to_inner(cx);
+            let global: Handle<JsObject> = cx.global("global")?;
+            let execute_handler_fn: Handle<JsFunction> = global.get(cx, "executeHandler")?;
+
+            // Вызываем executeHandler(handler, requestJson, timeout, readBody?) - возвращает Promise
+            let request_json = cx.string(&request_json);
+            let timeout = cx.number(timeout as f64);
+            let mut call = execute_handler_fn.call_with(cx);
+            call.arg(handler).arg(request_json).arg(timeout);
+            if let Some(body) = body {
+                let read_body = open_request_body(cx, body, runtime)?;
+                call.arg(read_body);
+            }
+            call.apply(cx)
+        })
+        .await;
+
+        let response = match result {
+            Ok(output) => return Ok(output),
+            Err(BridgeError::Promise(err)) => serde_json::json!({
+                "content": format!("Promise failed: {}", err),
+                "contentType": "text/plain",
+                "status": 500,
+                "error": "promise_failed"
+            }),
+            Err(BridgeError::Timeout) => serde_json::json!({
+                "content": format!("Handler timeout after {}ms", timeout),
+                "contentType": "text/plain",
+                "status": 408,
+                "error": "timeout"
+            }),
+            Err(BridgeError::Channel) => serde_json::json!({
+                "content": "Failed to receive result from JavaScript handler",
+                "contentType": "text/plain",
+                "status": 500,
+                "error": "channel_error"
+            }),
+        };
+        Ok(HandlerOutput::Json(response.to_string()))
     }
 
     // Call JavaScript function executeMiddleware
-    pub fn call_execute_middleware(
+    pub async fn call_execute_middleware(
+        app: &App,
         request_json: String,
         timeout: u64,
+        cancellation: Cancellation,
     ) -> Result<String, String> {
-        let (tx, rx) = mpsc::channel();
-        
-        let event_queue = crate::types::get_event_queue();
-        let channel = {
-            let event_queue_map = event_queue.read().unwrap();
-            event_queue_map.clone()
-        };
+        let result = Self::call_js(app, timeout, Some(cancellation), move |cx| {
+            let global: Handle<JsObject> = cx.global("global")?;
+            let execute_middleware_fn: Handle<JsFunction> =
+                global.get(cx, "executeMiddleware")?;
 
-        if let Some(channel) = channel {
-            let request_json_clone = request_json.clone();
-            
-            let _join_handle = channel.send(move |mut cx| {
-                let global: Handle<JsObject> = cx.global("global")?;
-                let execute_middleware_fn: Handle<JsFunction> =
-                    global.get(&mut cx, "executeMiddleware")?;
-
-                let result: Handle<JsValue> = execute_middleware_fn
-                    .call_with(&mut cx)
-                    .arg(cx.string(&request_json_clone))
-                    .arg(cx.number(timeout as f64))
-                    .apply(&mut cx)?;
-
-                if result.is_a::<JsPromise, _>(&mut cx) {
-                    let promise: Handle<JsPromise> = result.downcast_or_throw(&mut cx)?;
-
-                    let promise_future = promise.to_future(&mut cx, |mut cx, result| {
-                        let value = result.or_throw(&mut cx)?;
-                        let result_string = value
-                            .to_string(&mut cx)
-                            .unwrap_or_else(|_| cx.string("Failed to convert promise result"));
-                        Ok(result_string.value(&mut cx))
-                    })?;
-
-                    let _channel = cx.channel();
-                    let tx_clone = tx.clone();
-                    let timeout_clone = timeout;
-
-                    let _thread_handle = std::thread::spawn(move || {
-                        let rt = tokio::runtime::Builder::new_current_thread()
-                            .enable_all()
-                            .build()
-                            .unwrap();
-
-                        rt.block_on(async {
-                            match tokio::time::timeout(
-                                tokio::time::Duration::from_millis(timeout_clone),
-                                promise_future
-                            ).await {
-                                Ok(result) => {
-                                    match result {
-                                        Ok(result_string) => {
-                                            let _ = tx_clone.send(result_string);
-                                        }
-                                        Err(err) => {
-                                            let error_msg = format!("Promise failed: {:?}", err);
-                                            let _ = tx_clone.send(error_msg);
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    let _ = tx_clone.send(format!("Middleware timeout after {}ms", timeout_clone));
-                                }
-                            }
-                        });
-                    });
-                } else {
-                    let result_string = result
-                        .to_string(&mut cx)
-                        .unwrap_or_else(|_| cx.string("Failed to handle middleware result"));
-                    let _ = tx.send(result_string.value(&mut cx));
-                }
-
-                Ok(())
-            });
-
-            match rx.recv() {
-                Ok(result) => Ok(result),
-                Err(_) => Err("Failed to receive middleware result".to_string())
-            }
-        } else {
-            Err("No event queue available".to_string())
+            let request_json = cx.string(&request_json);
+            let timeout = cx.number(timeout as f64);
+            execute_middleware_fn
+                .call_with(cx)
+                .arg(request_json)
+                .arg(timeout)
+                .apply(cx)
+        })
+        .await;
+
+        match result {
+            Ok(output) => Ok(output.into_parts().0),
+            Err(BridgeError::Promise(err)) => Ok(format!("Promise failed: {}", err)),
+            Err(BridgeError::Timeout) => Ok(serde_json::json!({
+                "shouldContinue": false,
+                "error": format!("Middleware timeout after {}ms", timeout),
+                "status": 408
+            })
+            .to_string()),
+            Err(BridgeError::Channel) => Err("Failed to receive middleware result".to_string()),
+        }
+    }
+
+    // Call JavaScript function executeResponseHooks
+    pub async fn call_response_hooks(
+        app: &App,
+        hooks_json: String,
+        timeout: u64,
+    ) -> Result<String, String> {
+        let result = Self::call_js(app, timeout, None, move |cx| {
+            let global: Handle<JsObject> = cx.global("global")?;
+            let execute_hooks_fn: Handle<JsFunction> =
+                global.get(cx, "executeResponseHooks")?;
+
+            let hooks_json = cx.string(&hooks_json);
+            let timeout = cx.number(timeout as f64);
+            execute_hooks_fn
+                .call_with(cx)
+                .arg(hooks_json)
+                .arg(timeout)
+                .apply(cx)
+        })
+        .await;
+
+        match result {
+            Ok(output) => Ok(output.into_parts().0),
+            Err(BridgeError::Promise(err)) => Err(format!("Promise failed: {}", err)),
+            Err(BridgeError::Timeout) => Err(format!("Response hooks timeout after {}ms", timeout)),
+            Err(BridgeError::Channel) => Err("Failed to receive response hooks result".to_string()),
         }
     }
 }
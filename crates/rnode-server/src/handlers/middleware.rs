@@ -1,6 +1,8 @@
+use crate::app::{App, app_from_arg};
 use crate::request::Request;
-use crate::types::get_middleware;
-use globset::{Glob, GlobSetBuilder};
+use crate::telemetry::{Span, SpanKind};
+use crate::types::{HandlerId, MiddlewareInfo, MiddlewareOptions, next_handler_id};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use log::{debug, error, info, warn};
 use neon::prelude::*;
 use serde_json;
@@ -8,57 +10,117 @@ use axum::body::Body;
 use axum::http::StatusCode;
 use axum::response::Response;
 
+use super::cancellation::Cancellation;
 use super::javascript_bridge::JavaScriptBridge;
 use super::timeout_manager::TimeoutManager;
 
-// Function for middleware registration
-pub fn register_middleware(mut cx: FunctionContext) -> JsResult<JsUndefined> {
-    let path = cx.argument::<JsString>(0)?.value(&mut cx);
-    let _handler = cx.argument::<JsFunction>(1)?; // JS middleware function
+/// Middleware of an app in registration order, with the path globs compiled once
+#[derive(Debug, Default)]
+pub struct MiddlewareChain {
+    entries: Vec<MiddlewareInfo>,
+    globs: GlobSet,
+}
 
-    info!("Registering middleware for path: {}", path);
+impl MiddlewareChain {
+    pub fn push(&mut self, middleware: MiddlewareInfo) {
+        self.entries.push(middleware);
+        self.compile();
+    }
 
-    // Generate unique middleware ID
-    let handler_id = format!(
-        "middleware_{}_{}",
-        path.replace('/', "_"),
-        std::process::id()
-    );
+    /// Remove every middleware registered for `path`
+    pub fn remove_path(&mut self, path: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|m| m.path != path);
+        let removed = self.entries.len() != before;
+        if removed {
+            self.compile();
+        }
+        removed
+    }
 
-    // Add middleware to global storage
-    let middleware = get_middleware();
-    {
-        let mut middleware_vec = middleware.write().unwrap();
-
-        // Check if middleware for this path already exists
-        if let Some(existing_middleware) = middleware_vec.iter_mut().find(|m| m.path == path) {
-            // Update existing middleware
-            existing_middleware.middleware_id = handler_id.clone();
-            info!(
-                "🔄 Updated existing middleware for path: {} -> {}",
-                path, handler_id
-            );
-        } else {
-            // Add new middleware
-            let middleware_info = crate::types::MiddlewareInfo {
-                path: path.to_string(),
-                middleware_id: handler_id.clone(),
-            };
-            middleware_vec.push(middleware_info);
-            info!(
-                "✅ Added new middleware for path: {} -> {}",
-                path, handler_id
-            );
+    // Rebuild the GlobSet; index i matches entries[i]
+    fn compile(&mut self) {
+        let mut builder = GlobSetBuilder::new();
+        for middleware in &self.entries {
+            // A path that is not a valid glob is matched literally
+            let glob = Glob::new(&middleware.path)
+                .or_else(|_| Glob::new(&globset::escape(&middleware.path)))
+                .expect("escaped glob is valid");
+            builder.add(glob);
         }
+        self.globs = builder.build().unwrap_or_else(|e| {
+            warn!("⚠️ Failed to compile middleware paths: {}", e);
+            GlobSet::empty()
+        });
+    }
+
+    /// Middleware matching a request, in registration order
+    pub fn matching<'a>(&'a self, path: &str, method: &'a str) -> impl Iterator<Item = &'a MiddlewareInfo> + 'a {
+        let mut indices = self.globs.matches(path);
+        indices.sort_unstable();
+        indices.into_iter().map(|i| &self.entries[i]).filter(move |m| match &m.methods {
+            // HEAD is served by GET handlers, so GET middleware runs for it too
+            Some(methods) => methods
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(method) || (method == "HEAD" && allowed.eq_ignore_ascii_case("GET"))),
+            None => true,
+        })
+    }
+
+    /// Error middleware a failed route handler is passed to
+    pub fn error_handlers(&self, path: &str, method: &str) -> Vec<HandlerId> {
+        self.matching(path, method).filter(|m| m.error).map(|m| m.id).collect()
     }
+}
+
+// Function for middleware registration, returns the middleware ID
+pub fn register_middleware(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let _handler = cx.argument::<JsFunction>(1)?; // JS middleware function
+    let app = app_from_arg(&mut cx, 2)?;
+    let options: MiddlewareOptions = cx
+        .argument_opt(3)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .and_then(|json| serde_json::from_str(&json.value(&mut cx)).ok())
+        .unwrap_or_default();
+
+    let id = next_handler_id();
+    info!(
+        "✅ Registering {}middleware for path: {} {:?} (app {}, id {})",
+        if options.error { "error " } else { "" },
+        path,
+        options.methods,
+        app.id,
+        id
+    );
+
+    app.middleware.write().unwrap().push(MiddlewareInfo {
+        id,
+        path,
+        methods: options.methods.map(|methods| methods.iter().map(|m| m.to_uppercase()).collect()),
+        error: options.error,
+    });
+
+    Ok(cx.number(id as f64))
+}
+
+// Function for removing middleware registered for a path
+pub fn remove_middleware(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let app = app_from_arg(&mut cx, 1)?;
+
+    let removed = app.middleware.write().unwrap().remove_path(&path);
 
-    info!("Middleware registration completed for path: {}", path);
+    if removed {
+        info!("🗑️ Removed middleware for path: {}", path);
+    }
 
-    Ok(cx.undefined())
+    Ok(cx.boolean(removed))
 }
 
 // Function for middleware execution with Request and Response objects
 pub async fn execute_middleware(
+    app: &App,
     request: &mut Request,
     timeout: &mut u64,   // Timeout from app options - mutable reference to update remaining time
     dev_mode: bool, // Dev mode from app options
@@ -80,60 +142,24 @@ pub async fn execute_middleware(
         debug!("🔧 Initialized customParams object");
     }
 
-    // Get middleware for this path
-    let middleware = get_middleware();
-
-    // Check each middleware pattern for matches
-    let middleware_info_opt = {
-        let middleware_vec = middleware.read().unwrap();
-        middleware_vec
-            .iter()
-            .find(|middleware_info| {
-                // Use globset for flexible wildcard matching
-                let matches = if let Ok(glob) = Glob::new(&middleware_info.path) {
-                    // Create a GlobSet with single pattern for matching
-                    let mut builder = GlobSetBuilder::new();
-                    builder.add(glob);
-                    if let Ok(globset) = builder.build() {
-                        let result = globset.is_match(&actual_path);
-                        debug!(
-                            "🔍 Globset check: '{}' matches '{}' -> {}",
-                            actual_path, middleware_info.path, result
-                        );
-                        result
-                    } else {
-                        // Fallback if GlobSet building fails
-                        let result = actual_path == middleware_info.path;
-                        debug!(
-                            "🔍 Fallback exact match: '{}' == '{}' -> {}",
-                            actual_path, middleware_info.path, result
-                        );
-                        result
-                    }
-                } else {
-                    // Fallback to simple string comparison if glob parsing fails
-                    let result = actual_path == middleware_info.path;
-                    debug!(
-                        "🔍 Simple fallback: '{}' == '{}' -> {}",
-                        actual_path, middleware_info.path, result
-                    );
-                    result
-                };
-
-                debug!(
-                    "🔍 Checking middleware: {} -> matches: {} (glob: {})",
-                    middleware_info.path, matches, middleware_info.path
-                );
-
-                matches
-            })
-            .cloned()
+    // All middleware matching this request, in registration order
+    let middleware_ids: Vec<HandlerId> = {
+        let chain = app.middleware.read().unwrap();
+        let matched: Vec<&MiddlewareInfo> = chain.matching(&actual_path, &request.method).collect();
+        // Error middleware alone has nothing to handle yet
+        if matched.iter().all(|m| m.error) {
+            Vec::new()
+        } else {
+            matched.iter().map(|m| m.id).collect()
+        }
     };
 
-    if let Some(middleware_info) = middleware_info_opt {
+    if !middleware_ids.is_empty() {
         info!(
-            "✅ Middleware matched: {} -> {}",
-            middleware_info.path, middleware_info.middleware_id
+            "✅ {} middleware matched: {} [{}]",
+            middleware_ids.len(),
+            actual_path,
+            request.request_id
         );
 
         // Execute middleware for this pattern
@@ -153,10 +179,19 @@ pub async fn execute_middleware(
                 .unwrap());
         }
 
+        // Middleware span; JS sees it as the current trace context
+        let mut span = Span::child_of(request.traceparent.as_deref(), "middleware", SpanKind::Internal);
+
         // Call JavaScript executeMiddleware function through bridge
-        let request_json = serde_json::to_string(&request.to_json_map()).unwrap();
-        
-        let middleware_result = match JavaScriptBridge::call_execute_middleware(request_json, remaining_timeout) {
+        let mut request_data = request.to_json_map();
+        request_data.insert("middlewareIds".to_string(), serde_json::json!(middleware_ids));
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("rnode.middleware.count", middleware_ids.len() as u64);
+            request_data.insert("traceparent".to_string(), serde_json::json!(span.context().traceparent()));
+        }
+        let request_json = serde_json::to_string(&request_data).unwrap();
+
+        let middleware_result = match JavaScriptBridge::call_execute_middleware(app, request_json, remaining_timeout, Cancellation::for_request(request)).await {
             Ok(result) => {
                 debug!("🔍 Middleware result: {}", result);
                 debug!(
@@ -178,21 +213,39 @@ pub async fn execute_middleware(
                 serde_json::json!({"shouldContinue": true})
             }
         };
+        if let Some(mut span) = span
+            && let Some(error) = middleware_result["error"].as_str()
+        {
+            span.set_error(error);
+        }
+        // A middleware ran past its own timeout; JS already aborted it
+        if middleware_result["timedOut"].as_bool() == Some(true) {
+            crate::metrics::http::record_request_cancelled(&request.registered_path, "timeout");
+        }
 
         // If middleware wants to interrupt execution
         if let Some(should_continue) = middleware_result["shouldContinue"].as_bool() {
             if !should_continue {
                 warn!("🛑 Middleware interrupted execution: {}", actual_path);
 
+                let status = middleware_result["status"]
+                    .as_u64()
+                    .and_then(|status| StatusCode::from_u16(status as u16).ok());
+
                 // Check if middleware returned an error
                 if let Some(error) = middleware_result.get("error") {
                     error!("❌ Middleware error: {:?}", error);
 
                     // Return error response
                     let error_message = error.as_str().unwrap_or("Middleware error");
+                    let status = status.filter(|status| status.is_client_error() || status.is_server_error()).unwrap_or(StatusCode::FORBIDDEN);
+                    let title = match status {
+                        StatusCode::FORBIDDEN => "Access Denied",
+                        _ => status.canonical_reason().unwrap_or("Error"),
+                    };
                     return Err(crate::html_templates::generate_error_page(
-                        StatusCode::FORBIDDEN,
-                        "Access Denied",
+                        status,
+                        title,
                         "Middleware has blocked this request",
                         Some(&format!("Error: {}", error_message)),
                         dev_mode,
@@ -210,7 +263,7 @@ pub async fn execute_middleware(
                     .to_string();
 
                 let mut response_builder = Response::builder()
-                    .status(StatusCode::OK)
+                    .status(status.unwrap_or(StatusCode::OK))
                     .header("content-type", content_type);
 
                 // Add headers from middleware
@@ -227,7 +280,12 @@ pub async fn execute_middleware(
                         ]
                         .contains(&key_lower.as_str())
                         {
-                            if let Some(value_str) = value.as_str() {
+                            // Repeated headers such as Set-Cookie come as arrays
+                            let values = match value {
+                                serde_json::Value::Array(values) => values.iter().filter_map(|v| v.as_str()).collect(),
+                                value => value.as_str().into_iter().collect::<Vec<_>>(),
+                            };
+                            for value_str in values {
                                 response_builder = response_builder.header(key, value_str);
                             }
                         }
@@ -332,3 +390,41 @@ pub async fn execute_middleware(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn middleware(id: HandlerId, path: &str, methods: Option<&[&str]>, error: bool) -> MiddlewareInfo {
+        MiddlewareInfo {
+            id,
+            path: path.to_string(),
+            methods: methods.map(|methods| methods.iter().map(|m| m.to_string()).collect()),
+            error,
+        }
+    }
+
+    fn ids(chain: &MiddlewareChain, path: &str, method: &str) -> Vec<HandlerId> {
+        chain.matching(path, method).map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn test_chain_matches_in_registration_order() {
+        let mut chain = MiddlewareChain::default();
+        chain.push(middleware(1, "/api/*", None, false));
+        chain.push(middleware(2, "*", None, false));
+        chain.push(middleware(3, "/api/*", Some(&["POST"]), false));
+        chain.push(middleware(4, "/api/*", None, true));
+        chain.push(middleware(5, "/api/[", None, false)); // Invalid glob, matched literally
+
+        assert_eq!(ids(&chain, "/api/users", "GET"), vec![1, 2, 4]);
+        assert_eq!(ids(&chain, "/api/users", "POST"), vec![1, 2, 3, 4]);
+        assert_eq!(ids(&chain, "/other", "GET"), vec![2]);
+        assert_eq!(ids(&chain, "/api/[", "GET"), vec![1, 2, 4, 5]);
+        assert_eq!(chain.error_handlers("/api/users", "GET"), vec![4]);
+
+        assert!(chain.remove_path("/api/*"));
+        assert_eq!(ids(&chain, "/api/users", "POST"), vec![2]);
+        assert!(!chain.remove_path("/api/*"));
+    }
+}
@@ -1,12 +1,17 @@
 // Handlers module - объединяет обработчики HTTP запросов и middleware
+pub mod cancellation;
 pub mod dynamic_handler;
 pub mod middleware;
 pub mod request_processor;
+pub mod request_stream;
 pub mod response_builder;
+pub mod response_hooks;
+pub mod response_stream;
 pub mod timeout_manager;
 pub mod javascript_bridge;
 
 // Re-export main functions for backward compatibility
 pub use dynamic_handler::dynamic_handler;
-pub use middleware::register_middleware;
+pub use middleware::{register_middleware, remove_middleware};
+pub use response_hooks::register_after_hook;
 pub use request_processor::process_http_request;
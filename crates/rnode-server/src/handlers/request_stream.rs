@@ -0,0 +1,35 @@
+use axum::body::{Body, BodyDataStream};
+use futures_util::StreamExt;
+use neon::prelude::*;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Expose a raw request body to JS as a native `read()` function.
+///
+/// Each call resolves to the next chunk as a Buffer, or `null` once the body
+/// has ended; it rejects if reading fails or the body exceeds its size limit.
+pub fn open_request_body<'a>(
+    cx: &mut Cx<'a>,
+    body: Body,
+    runtime: tokio::runtime::Handle,
+) -> JsResult<'a, JsFunction> {
+    let stream: Arc<Mutex<BodyDataStream>> = Arc::new(Mutex::new(body.into_data_stream()));
+    let channel = cx.channel();
+
+    JsFunction::new(cx, move |mut cx| {
+        let (deferred, promise) = cx.promise();
+        let stream = stream.clone();
+        let channel = channel.clone();
+
+        runtime.spawn(async move {
+            let chunk = stream.lock().await.next().await;
+            deferred.settle_with(&channel, move |mut cx| match chunk {
+                Some(Ok(bytes)) => Ok(JsBuffer::from_slice(&mut cx, &bytes)?.upcast::<JsValue>()),
+                Some(Err(e)) => cx.throw_error(e.to_string()),
+                None => Ok(cx.null().upcast()),
+            });
+        });
+
+        Ok(promise)
+    })
+}
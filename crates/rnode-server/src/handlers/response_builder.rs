@@ -7,16 +7,8 @@ use axum::body::Body;
 pub struct ResponseBuilder;
 
 impl ResponseBuilder {
-    // Создание ответа из JSON данных
-    pub fn from_json_response(
-        response_json_value: serde_json::Value,
-        dev_mode: bool,
-    ) -> axum::response::Response<Body> {
-        let response_text = response_json_value["content"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-
+    // Status, content-type and headers from the handler's response JSON
+    fn response_head(response_json_value: &serde_json::Value) -> axum::http::response::Builder {
         let status = response_json_value["status"]
             .as_u64()
             .map(|s| s as u16)
@@ -63,6 +55,29 @@ impl ResponseBuilder {
             }
         }
 
+        response_builder
+    }
+
+    // Создание ответа из JSON данных
+    pub fn from_json_response(
+        response_json_value: serde_json::Value,
+        dev_mode: bool,
+    ) -> axum::response::Response<Body> {
+        let response_text = response_json_value["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        let status = response_json_value["status"]
+            .as_u64()
+            .map(|s| s as u16)
+            .unwrap_or(200);
+        let content_type = response_json_value["contentType"]
+            .as_str()
+            .unwrap_or("text/plain");
+
+        let response_builder = Self::response_head(&response_json_value);
+
         // Check error condition
         debug!("🔍 Checking error condition - Status: {}, Content-Type: '{}'", status, content_type);
         if status >= 400 && content_type == "text/plain" {
@@ -88,4 +103,16 @@ impl ResponseBuilder {
 
         response_builder.body(Body::from(response_text)).unwrap()
     }
+
+    // Response with a binary or streamed body; `contentLength` in the JSON
+    // fixes the length of a stream, otherwise it is sent chunked
+    pub fn with_body(response_json_value: serde_json::Value, body: Body) -> axum::response::Response<Body> {
+        let mut response_builder = Self::response_head(&response_json_value);
+
+        if let Some(content_length) = response_json_value["contentLength"].as_u64() {
+            response_builder = response_builder.header("content-length", content_length);
+        }
+
+        response_builder.body(body).unwrap()
+    }
 }
@@ -0,0 +1,272 @@
+use crate::app::{App, app_from_arg};
+use crate::types::{HandlerId, MiddlewareInfo, MiddlewareOptions, next_handler_id};
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use base64::Engine;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::StatusCode;
+use log::{debug, info, warn};
+use neon::prelude::*;
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::dynamic_handler::extract_query_parameters;
+use super::javascript_bridge::JavaScriptBridge;
+
+// Larger bodies are passed to JS hooks without their content
+const HOOK_BODY_LIMIT: u64 = 1024 * 1024;
+
+/// Request details available to response hooks
+#[derive(Debug, Clone)]
+pub struct HookRequest {
+    pub started: Instant,
+}
+
+/// Hook run natively on every response before it is sent
+pub trait ResponseHook: Send + Sync + std::fmt::Debug {
+    fn on_response(&self, request: &HookRequest, response: &mut Response);
+}
+
+/// Native hooks of a server, run in order after the JS ones
+#[derive(Debug, Default)]
+pub struct ResponseHooks {
+    hooks: Vec<Box<dyn ResponseHook>>,
+}
+
+impl ResponseHooks {
+    pub fn push(&mut self, hook: impl ResponseHook + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+}
+
+/// `responseTime` option: X-Response-Time with the handling time in milliseconds
+#[derive(Debug)]
+pub struct ResponseTimeHook;
+
+impl ResponseHook for ResponseTimeHook {
+    fn on_response(&self, request: &HookRequest, response: &mut Response) {
+        let elapsed = request.started.elapsed().as_secs_f64() * 1000.0;
+        if let Ok(value) = HeaderValue::from_str(&format!("{:.3}ms", elapsed)) {
+            response.headers_mut().insert("x-response-time", value);
+        }
+    }
+}
+
+// Function for response hook registration, returns the hook ID
+pub fn register_after_hook(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let _handler = cx.argument::<JsFunction>(1)?; // JS hook function
+    let app = app_from_arg(&mut cx, 2)?;
+    let options: MiddlewareOptions = cx
+        .argument_opt(3)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .and_then(|json| serde_json::from_str(&json.value(&mut cx)).ok())
+        .unwrap_or_default();
+
+    let id = next_handler_id();
+    info!("✅ Registering response hook for path: {} {:?} (app {}, id {})", path, options.methods, app.id, id);
+
+    app.after_hooks.write().unwrap().push(MiddlewareInfo {
+        id,
+        path,
+        methods: options.methods.map(|methods| methods.iter().map(|m| m.to_uppercase()).collect()),
+        error: false,
+    });
+
+    Ok(cx.number(id as f64))
+}
+
+// Headers as JS sees them: lower-case names, repeated headers as arrays
+fn headers_to_json(headers: &HeaderMap) -> Map<String, Value> {
+    let mut map = Map::new();
+    for name in headers.keys() {
+        if name == header::CONTENT_TYPE || name == header::CONTENT_LENGTH {
+            continue;
+        }
+        let values: Vec<Value> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(|value| Value::String(value.to_string()))
+            .collect();
+        let value = match values.len() {
+            1 => values.into_iter().next().unwrap(),
+            _ => Value::Array(values),
+        };
+        map.insert(name.as_str().to_string(), value);
+    }
+    map
+}
+
+// Headers returned by the hooks; Content-Length is recomputed, Content-Type may come from `contentType`
+fn headers_from_json(headers: &Map<String, Value>) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+            warn!("⚠️ Response hook set an invalid header name: {}", name);
+            continue;
+        };
+        if name == header::CONTENT_LENGTH || name == header::TRANSFER_ENCODING {
+            continue;
+        }
+        let values = match value {
+            Value::Array(values) => values.iter().filter_map(|v| v.as_str()).collect(),
+            value => value.as_str().into_iter().collect::<Vec<_>>(),
+        };
+        for value in values {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                map.append(name.clone(), value);
+            }
+        }
+    }
+    map
+}
+
+// Pass the built response to the matching JS hooks and apply what they changed
+async fn run_js_hooks(
+    app: &App,
+    ids: Vec<HandlerId>,
+    request: crate::request::Request,
+    started: Instant,
+    response: Response,
+    timeout: u64,
+) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let size = body.size_hint().exact();
+    let is_event_stream = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+
+    // Buffer small bodies so hooks can read them; streams pass through untouched
+    let (buffered, body) = match size {
+        Some(size) if size <= HOOK_BODY_LIMIT && !is_event_stream => match axum::body::to_bytes(body, HOOK_BODY_LIMIT as usize).await {
+            Ok(bytes) => (Some(bytes), None),
+            Err(e) => {
+                // The body is gone; sending it empty under the old Content-Length would break the connection
+                warn!("⚠️ Failed to buffer response body for hooks, skipping them: {}", e);
+                return crate::html_templates::generate_error_page(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal Server Error",
+                    "The response body could not be read.",
+                    None,
+                    false,
+                );
+            }
+        },
+        _ => (None, Some(body)),
+    };
+    let content = buffered.as_ref().and_then(|bytes| std::str::from_utf8(bytes).ok());
+
+    let payload = json!({
+        "hookIds": ids,
+        "request": request.to_json_map(),
+        "response": {
+            "status": parts.status.as_u16(),
+            "contentType": parts.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()),
+            "headers": headers_to_json(&parts.headers),
+            "content": content,
+            "bodySize": size,
+            "durationMs": started.elapsed().as_secs_f64() * 1000.0,
+        }
+    });
+
+    let result = JavaScriptBridge::call_response_hooks(app, payload.to_string(), timeout)
+        .await
+        .ok()
+        .and_then(|result| serde_json::from_str::<Value>(&result).ok())
+        .filter(|result| result.get("error").is_none());
+
+    let original_body = || match (&buffered, body) {
+        (Some(bytes), _) => Body::from(bytes.clone()),
+        (None, Some(body)) => body,
+        (None, None) => Body::empty(),
+    };
+    let Some(result) = result else {
+        warn!("⚠️ Response hooks failed, sending the response unchanged");
+        return Response::from_parts(parts, original_body());
+    };
+
+    if let Some(status) = result["status"].as_u64().and_then(|status| StatusCode::from_u16(status as u16).ok()) {
+        parts.status = status;
+    }
+
+    let replaced = match result["content"].as_str() {
+        Some(content) if result["base64"].as_bool() == Some(true) => Some(Bytes::from(
+            base64::engine::general_purpose::STANDARD.decode(content).unwrap_or_default(),
+        )),
+        Some(content) => Some(Bytes::from(content.to_string())),
+        None => None,
+    };
+
+    if let Some(headers) = result["headers"].as_object() {
+        let content_length = parts.headers.get(header::CONTENT_LENGTH).cloned();
+        let mut new_headers = headers_from_json(headers);
+        if !new_headers.contains_key(header::CONTENT_TYPE)
+            && let Some(content_type) = result["contentType"]
+                .as_str()
+                .filter(|value| !value.is_empty())
+                .and_then(|value| HeaderValue::from_str(value).ok())
+        {
+            new_headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        // A streamed body keeps the length it was declared with
+        if replaced.is_none()
+            && let Some(content_length) = content_length
+        {
+            new_headers.insert(header::CONTENT_LENGTH, content_length);
+        }
+        parts.headers = new_headers;
+    }
+
+    match replaced {
+        Some(bytes) => {
+            debug!("🔧 Response hooks replaced the body ({} bytes)", bytes.len());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(bytes))
+        }
+        None => Response::from_parts(parts, original_body()),
+    }
+}
+
+/// Run JS response hooks matching the request, then the native hooks, on every response
+pub async fn response_hooks_layer(app: Arc<App>, native: Arc<ResponseHooks>, timeout: u64, req: Request, next: Next) -> Response {
+    let hook_ids: Vec<HandlerId> = app
+        .after_hooks
+        .read()
+        .unwrap()
+        .matching(req.uri().path(), req.method().as_str())
+        .map(|hook| hook.id)
+        .collect();
+    if hook_ids.is_empty() && native.is_empty() {
+        return next.run(req).await;
+    }
+
+    let hook_request = HookRequest { started: Instant::now() };
+    let js_request = (!hook_ids.is_empty()).then(|| {
+        let mut request = crate::request::Request::from_axum_request(&req);
+        request.app_id = app.id;
+        if let Some(query) = req.uri().query() {
+            extract_query_parameters(&mut request, query);
+        }
+        request
+    });
+
+    let mut response = next.run(req).await;
+
+    if let Some(request) = js_request {
+        response = run_js_hooks(&app, hook_ids, request, hook_request.started, response, timeout).await;
+    }
+    for hook in &native.hooks {
+        hook.on_response(&hook_request, &mut response);
+    }
+    response
+}
@@ -0,0 +1,72 @@
+use axum::body::{Body, Bytes};
+use log::debug;
+use neon::prelude::*;
+use neon::types::buffer::TypedArray;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+// Chunks buffered before JS writes start waiting for the client
+const STREAM_BUFFER_CHUNKS: usize = 16;
+
+/// Receiving side of a streamed response body
+pub type ResponseChunks = mpsc::Receiver<Result<Bytes, std::io::Error>>;
+
+/// Pipe a JS readable stream (any async iterable) into a response body channel.
+///
+/// JS pushes chunks through a native `write(chunk, error?)` function whose Promise
+/// resolves once the chunk is queued, so a slow client applies backpressure.
+/// It resolves to `false` when the client has gone away.
+pub fn open_response_stream<'a, 'b>(
+    cx: &mut Cx<'a>,
+    stream: Handle<'b, JsObject>,
+    runtime: tokio::runtime::Handle,
+) -> NeonResult<ResponseChunks> {
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_CHUNKS);
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let channel = cx.channel();
+
+    let write = JsFunction::new(cx, move |mut cx| {
+        let chunk = cx
+            .argument_opt(0)
+            .and_then(|arg| arg.downcast::<JsBuffer, _>(&mut cx).ok())
+            .map(|buffer| Bytes::copy_from_slice(buffer.as_slice(&cx)));
+        let error = cx
+            .argument_opt(1)
+            .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+            .map(|message| message.value(&mut cx));
+
+        // End of stream or error: take the sender so the body finishes once drained
+        let tx = match (&chunk, &error) {
+            (Some(_), None) => sender.lock().unwrap().clone(),
+            _ => sender.lock().unwrap().take(),
+        };
+
+        let (deferred, promise) = cx.promise();
+        let channel = channel.clone();
+        runtime.spawn(async move {
+            let open = match (tx, chunk, error) {
+                (Some(tx), _, Some(error)) => {
+                    debug!("❌ Response stream failed: {}", error);
+                    let _ = tx.send(Err(std::io::Error::other(error))).await;
+                    false
+                }
+                (Some(tx), Some(chunk), None) => tx.send(Ok(chunk)).await.is_ok(),
+                _ => false,
+            };
+            deferred.settle_with(&channel, move |mut cx| Ok(cx.boolean(open)));
+        });
+
+        Ok(promise)
+    })?;
+
+    let global: Handle<JsObject> = cx.global("global")?;
+    let pipe_fn: Handle<JsFunction> = global.get(cx, "pipeResponseStream")?;
+    pipe_fn.call_with(cx).arg(stream).arg(write).exec(cx)?;
+
+    Ok(rx)
+}
+
+/// Response body fed by a stream channel, sent with chunked transfer encoding
+pub fn stream_body(mut chunks: ResponseChunks) -> Body {
+    Body::from_stream(futures_util::stream::poll_fn(move |cx| chunks.poll_recv(cx)))
+}
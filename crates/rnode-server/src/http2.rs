@@ -0,0 +1,79 @@
+use hyper_util::rt::{TokioExecutor, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// `http2` option of listen(); HTTP/2 is offered over TLS unless the option is `false`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Http2Options {
+    pub h2c: bool, // Accept prior-knowledge HTTP/2 on plain listeners
+    pub max_concurrent_streams: Option<u32>,
+    pub initial_stream_window_size: Option<u32>,
+    pub initial_connection_window_size: Option<u32>,
+    pub adaptive_window: bool, // BDP-based flow control, overrides the window sizes
+    pub max_frame_size: Option<u32>,
+    pub max_header_list_size: Option<u32>,
+    pub keep_alive_interval_ms: Option<u64>, // PING idle connections at this interval
+    pub keep_alive_timeout_ms: Option<u64>, // Close the connection if a PING is not answered in time
+}
+
+/// ALPN protocols a TLS listener offers, in preference order
+pub fn alpn_protocols(http2: Option<&Http2Options>) -> Vec<Vec<u8>> {
+    match http2 {
+        Some(_) => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        None => vec![b"http/1.1".to_vec()],
+    }
+}
+
+/// Apply the HTTP/2 settings to a connection builder; without them only HTTP/1.1 is served
+pub fn configure(builder: &mut Builder<TokioExecutor>, http2: Option<&Http2Options>) {
+    let Some(options) = http2 else {
+        *builder = std::mem::replace(builder, Builder::new(TokioExecutor::new())).http1_only();
+        return;
+    };
+
+    // Unset values keep hyper's defaults; a None limit would lift the stream cap
+    let mut h2 = builder.http2();
+    h2.timer(TokioTimer::new())
+        .initial_stream_window_size(options.initial_stream_window_size)
+        .initial_connection_window_size(options.initial_connection_window_size)
+        .max_frame_size(options.max_frame_size)
+        .keep_alive_interval(options.keep_alive_interval_ms.map(Duration::from_millis));
+    if options.adaptive_window {
+        h2.adaptive_window(true);
+    }
+    if let Some(max) = options.max_concurrent_streams {
+        h2.max_concurrent_streams(max);
+    }
+    if let Some(size) = options.max_header_list_size {
+        h2.max_header_list_size(size);
+    }
+    if let Some(timeout) = options.keep_alive_timeout_ms {
+        h2.keep_alive_timeout(Duration::from_millis(timeout));
+    }
+}
+
+/// Protocol version as Node.js reports it in `httpVersion`
+pub fn version_name(version: http::Version) -> &'static str {
+    match version {
+        http::Version::HTTP_09 => "0.9",
+        http::Version::HTTP_10 => "1.0",
+        http::Version::HTTP_2 => "2.0",
+        http::Version::HTTP_3 => "3.0",
+        _ => "1.1",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alpn_and_version_names() {
+        assert_eq!(alpn_protocols(Some(&Http2Options::default())), vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+        assert_eq!(alpn_protocols(None), vec![b"http/1.1".to_vec()]);
+        assert_eq!(version_name(http::Version::HTTP_2), "2.0");
+        assert_eq!(version_name(http::Version::HTTP_11), "1.1");
+    }
+}
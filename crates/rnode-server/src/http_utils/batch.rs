@@ -9,6 +9,11 @@ use crate::http_utils::client::make_http_request;
 pub fn http_batch(mut cx: FunctionContext) -> JsResult<JsString> {
     let requests_json = cx.argument::<JsString>(0)?.value(&mut cx);
     let timeout = cx.argument::<JsNumber>(1)?.value(&mut cx) as u64;
+    // Trace context of the JS code making the calls
+    let traceparent = cx
+        .argument_opt(2)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .map(|value| value.value(&mut cx));
 
     info!("🔄 Batch HTTP requests with timeout: {}ms", timeout);
     debug!("📝 Requests: {}", requests_json);
@@ -28,7 +33,7 @@ pub fn http_batch(mut cx: FunctionContext) -> JsResult<JsString> {
             .unwrap();
 
         rt.block_on(async {
-            let results = execute_batch_requests(requests, timeout).await;
+            let results = execute_batch_requests(requests, timeout, traceparent).await;
             let _ = tx.send(results);
         });
     });
@@ -50,6 +55,7 @@ pub fn http_batch(mut cx: FunctionContext) -> JsResult<JsString> {
 pub async fn execute_batch_requests(
     requests: Vec<serde_json::Value>,
     timeout_ms: u64,
+    traceparent: Option<String>,
 ) -> String {
     let mut futures = Vec::new();
 
@@ -70,9 +76,10 @@ pub async fn execute_batch_requests(
         let url_clone = url.clone();
         let headers_clone = headers.clone();
         let body_clone = body.clone();
+        let traceparent = traceparent.clone();
         
         let future = async move {
-            let result = make_http_request(method_clone, url_clone, headers_clone, body_clone, timeout_ms).await;
+            let result = make_http_request(method_clone, url_clone, headers_clone, body_clone, timeout_ms, traceparent).await;
             // Add request index to result for association
             let mut result_json: serde_json::Value = serde_json::from_str(&result).unwrap_or_else(|_| serde_json::json!({}));
             result_json["requestIndex"] = serde_json::Value::Number(serde_json::Number::from(index));
@@ -2,6 +2,7 @@ use neon::prelude::*;
 use serde_json;
 use log::{debug, error, info, warn};
 use std::collections::HashMap;
+use crate::telemetry::{Span, SpanKind};
 
 // HTTP client utility for making requests from the backend
 pub fn http_request(mut cx: FunctionContext) -> JsResult<JsString> {
@@ -10,6 +11,11 @@ pub fn http_request(mut cx: FunctionContext) -> JsResult<JsString> {
     let headers_json = cx.argument::<JsString>(2)?.value(&mut cx);
     let body_json = cx.argument::<JsString>(3)?.value(&mut cx);
     let timeout = cx.argument::<JsNumber>(4)?.value(&mut cx) as u64;
+    // Trace context of the JS code making the call
+    let traceparent = cx
+        .argument_opt(5)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .map(|value| value.value(&mut cx));
 
     info!("🌐 HTTP request: {} {}", method, url);
     debug!("📝 Headers: {}", headers_json);
@@ -38,7 +44,7 @@ pub fn http_request(mut cx: FunctionContext) -> JsResult<JsString> {
             .unwrap();
 
         rt.block_on(async {
-            let result = make_http_request(method.clone(), url.clone(), headers.clone(), body.clone(), timeout).await;
+            let result = make_http_request(method.clone(), url.clone(), headers.clone(), body.clone(), timeout, traceparent).await;
             let _ = tx.send(result);
         });
     });
@@ -63,9 +69,21 @@ pub async fn make_http_request(
     headers: HashMap<String, String>,
     body: Option<String>,
     timeout_ms: u64,
+    traceparent: Option<String>,
 ) -> String {
     use tokio::time::Duration;
 
+    // Client span; the callee continues the trace from the injected traceparent
+    let mut span = Span::child_of(traceparent.as_deref(), format!("HTTP {}", method.to_uppercase()), SpanKind::Client);
+    let mut headers = headers;
+    if let Some(span) = span.as_mut() {
+        span.set_attribute("http.request.method", method.to_uppercase());
+        span.set_attribute("url.full", url.clone());
+        if !headers.keys().any(|name| name.eq_ignore_ascii_case("traceparent")) {
+            headers.insert("traceparent".to_string(), span.context().traceparent());
+        }
+    }
+
     // Create HTTP client
     let client = reqwest::Client::builder()
         .timeout(Duration::from_millis(timeout_ms))
@@ -101,6 +119,12 @@ pub async fn make_http_request(
     match tokio::time::timeout(Duration::from_millis(timeout_ms), request_builder.send()).await {
         Ok(Ok(response)) => {
             let status = response.status().as_u16();
+            if let Some(span) = span.as_mut() {
+                span.set_attribute("http.response.status_code", status);
+                if status >= 500 {
+                    span.set_error(response.status().to_string());
+                }
+            }
             let response_headers: HashMap<String, String> = response
                 .headers()
                 .iter()
@@ -136,6 +160,9 @@ pub async fn make_http_request(
         }
         Ok(Err(e)) => {
             error!("❌ HTTP request failed: {} {} -> {:?}", method, url, e);
+            if let Some(span) = span.as_mut() {
+                span.set_error(e.to_string());
+            }
             let error_response = serde_json::json!({
                 "success": false,
                 "error": format!("HTTP request failed: {:?}", e),
@@ -147,6 +174,9 @@ pub async fn make_http_request(
         }
         Err(_) => {
             error!("⏰ HTTP request timeout: {} {} after {}ms", method, url, timeout_ms);
+            if let Some(span) = span.as_mut() {
+                span.set_error("timeout");
+            }
             let timeout_response = serde_json::json!({
                 "success": false,
                 "error": format!("HTTP request timeout after {}ms", timeout_ms),
@@ -0,0 +1,156 @@
+use crate::listener::{ListenAddress, parse_listen_address};
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{StatusCode, header};
+use log::debug;
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+const DEFAULT_PORT: u16 = 80;
+
+/// `httpsRedirect` option of listen()
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpsRedirectOptions {
+    pub port: Option<u16>, // Plain listener port (default 80)
+    pub host: Option<String>, // Bind address, defaults to the IP of the main TCP listener
+    pub https_port: Option<u16>, // Port in the Location; listen() fills in the bound TCP port, 443 otherwise
+    pub hostname: Option<String>, // Host in the Location, defaults to the request's Host
+    pub status: Option<u16>, // 301 (default) or 308, which keeps the method and body
+    pub exclude: Vec<String>, // Path prefixes the app serves over plain HTTP
+}
+
+impl HttpsRedirectOptions {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_PORT)
+    }
+
+    /// Address of the plain listener; None without `host` when the server has no TCP listener
+    pub fn listen_address(&self, main: Option<SocketAddr>) -> Option<ListenAddress> {
+        match &self.host {
+            Some(host) => parse_listen_address(host, self.port()),
+            None => main.map(|addr| ListenAddress::Tcp(SocketAddr::new(addr.ip(), self.port()))),
+        }
+    }
+}
+
+/// Redirect rules of the plain listener
+#[derive(Debug)]
+pub struct HttpsRedirect {
+    status: StatusCode,
+    https_port: u16,
+    hostname: Option<String>,
+    exclude: Vec<String>,
+}
+
+impl HttpsRedirect {
+    pub fn new(options: &HttpsRedirectOptions) -> Self {
+        Self {
+            status: match options.status {
+                Some(308) => StatusCode::PERMANENT_REDIRECT,
+                _ => StatusCode::MOVED_PERMANENTLY,
+            },
+            https_port: options.https_port.unwrap_or(443),
+            hostname: options.hostname.clone(),
+            exclude: options.exclude.iter().map(|path| path.trim_end_matches('/').to_string()).collect(),
+        }
+    }
+
+    // Prefixes match whole path segments: "/.well-known/acme-challenge" covers its tokens
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.iter().any(|prefix| {
+            prefix.is_empty()
+                || path == prefix
+                || path.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    // HTTPS URL of the same resource; None without a host to redirect to
+    fn location(&self, host: Option<&str>, path_and_query: &str) -> Option<String> {
+        let host = match &self.hostname {
+            Some(hostname) => hostname.as_str(),
+            None => strip_port(host?),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(match self.https_port {
+            443 => format!("https://{}{}", host, path_and_query),
+            port => format!("https://{}:{}{}", host, port, path_and_query),
+        })
+    }
+}
+
+// Host without its port; IPv6 literals keep their brackets
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.find(']').map(|end| &host[..=end]).unwrap_or(host);
+    }
+    match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|ch| ch.is_ascii_digit()) => name,
+        _ => host,
+    }
+}
+
+/// Redirect every request to HTTPS except excluded paths, which reach the app
+pub async fn https_redirect_layer(config: Arc<HttpsRedirect>, req: Request, next: Next) -> Response {
+    if config.is_excluded(req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    // HTTP/1.1 sends the Host header, HTTP/2 the :authority
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| req.uri().authority().map(|authority| authority.as_str()));
+    let path_and_query = req.uri().path_and_query().map(|value| value.as_str()).unwrap_or("/");
+
+    match config.location(host, path_and_query) {
+        Some(location) => {
+            debug!("↪️ Redirecting {} to {}", req.uri(), location);
+            (config.status, [(header::LOCATION, location)]).into_response()
+        }
+        None => (StatusCode::BAD_REQUEST, "Missing Host header").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_location_and_exclusions() {
+        let redirect = HttpsRedirect::new(&HttpsRedirectOptions {
+            https_port: Some(8443),
+            exclude: vec!["/.well-known/acme-challenge/".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            redirect.location(Some("example.com:8080"), "/a/b?x=1").as_deref(),
+            Some("https://example.com:8443/a/b?x=1")
+        );
+        assert_eq!(redirect.location(Some("[::1]:80"), "/").as_deref(), Some("https://[::1]:8443/"));
+        assert_eq!(redirect.location(None, "/"), None);
+        assert!(redirect.is_excluded("/.well-known/acme-challenge/token"));
+        assert!(!redirect.is_excluded("/.well-known/acme-challenge-other"));
+
+        let default_port = HttpsRedirect::new(&HttpsRedirectOptions::default());
+        assert_eq!(default_port.location(Some("example.com"), "/").as_deref(), Some("https://example.com/"));
+        assert_eq!(default_port.status, StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[test]
+    fn test_listen_address() {
+        let main: SocketAddr = "0.0.0.0:8443".parse().unwrap();
+        let defaults = HttpsRedirectOptions::default();
+        assert_eq!(defaults.listen_address(Some(main)), Some(ListenAddress::Tcp("0.0.0.0:80".parse().unwrap())));
+        // Unix-only servers need an explicit host
+        assert_eq!(defaults.listen_address(None), None);
+
+        let explicit = HttpsRedirectOptions { port: Some(8080), host: Some("::1".to_string()), ..Default::default() };
+        assert_eq!(explicit.listen_address(Some(main)), Some(ListenAddress::Tcp("[::1]:8080".parse().unwrap())));
+    }
+}
@@ -1,19 +1,35 @@
 use neon::prelude::*;
 
 // Import modules
+mod access_log;
+mod app;
 mod cache;
+mod client_cert;
+mod client_ip;
 mod compression;
+mod connection;
+mod cors;
 mod file_operations;
 mod handlers;
 mod html_templates;
+mod http2;
 mod http_utils;
+mod https_redirect;
+mod listener;
 mod metrics;
+mod rate_limit;
 mod request;
 mod request_parser;
+mod route_table;
 mod routes;
+mod security_headers;
 mod server;
+mod shutdown;
+mod sse;
 mod static_files;
+mod telemetry;
 mod templates;
+mod tls;
 mod types;
 mod utils;
 mod websocket;
@@ -28,6 +44,7 @@ use utils::*;
 use cache::neon_wrappers::*;
 use http_utils::*;
 use server::start_listen;
+use shutdown::close_server;
 
 #[neon::main]
 fn main(mut cx: ModuleContext) -> NeonResult<()> {
@@ -43,7 +60,12 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
     cx.export_function("any", register_any)?;
     cx.export_function("trace", register_trace)?;
     cx.export_function("use", register_middleware)?;
+    cx.export_function("after", register_after_hook)?;
+    cx.export_function("removeRoute", remove_route)?;
+    cx.export_function("removeMiddleware", remove_middleware)?;
     cx.export_function("listen", start_listen)?;
+    cx.export_function("close", close_server)?;
+    cx.export_function("reloadTls", tls::reload_tls)?;
     cx.export_function("processHttpRequest", process_http_request)?;
     cx.export_function("loadStaticFiles", load_static_files)?;
     cx.export_function("clearStaticCache", clear_static_cache)?;
@@ -78,6 +100,9 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
     cx.export_function("getClientInfo", websocket::get_client_info)?;
     cx.export_function("getUserRooms", websocket::get_user_rooms)?;
 
+    // Export Server-Sent Events functions
+    cx.export_function("registerSse", sse::register_sse)?;
+
     // Export cache functions
     cx.export_function("initCacheSystem", init_cache_system_wrapper)?;
     cx.export_function("cacheGet", cache_get_wrapper)?;
@@ -0,0 +1,254 @@
+use log::{debug, warn};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+
+// Backlog for listening sockets
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Address the server listens on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenAddress::Tcp(addr) => write!(f, "{}", addr),
+            ListenAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Bound listener ready to be served
+pub enum BoundListener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+/// Parse a listen address specification.
+///
+/// Accepted forms: `127.0.0.1`, `::`, `0.0.0.0:8080`, `[::1]:8080`,
+/// `localhost`, `example.local:8080` and `unix:/path/to/socket`.
+/// Addresses without a port use `default_port`.
+pub fn parse_listen_address(spec: &str, default_port: u16) -> Option<ListenAddress> {
+    let spec = spec.trim();
+
+    if let Some(path) = spec.strip_prefix("unix:") {
+        if path.is_empty() {
+            return None;
+        }
+        return Some(ListenAddress::Unix(PathBuf::from(path)));
+    }
+
+    // Full socket address (with port)
+    if let Ok(addr) = spec.parse::<SocketAddr>() {
+        return Some(ListenAddress::Tcp(addr));
+    }
+
+    // Bare IP address, including IPv6 in brackets
+    let bare = spec.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = bare.parse::<IpAddr>() {
+        return Some(ListenAddress::Tcp(SocketAddr::new(ip, default_port)));
+    }
+
+    match spec {
+        "" | "localhost" => {
+            return Some(ListenAddress::Tcp(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                default_port,
+            )));
+        }
+        "0" => {
+            return Some(ListenAddress::Tcp(SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                default_port,
+            )));
+        }
+        _ => {}
+    }
+
+    // Host name, optionally with port
+    let with_port = if spec.contains(':') {
+        spec.to_string()
+    } else {
+        format!("{}:{}", spec, default_port)
+    };
+    match with_port.to_socket_addrs() {
+        Ok(mut addrs) => addrs.next().map(ListenAddress::Tcp),
+        Err(e) => {
+            warn!("Failed to resolve listen address {}: {}", spec, e);
+            None
+        }
+    }
+}
+
+/// Parse the host argument plus additional listen addresses, dropping duplicates
+pub fn parse_listen_addresses(host: &str, port: u16, extra: &[String]) -> Vec<ListenAddress> {
+    let mut addresses = Vec::new();
+
+    for spec in std::iter::once(host).chain(extra.iter().map(|s| s.as_str())) {
+        match parse_listen_address(spec, port) {
+            Some(addr) if !addresses.contains(&addr) => addresses.push(addr),
+            Some(_) => {}
+            None => warn!("Invalid listen address: {}, skipping", spec),
+        }
+    }
+
+    if addresses.is_empty() {
+        warn!("No valid listen address, using 127.0.0.1:{}", port);
+        addresses.push(ListenAddress::Tcp(SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+        )));
+    }
+
+    addresses
+}
+
+// Remove a socket left by a previous run; anything else at the path is refused
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    // A socket someone still accepts on belongs to a live server
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("another server is listening on {}", path.display()),
+        ));
+    }
+    debug!("🧹 Removing stale Unix socket {}", path.display());
+    std::fs::remove_file(path)
+}
+
+/// Bind a listen address.
+///
+/// Unspecified IPv6 addresses (`::`) are bound dual-stack unless `ipv6_only` is set,
+/// so a single socket accepts both IPv4 and IPv6 clients.
+pub fn bind(address: &ListenAddress, ipv6_only: bool) -> std::io::Result<BoundListener> {
+    match address {
+        ListenAddress::Tcp(addr) => {
+            use socket2::{Domain, Protocol, Socket, Type};
+
+            let socket = Socket::new(Domain::for_address(*addr), Type::STREAM, Some(Protocol::TCP))?;
+            socket.set_reuse_address(true)?;
+            if addr.is_ipv6() {
+                socket.set_only_v6(ipv6_only)?;
+            }
+            socket.bind(&(*addr).into())?;
+            socket.listen(LISTEN_BACKLOG)?;
+            socket.set_nonblocking(true)?;
+
+            let listener = tokio::net::TcpListener::from_std(socket.into())?;
+            debug!("🔌 Bound TCP listener on {}", addr);
+            Ok(BoundListener::Tcp(listener))
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            remove_stale_socket(path)?;
+            let listener = tokio::net::UnixListener::bind(path)?;
+            debug!("🔌 Bound Unix socket listener on {}", path.display());
+            Ok(BoundListener::Unix(listener, path.clone()))
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(path) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("Unix sockets are not supported on this platform: {}", path.display()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listen_address() {
+        assert_eq!(
+            parse_listen_address("0.0.0.0", 3000),
+            Some(ListenAddress::Tcp("0.0.0.0:3000".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_listen_address("::", 3000),
+            Some(ListenAddress::Tcp("[::]:3000".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_listen_address("[::1]:8080", 3000),
+            Some(ListenAddress::Tcp("[::1]:8080".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_listen_address("localhost", 3000),
+            Some(ListenAddress::Tcp("127.0.0.1:3000".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_listen_address("unix:/tmp/rnode.sock", 3000),
+            Some(ListenAddress::Unix(PathBuf::from("/tmp/rnode.sock")))
+        );
+        assert_eq!(parse_listen_address("unix:", 3000), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_socket() {
+        let path = std::env::temp_dir().join(format!("rnode-listener-{}.sock", std::process::id()));
+        let address = ListenAddress::Unix(path.clone());
+        let _ = std::fs::remove_file(&path);
+
+        // A regular file at the path is left alone
+        std::fs::write(&path, "config").unwrap();
+        let err = bind(&address, false).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "config");
+        std::fs::remove_file(&path).unwrap();
+
+        // A live socket is refused, a stale one replaced
+        let Ok(BoundListener::Unix(listener, _)) = bind(&address, false) else { panic!("bind failed") };
+        assert_eq!(bind(&address, false).err().unwrap().kind(), std::io::ErrorKind::AddrInUse);
+        let client = tokio::net::UnixStream::connect(&path).await.unwrap();
+        listener.accept().await.unwrap();
+        drop((client, listener));
+        assert!(matches!(bind(&address, false), Ok(BoundListener::Unix(..))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bind_dual_stack() {
+        // Skipped where the host has no IPv6
+        let Ok(BoundListener::Tcp(listener)) = bind(&ListenAddress::Tcp("[::]:0".parse().unwrap()), false) else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+        let client = tokio::net::TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (_, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer.port(), client.local_addr().unwrap().port());
+
+        let Ok(BoundListener::Tcp(v6_only)) = bind(&ListenAddress::Tcp("[::]:0".parse().unwrap()), true) else {
+            panic!("bind failed");
+        };
+        let port = v6_only.local_addr().unwrap().port();
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_listen_addresses_dedup() {
+        let extra = vec!["127.0.0.1:3000".to_string(), "[::]:3000".to_string()];
+        let addresses = parse_listen_addresses("127.0.0.1", 3000, &extra);
+        assert_eq!(addresses.len(), 2);
+    }
+}
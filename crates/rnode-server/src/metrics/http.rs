@@ -1,5 +1,8 @@
 use axum::{body::Body, extract::Request, middleware::Next, response::IntoResponse};
-use prometheus::{HistogramVec, IntCounterVec, opts, register_histogram_vec, register_int_counter_vec};
+use prometheus::{
+    HistogramVec, IntCounterVec, IntGaugeVec, opts, register_histogram_vec,
+    register_int_counter_vec, register_int_gauge_vec,
+};
 use std::sync::OnceLock;
 use std::time::Instant;
 
@@ -10,6 +13,15 @@ static HTTP_STATUS_COUNTER: OnceLock<IntCounterVec> = OnceLock::new();
 static SLOW_REQUESTS: OnceLock<IntCounterVec> = OnceLock::new();
 static PENDING_REQUESTS: OnceLock<prometheus::IntGauge> = OnceLock::new();
 static TOTAL_CONNECTIONS: OnceLock<prometheus::IntCounter> = OnceLock::new();
+static ACTIVE_CONNECTIONS: OnceLock<prometheus::IntGauge> = OnceLock::new();
+static CONNECTIONS_REJECTED: OnceLock<prometheus::IntCounter> = OnceLock::new();
+static SSE_CONNECTIONS_ACTIVE: OnceLock<IntGaugeVec> = OnceLock::new();
+static SSE_CONNECTIONS_TOTAL: OnceLock<IntCounterVec> = OnceLock::new();
+static SSE_EVENTS_SENT: OnceLock<IntCounterVec> = OnceLock::new();
+static SSE_CONNECTION_DURATION: OnceLock<HistogramVec> = OnceLock::new();
+static CORS_REJECTED: OnceLock<prometheus::IntCounter> = OnceLock::new();
+static RATE_LIMITED: OnceLock<IntCounterVec> = OnceLock::new();
+static REQUESTS_CANCELLED: OnceLock<IntCounterVec> = OnceLock::new();
 
 pub fn init_http_metrics() {
     HTTP_REQUESTS_TOTAL
@@ -75,6 +87,99 @@ pub fn init_http_metrics() {
             .expect("Can't create total connections metric"),
         )
         .expect("TOTAL_CONNECTIONS already initialized");
+
+    ACTIVE_CONNECTIONS
+        .set(
+            prometheus::register_int_gauge!(opts!(
+                "rnode_server_active_connections",
+                "Open connections"
+            ))
+            .expect("Can't create active connections metric"),
+        )
+        .expect("ACTIVE_CONNECTIONS already initialized");
+
+    CONNECTIONS_REJECTED
+        .set(
+            prometheus::register_int_counter!(opts!(
+                "rnode_server_connections_rejected_total",
+                "Connections rejected over the maxConnections limit"
+            ))
+            .expect("Can't create rejected connections metric"),
+        )
+        .expect("CONNECTIONS_REJECTED already initialized");
+
+    SSE_CONNECTIONS_ACTIVE
+        .set(
+            register_int_gauge_vec!(
+                opts!("rnode_server_sse_connections_active", "Open SSE connections"),
+                &["path"]
+            )
+            .expect("Can't create SSE active connections metric"),
+        )
+        .expect("SSE_CONNECTIONS_ACTIVE already initialized");
+
+    SSE_CONNECTIONS_TOTAL
+        .set(
+            register_int_counter_vec!(
+                opts!("rnode_server_sse_connections_total", "Total SSE connections"),
+                &["path"]
+            )
+            .expect("Can't create SSE connections total metric"),
+        )
+        .expect("SSE_CONNECTIONS_TOTAL already initialized");
+
+    SSE_EVENTS_SENT
+        .set(
+            register_int_counter_vec!(
+                opts!("rnode_server_sse_events_sent_total", "Total SSE frames sent"),
+                &["path", "kind"]
+            )
+            .expect("Can't create SSE events sent metric"),
+        )
+        .expect("SSE_EVENTS_SENT already initialized");
+
+    SSE_CONNECTION_DURATION
+        .set(
+            register_histogram_vec!(
+                "rnode_server_sse_connection_duration_seconds",
+                "SSE connection duration in seconds",
+                &["path"],
+                vec![1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 3600.0]
+            )
+            .expect("Can't create SSE connection duration metric"),
+        )
+        .expect("SSE_CONNECTION_DURATION already initialized");
+
+    CORS_REJECTED
+        .set(
+            // No origin label: the header is client-controlled and would create unbounded series
+            prometheus::register_int_counter!(opts!(
+                "rnode_server_cors_rejected_total",
+                "Requests from origins rejected by CORS"
+            ))
+            .expect("Can't create CORS rejected metric"),
+        )
+        .expect("CORS_REJECTED already initialized");
+
+    RATE_LIMITED
+        .set(
+            register_int_counter_vec!(
+                opts!("rnode_server_rate_limited_total", "Requests rejected by the rate limiter"),
+                &["path"]
+            )
+            .expect("Can't create rate limited metric"),
+        )
+        .expect("RATE_LIMITED already initialized");
+
+    REQUESTS_CANCELLED
+        .set(
+            register_int_counter_vec!(
+                opts!("rnode_server_requests_cancelled_total", "Requests whose JS handler or middleware was aborted"),
+                &["path", "reason"]
+            )
+            .expect("Can't create requests cancelled metric"),
+        )
+        .expect("REQUESTS_CANCELLED already initialized");
 }
 
 pub fn record_http_request(method: &str, path: &str, status: &str, duration: f64) {
@@ -106,16 +211,76 @@ pub fn record_http_request(method: &str, path: &str, status: &str, duration: f64
     }
 }
 
-pub fn update_pending_requests(count: i64) {
+pub fn add_pending_requests(delta: i64) {
     if let Some(gauge) = PENDING_REQUESTS.get() {
-        gauge.set(count);
+        gauge.add(delta);
+    }
+}
+
+pub fn record_sse_connection_start(path: &str) {
+    if let Some(gauge) = SSE_CONNECTIONS_ACTIVE.get() {
+        gauge.with_label_values(&[path]).inc();
+    }
+
+    if let Some(counter) = SSE_CONNECTIONS_TOTAL.get() {
+        counter.with_label_values(&[path]).inc();
     }
 }
 
-pub fn increment_total_connections() {
+pub fn record_sse_connection_end(path: &str, duration: f64) {
+    if let Some(gauge) = SSE_CONNECTIONS_ACTIVE.get() {
+        gauge.with_label_values(&[path]).dec();
+    }
+
+    if let Some(histogram) = SSE_CONNECTION_DURATION.get() {
+        histogram.with_label_values(&[path]).observe(duration);
+    }
+}
+
+// kind: "event", "replay" or "keepalive"
+pub fn record_sse_event(path: &str, kind: &str) {
+    if let Some(counter) = SSE_EVENTS_SENT.get() {
+        counter.with_label_values(&[path, kind]).inc();
+    }
+}
+
+pub fn record_cors_rejected() {
+    if let Some(counter) = CORS_REJECTED.get() {
+        counter.inc();
+    }
+}
+
+pub fn record_rate_limited(path: &str) {
+    if let Some(counter) = RATE_LIMITED.get() {
+        counter.with_label_values(&[path]).inc();
+    }
+}
+
+pub fn record_request_cancelled(path: &str, reason: &str) {
+    if let Some(counter) = REQUESTS_CANCELLED.get() {
+        counter.with_label_values(&[path, reason]).inc();
+    }
+}
+
+pub fn record_connection_opened() {
     if let Some(counter) = TOTAL_CONNECTIONS.get() {
         counter.inc();
     }
+    if let Some(gauge) = ACTIVE_CONNECTIONS.get() {
+        gauge.inc();
+    }
+}
+
+pub fn record_connection_closed() {
+    if let Some(gauge) = ACTIVE_CONNECTIONS.get() {
+        gauge.dec();
+    }
+}
+
+pub fn record_connection_rejected() {
+    if let Some(counter) = CONNECTIONS_REJECTED.get() {
+        counter.inc();
+    }
 }
 
 // Middleware to track HTTP metrics
@@ -128,20 +293,12 @@ pub async fn track_metrics(req: Request<Body>, next: Next) -> impl IntoResponse
     };
     let method = req.method().clone();
 
-    // Track new connection
-    crate::metrics::http::increment_total_connections();
-
-    // Increment pending requests counter
-    crate::metrics::http::update_pending_requests(1);
-
+    // Connections and pending requests are counted by the connection layer
     let response = next.run(req).await;
 
     let latency = start.elapsed().as_secs_f64();
     let status = response.status().as_u16().to_string();
 
-    // Decrement pending requests counter
-    crate::metrics::http::update_pending_requests(-1);
-
     // Update Prometheus metrics
     crate::metrics::http::record_http_request(&method.to_string(), &path, &status, latency);
 
@@ -27,11 +27,8 @@ pub fn init_metrics() {
     cache::init_cache_metrics();
     
     // Only initialize WebSocket metrics if WebSocket routes are registered
-    let routes = crate::websocket::get_websocket_routes();
-    if let Ok(routes) = routes.try_read() {
-        if !routes.is_empty() {
-            websocket::init_websocket_metrics();
-        }
+    if crate::websocket::has_websocket_routes() {
+        websocket::init_websocket_metrics();
     }
 }
 
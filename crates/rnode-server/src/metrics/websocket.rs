@@ -245,12 +245,9 @@ pub fn record_error(error_type: &str, path: &str, room_id: Option<&str>) {
 // Update WebSocket metrics from current state
 pub fn update_websocket_metrics() {
     // Check if WebSocket routes are registered
-    let routes = crate::websocket::get_websocket_routes();
-    if let Ok(routes) = routes.try_read() {
-        if routes.is_empty() {
-            // No WebSocket routes registered, skip metrics update
-            return;
-        }
+    if !crate::websocket::has_websocket_routes() {
+        // No WebSocket routes registered, skip metrics update
+        return;
     }
 
     // Update active connections count
@@ -0,0 +1,170 @@
+//! Pure limiter math shared by the in-memory store and the Redis scripts
+
+/// Outcome of one rate limit check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u64,
+    pub remaining: u64,
+    // Milliseconds until the limit is fully available again
+    pub reset_ms: u64,
+    // Milliseconds until the next request would be allowed (0 when allowed)
+    pub retry_after_ms: u64,
+}
+
+/// Token bucket: `limit` tokens, refilled evenly over the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketState {
+    pub tokens: f64,
+    pub updated_ms: u64,
+}
+
+/// Sliding window counter: request counts of the current and previous fixed windows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowState {
+    pub window: u64,
+    pub previous: u64,
+    pub current: u64,
+}
+
+pub fn token_bucket(state: Option<BucketState>, limit: u64, window_ms: u64, now_ms: u64) -> (BucketState, Decision) {
+    let (mut tokens, updated_ms) = match state {
+        Some(state) if now_ms > state.updated_ms => (
+            (state.tokens + ((now_ms - state.updated_ms) * limit) as f64 / window_ms as f64).min(limit as f64),
+            now_ms,
+        ),
+        Some(state) => (state.tokens, state.updated_ms),
+        None => (limit as f64, now_ms),
+    };
+
+    let allowed = tokens >= 1.0;
+    if allowed {
+        tokens -= 1.0;
+    }
+
+    (BucketState { tokens, updated_ms }, bucket_decision(allowed, tokens, limit, window_ms))
+}
+
+/// Headers for a bucket holding `tokens` after the check
+pub fn bucket_decision(allowed: bool, tokens: f64, limit: u64, window_ms: u64) -> Decision {
+    // Milliseconds to refill `missing` tokens
+    let refill = |missing: f64| (missing.max(0.0) * window_ms as f64 / limit as f64).ceil() as u64;
+    Decision {
+        allowed,
+        limit,
+        remaining: tokens.floor().max(0.0) as u64,
+        reset_ms: refill(limit as f64 - tokens),
+        retry_after_ms: if allowed { 0 } else { refill(1.0 - tokens) },
+    }
+}
+
+// Previous window's count weighted by how much of it still overlaps the sliding window
+fn estimate(previous: u64, current: u64, window_ms: u64, now_ms: u64) -> f64 {
+    let weight = (window_ms - now_ms % window_ms) as f64 / window_ms as f64;
+    previous as f64 * weight + current as f64
+}
+
+pub fn sliding_window(state: Option<WindowState>, limit: u64, window_ms: u64, now_ms: u64) -> (WindowState, Decision) {
+    let window = now_ms / window_ms;
+    let (previous, mut current) = match state {
+        Some(state) if state.window == window => (state.previous, state.current),
+        Some(state) if state.window + 1 == window => (state.current, 0),
+        _ => (0, 0),
+    };
+
+    let allowed = estimate(previous, current, window_ms, now_ms) + 1.0 <= limit as f64;
+    if allowed {
+        current += 1;
+    }
+
+    (
+        WindowState { window, previous, current },
+        window_decision(allowed, previous, current, limit, window_ms, now_ms),
+    )
+}
+
+/// Headers for window counts after the check
+pub fn window_decision(allowed: bool, previous: u64, current: u64, limit: u64, window_ms: u64, now_ms: u64) -> Decision {
+    let elapsed = (now_ms % window_ms) as f64;
+    let window = window_ms as f64;
+    let used = estimate(previous, current, window_ms, now_ms);
+
+    let retry_after_ms = if allowed {
+        0.0
+    } else if previous > 0 && current < limit {
+        // Wait until the previous window has decayed enough for one more request
+        let weight = (limit - current - 1) as f64 / previous as f64;
+        (window * (1.0 - weight) - elapsed).max(1.0)
+    } else {
+        // This window alone is full: wait for it to end and decay
+        let weight = if current > 0 { ((limit as f64 - 1.0) / current as f64).clamp(0.0, 1.0) } else { 1.0 };
+        window - elapsed + window * (1.0 - weight)
+    };
+
+    Decision {
+        allowed,
+        limit,
+        remaining: (limit as f64 - used).floor().max(0.0) as u64,
+        reset_ms: (window - elapsed) as u64,
+        retry_after_ms: retry_after_ms.ceil() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let (mut state, mut decision) = token_bucket(None, 3, 3000, 0);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 2);
+
+        for _ in 0..2 {
+            (state, decision) = token_bucket(Some(state), 3, 3000, 0);
+        }
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        (state, decision) = token_bucket(Some(state), 3, 3000, 500);
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after_ms, 500);
+
+        // One token refills per second
+        let (_, decision) = token_bucket(Some(state), 3, 3000, 1000);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert_eq!(decision.reset_ms, 3000);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let mut state = None;
+        for _ in 0..4 {
+            let (next, decision) = sliding_window(state, 4, 1000, 100);
+            assert!(decision.allowed);
+            state = Some(next);
+        }
+
+        let (next, decision) = sliding_window(state, 4, 1000, 900);
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert_eq!(decision.reset_ms, 100);
+
+        // Halfway into the next window half of the previous count still applies
+        let (next, decision) = sliding_window(Some(next), 4, 1000, 1500);
+        assert!(decision.allowed);
+        assert_eq!(next.previous, 4);
+        assert_eq!(decision.remaining, 1);
+
+        let (next, _) = sliding_window(Some(next), 4, 1000, 1500);
+        let (_, decision) = sliding_window(Some(next), 4, 1000, 1500);
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after_ms, 250);
+
+        // Windows older than the previous one are forgotten
+        let (next, decision) = sliding_window(Some(next), 4, 1000, 5000);
+        assert!(decision.allowed);
+        assert_eq!((next.previous, next.current), (0, 1));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::metrics::http::record_rate_limited;
+use crate::client_ip::ClientIp;
+use axum::extract::{FromRequestParts, MatchedPath, RawPathParams, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use globset::{Glob, GlobMatcher};
+use http::StatusCode;
+use http::header::{self, HeaderMap, HeaderValue};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod algorithm;
+pub mod store;
+
+use algorithm::Decision;
+use store::{RateLimitStore, StoreKind};
+
+const DEFAULT_WINDOW_MS: u64 = 60000;
+
+/// Limiting algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Algorithm {
+    TokenBucket,
+    #[default]
+    SlidingWindow,
+}
+
+/// What requests are counted by: "ip", `{ header }` or `{ param }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RateLimitKey {
+    Header { header: String },
+    Param { param: String },
+    Named(String),
+}
+
+/// Route entry: a rule, or `false` to disable limiting
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RouteRateLimit {
+    Enabled(bool),
+    Rule(RateLimitOptions),
+}
+
+/// `rateLimit` option of listen(); route entries override the top-level fields
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimitOptions {
+    pub algorithm: Option<Algorithm>,
+    pub limit: Option<u64>, // Requests per window (bucket capacity)
+    pub window_ms: Option<u64>,
+    pub key: Option<RateLimitKey>,
+    pub store: StoreKind,
+    pub routes: HashMap<String, RouteRateLimit>, // Overrides by path glob
+}
+
+/// Limit applied to a set of paths
+#[derive(Debug)]
+pub struct RateLimitRule {
+    id: String, // Separates counters of different rules
+    pub algorithm: Algorithm,
+    pub limit: u64,
+    pub window_ms: u64,
+    key: RateLimitKey,
+}
+
+impl RateLimitRule {
+    // Build a rule from route options falling back to the top-level ones
+    fn new(id: &str, options: &RateLimitOptions, base: &RateLimitOptions) -> Option<Self> {
+        let limit = options.limit.or(base.limit)?;
+        let key = options
+            .key
+            .clone()
+            .or_else(|| base.key.clone())
+            .unwrap_or(RateLimitKey::Named("ip".to_string()));
+        if let RateLimitKey::Named(name) = &key
+            && name != "ip"
+        {
+            warn!("⚠️ Unknown rate limit key '{}', limiting by IP", name);
+        }
+
+        Some(Self {
+            id: id.to_string(),
+            algorithm: options.algorithm.or(base.algorithm).unwrap_or_default(),
+            limit,
+            window_ms: options.window_ms.or(base.window_ms).unwrap_or(DEFAULT_WINDOW_MS).max(1),
+            key,
+        })
+    }
+}
+
+/// Compiled rate limit configuration of a server
+#[derive(Debug)]
+pub struct RateLimitConfig {
+    default: Option<Arc<RateLimitRule>>,
+    // Route overrides, most specific pattern first; None disables limiting
+    routes: Vec<(GlobMatcher, Option<Arc<RateLimitRule>>)>,
+    store: Arc<RateLimitStore>,
+}
+
+impl RateLimitConfig {
+    pub fn new(options: &RateLimitOptions) -> Self {
+        let mut routes: Vec<(String, GlobMatcher, Option<Arc<RateLimitRule>>)> = options
+            .routes
+            .iter()
+            .filter_map(|(pattern, route)| match Glob::new(pattern) {
+                Ok(glob) => {
+                    let rule = match route {
+                        RouteRateLimit::Enabled(false) => None,
+                        RouteRateLimit::Enabled(true) => RateLimitRule::new(pattern, options, options),
+                        RouteRateLimit::Rule(route) => RateLimitRule::new(pattern, route, options),
+                    };
+                    Some((pattern.clone(), glob.compile_matcher(), rule.map(Arc::new)))
+                }
+                Err(e) => {
+                    warn!("⚠️ Invalid rate limit route pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        routes.sort_by_key(|(pattern, _, _)| std::cmp::Reverse(pattern.len()));
+
+        Self {
+            default: RateLimitRule::new("default", options, options).map(Arc::new),
+            routes: routes.into_iter().map(|(_, matcher, rule)| (matcher, rule)).collect(),
+            store: Arc::new(RateLimitStore::new(options.store)),
+        }
+    }
+
+    /// Rule for a request path, if it is limited
+    pub fn rule_for(&self, path: &str) -> Option<&Arc<RateLimitRule>> {
+        self.routes
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(path))
+            .map(|(_, rule)| rule.as_ref())
+            .unwrap_or(self.default.as_ref())
+    }
+}
+
+// Value the request is counted by; falls back to the client IP
+async fn request_key(rule: &RateLimitRule, req: Request) -> (String, Request) {
+    let (kind, value, req) = match &rule.key {
+        RateLimitKey::Header { header } => {
+            let value = req
+                .headers()
+                .get(header)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+            ("header", value, req)
+        }
+        RateLimitKey::Param { param } => {
+            let (mut parts, body) = req.into_parts();
+            let value = RawPathParams::from_request_parts(&mut parts, &())
+                .await
+                .ok()
+                .and_then(|params| {
+                    params
+                        .iter()
+                        .find(|(name, _)| name == param)
+                        .map(|(_, value)| value.to_string())
+                });
+            ("param", value, Request::from_parts(parts, body))
+        }
+        RateLimitKey::Named(_) => ("ip", None, req),
+    };
+
+    let key = match value {
+        Some(value) => format!("{}:{}:{}", rule.id, kind, value),
+        None => {
+            let ip = req.extensions().get::<ClientIp>().map(|client| client.ip.as_str()).unwrap_or("unknown");
+            format!("{}:ip:{}", rule.id, ip)
+        }
+    };
+    (key, req)
+}
+
+// RateLimit-* headers (IETF draft), in whole seconds
+fn apply_headers(headers: &mut HeaderMap, decision: &Decision) {
+    headers.insert("ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert("ratelimit-reset", HeaderValue::from(decision.reset_ms.div_ceil(1000)));
+}
+
+/// Count requests per key and answer 429 once a rule's limit is exhausted
+pub async fn rate_limit_layer(config: Arc<RateLimitConfig>, dev_mode: bool, req: Request, next: Next) -> Response {
+    let Some(rule) = config.rule_for(req.uri().path()).cloned() else {
+        return next.run(req).await;
+    };
+
+    let (key, req) = request_key(&rule, req).await;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let decision = config.store.check(&rule, key.clone(), now_ms).await;
+
+    if !decision.allowed {
+        let path = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|matched| matched.as_str().to_string())
+            .unwrap_or_else(|| req.uri().path().to_string());
+        debug!("🚦 Rate limit exceeded: {} for {}", key, path);
+        record_rate_limited(&path);
+
+        let retry_after = decision.retry_after_ms.div_ceil(1000).max(1);
+        let mut response = crate::html_templates::generate_error_page(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too Many Requests",
+            "Rate limit exceeded. Please try again later.",
+            Some(&format!("Retry after {} seconds", retry_after)),
+            dev_mode,
+        );
+        apply_headers(response.headers_mut(), &decision);
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    let mut response = next.run(req).await;
+    apply_headers(response.headers_mut(), &decision);
+    response
+}
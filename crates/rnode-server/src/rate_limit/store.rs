@@ -0,0 +1,186 @@
+use super::algorithm::{self, BucketState, Decision, WindowState};
+use super::{Algorithm, RateLimitRule};
+use crate::cache::redis_sync::RedisCacheSync;
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Expired entries are swept every this many checks
+const SWEEP_INTERVAL: u64 = 1024;
+
+// Same math as algorithm::token_bucket, run atomically inside Redis.
+// Returns {allowed, tokens * 1000}
+const TOKEN_BUCKET_SCRIPT: &str = r"
+local capacity = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or capacity
+local updated = tonumber(state[2]) or now
+if now > updated then
+  tokens = math.min(capacity, tokens + (now - updated) * capacity / window)
+  updated = now
+end
+local allowed = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(updated))
+redis.call('PEXPIRE', KEYS[1], window)
+return {allowed, math.floor(tokens * 1000)}
+";
+
+// Same math as algorithm::sliding_window, one counter per fixed window.
+// Returns {allowed, previous, current}
+const SLIDING_WINDOW_SCRIPT: &str = r"
+local limit = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local index = math.floor(now / window)
+local current_key = KEYS[1] .. ':' .. index
+local previous = tonumber(redis.call('GET', KEYS[1] .. ':' .. (index - 1))) or 0
+local current = tonumber(redis.call('GET', current_key)) or 0
+local weight = (window - now % window) / window
+local allowed = 0
+if previous * weight + current + 1 <= limit then
+  current = redis.call('INCR', current_key)
+  redis.call('PEXPIRE', current_key, window * 2)
+  allowed = 1
+end
+return {allowed, previous, current}
+";
+
+/// Where limiter state lives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreKind {
+    #[default]
+    Memory,
+    Redis,
+}
+
+#[derive(Debug)]
+enum Entry {
+    Bucket(BucketState),
+    Window(WindowState),
+}
+
+/// Limiter state of this process
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    // Key -> (state, expiry in ms)
+    entries: Mutex<HashMap<String, (Entry, u64)>>,
+    checks: AtomicU64,
+}
+
+impl MemoryStore {
+    pub fn check(&self, rule: &RateLimitRule, key: &str, now_ms: u64) -> Decision {
+        let mut entries = self.entries.lock().unwrap();
+
+        if self.checks.fetch_add(1, Ordering::Relaxed).is_multiple_of(SWEEP_INTERVAL) {
+            entries.retain(|_, (_, expires_ms)| *expires_ms > now_ms);
+        }
+
+        let previous = entries.get(key).map(|(entry, _)| entry);
+        let (entry, decision) = match rule.algorithm {
+            Algorithm::TokenBucket => {
+                let state = match previous {
+                    Some(Entry::Bucket(state)) => Some(*state),
+                    _ => None,
+                };
+                let (state, decision) = algorithm::token_bucket(state, rule.limit, rule.window_ms, now_ms);
+                (Entry::Bucket(state), decision)
+            }
+            Algorithm::SlidingWindow => {
+                let state = match previous {
+                    Some(Entry::Window(state)) => Some(*state),
+                    _ => None,
+                };
+                let (state, decision) = algorithm::sliding_window(state, rule.limit, rule.window_ms, now_ms);
+                (Entry::Window(state), decision)
+            }
+        };
+
+        // Sliding windows still count the previous window's requests
+        entries.insert(key.to_string(), (entry, now_ms + rule.window_ms * 2));
+        decision
+    }
+}
+
+/// Memory or Redis backed limiter state
+#[derive(Debug)]
+pub struct RateLimitStore {
+    kind: StoreKind,
+    memory: MemoryStore,
+    token_bucket: redis::Script,
+    sliding_window: redis::Script,
+    warned: AtomicBool,
+}
+
+impl RateLimitStore {
+    pub fn new(kind: StoreKind) -> Self {
+        Self {
+            kind,
+            memory: MemoryStore::default(),
+            token_bucket: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+            sliding_window: redis::Script::new(SLIDING_WINDOW_SCRIPT),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    /// Count one request against `key`
+    pub async fn check(self: &Arc<Self>, rule: &Arc<RateLimitRule>, key: String, now_ms: u64) -> Decision {
+        if self.kind == StoreKind::Redis {
+            // The cache may be initialized after listen(), so look it up per check
+            match crate::cache::get_cache_manager().and_then(|manager| manager.redis()) {
+                Some(redis) => {
+                    let store = self.clone();
+                    let rule_clone = rule.clone();
+                    let redis_key = key.clone();
+                    let result = tokio::task::spawn_blocking(move || {
+                        store.check_redis(&redis, &rule_clone, &redis_key, now_ms)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result);
+
+                    match result {
+                        Ok(decision) => return decision,
+                        // Keep limiting per process rather than letting everything through
+                        Err(e) => warn!("⚠️ Redis rate limit store failed, using memory: {}", e),
+                    }
+                }
+                None => {
+                    if !self.warned.swap(true, Ordering::Relaxed) {
+                        warn!("⚠️ Rate limit store is 'redis' but no Redis cache is configured, using memory");
+                    }
+                }
+            }
+        }
+
+        self.memory.check(rule, &key, now_ms)
+    }
+
+    fn check_redis(&self, redis: &RedisCacheSync, rule: &RateLimitRule, key: &str, now_ms: u64) -> Result<Decision, String> {
+        let redis_key = format!("ratelimit:{}", key);
+        let args = [rule.limit, rule.window_ms, now_ms];
+
+        match rule.algorithm {
+            Algorithm::TokenBucket => {
+                let (allowed, tokens): (u64, u64) = redis
+                    .eval_script(&self.token_bucket, &redis_key, &args)
+                    .map_err(|e| e.to_string())?;
+                Ok(algorithm::bucket_decision(allowed == 1, tokens as f64 / 1000.0, rule.limit, rule.window_ms))
+            }
+            Algorithm::SlidingWindow => {
+                let (allowed, previous, current): (u64, u64, u64) = redis
+                    .eval_script(&self.sliding_window, &redis_key, &args)
+                    .map_err(|e| e.to_string())?;
+                Ok(algorithm::window_decision(allowed == 1, previous, current, rule.limit, rule.window_ms, now_ms))
+            }
+        }
+    }
+}
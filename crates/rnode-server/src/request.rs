@@ -1,9 +1,16 @@
+use crate::access_log::RequestId;
+use crate::client_ip::{self, ClientIp, TrustedProxies};
+use crate::security_headers::CspNonce;
+use crate::telemetry::TraceContext;
 use log::debug;
 use serde_json::{Map, Value};
 
 /// Общая структура для представления HTTP сообщения (запроса и ответа)
 #[derive(Clone)]
 pub struct HttpMessage {
+    // App instance the request belongs to
+    pub app_id: u32,
+
     // Request fields
     pub method: String,
     pub path: String,
@@ -16,6 +23,13 @@ pub struct HttpMessage {
     pub ip: String,
     pub ips: Vec<String>,
     pub ip_source: String,
+    pub csp_nonce: Option<String>,
+    pub request_id: String,
+    pub traceparent: Option<String>,
+    pub cancel_id: u64, // Key of the request's AbortController in JS
+    pub http_version: String, // "1.1", "2.0"
+    pub protocol: String, // "http" or "https"
+    pub client_cert: Option<Value>, // Verified mTLS client certificate
     pub path_params: Map<String, Value>,
     pub query_params: Map<String, Value>,
     pub custom_params: Map<String, Value>,
@@ -34,9 +48,13 @@ impl HttpMessage {
             .cloned()
             .unwrap_or(Map::new());
 
-        let (ip, ips, ip_source) = Self::extract_ip_from_headers(&headers);
+        let ClientIp { ip, ips, source: ip_source } = Self::ip_from_data(&data, &headers);
 
         Self {
+            app_id: data
+                .get("appId")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as u32,
             method: data
                 .get("method")
                 .and_then(|v| v.as_str())
@@ -68,6 +86,31 @@ impl HttpMessage {
             ip,
             ips,
             ip_source,
+            csp_nonce: data
+                .get("cspNonce")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string()),
+            request_id: data
+                .get("requestId")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            traceparent: data
+                .get("traceparent")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string()),
+            cancel_id: data.get("cancelId").and_then(|v| v.as_u64()).unwrap_or(0),
+            http_version: data
+                .get("httpVersion")
+                .and_then(|v| v.as_str())
+                .unwrap_or("1.1")
+                .to_string(),
+            protocol: data
+                .get("protocol")
+                .and_then(|v| v.as_str())
+                .unwrap_or("http")
+                .to_string(),
+            client_cert: data.get("clientCert").filter(|v| v.is_object()).cloned(),
             path_params: data
                 .get("pathParams")
                 .and_then(|v| v.as_object())
@@ -90,132 +133,24 @@ impl HttpMessage {
         }
     }
 
-    /// Извлекает IP адрес из заголовков
-    fn extract_ip_from_headers(headers: &Map<String, Value>) -> (String, Vec<String>, String) {
-        // Priority order for IP extraction (most trusted first)
-        let ip_headers = [
-            "cf-connecting-ip",    // Cloudflare (most trusted)
-            "x-real-ip",           // Nginx proxy
-            "x-forwarded-for",     // Standard proxy header
-            "x-client-ip",         // Custom proxy header
-            "x-forwarded",         // Alternative proxy header
-            "forwarded-for",       // RFC 7239
-            "forwarded",           // RFC 7239
-            "x-cluster-client-ip", // Load balancer
-            "x-remote-ip",         // Custom remote IP
-            "x-remote-addr",       // Alternative remote addr
-        ];
-
-        let mut all_ips = Vec::new();
-        let mut primary_ip = String::new();
-        let mut ip_source = "default".to_string();
-
-        // Try to extract IP from various headers
-        for header_name in &ip_headers {
-            if let Some(header_value) = headers.get(*header_name) {
-                if let Some(value_str) = header_value.as_str() {
-                    let ips: Vec<&str> = value_str.split(',').collect();
-
-                    // Add all valid IPs to the list
-                    for ip in &ips {
-                        let clean_ip = ip.trim();
-                        if !clean_ip.is_empty()
-                            && Self::is_valid_ip(clean_ip)
-                            && !all_ips.contains(&clean_ip.to_string())
-                        {
-                            all_ips.push(clean_ip.to_string());
-                        }
-                    }
-
-                    // Set primary IP from first valid header found
-                    if primary_ip.is_empty() && !ips.is_empty() {
-                        let first_ip = ips[0].trim();
-                        if Self::is_valid_ip(first_ip) {
-                            primary_ip = first_ip.to_string();
-                            ip_source = header_name.to_string();
-                        }
-                    }
-
-                    // If we found valid IPs, break (don't check lower priority headers)
-                    if !primary_ip.is_empty() {
-                        break;
-                    }
-                }
-            }
+    /// Client address stored from JSON, or resolved from the headers alone
+    fn ip_from_data(data: &Map<String, Value>, headers: &Map<String, Value>) -> ClientIp {
+        if let Some(ip) = data.get("ip").and_then(|v| v.as_str()) {
+            return ClientIp {
+                ip: ip.to_string(),
+                ips: data
+                    .get("ips")
+                    .and_then(|v| v.as_array())
+                    .map(|ips| ips.iter().filter_map(|ip| ip.as_str().map(|ip| ip.to_string())).collect())
+                    .unwrap_or_else(|| vec![ip.to_string()]),
+                source: data
+                    .get("ipSource")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("default")
+                    .to_string(),
+            };
         }
-
-        // If no IP found in headers, use default localhost
-        if primary_ip.is_empty() {
-            primary_ip = "127.0.0.1".to_string();
-            if all_ips.is_empty() {
-                all_ips.push("127.0.0.1".to_string());
-            }
-        }
-
-        (primary_ip, all_ips, ip_source)
-    }
-
-    /// Проверяет валидность IP адреса
-    fn is_valid_ip(ip: &str) -> bool {
-        // Basic IP validation (IPv4 and IPv6)
-        if ip.is_empty() {
-            return false;
-        }
-
-        // Check for private/local IPs that might be invalid
-        let invalid_prefixes = [
-            "0.",
-            "127.",
-            "10.",
-            "172.16.",
-            "172.17.",
-            "172.18.",
-            "172.19.",
-            "172.20.",
-            "172.21.",
-            "172.22.",
-            "172.23.",
-            "172.24.",
-            "172.25.",
-            "172.26.",
-            "172.27.",
-            "172.28.",
-            "172.29.",
-            "172.30.",
-            "172.31.",
-            "192.168.",
-            "169.254.",
-            "224.",
-            "240.",
-            "255.255.255.255",
-        ];
-
-        for prefix in &invalid_prefixes {
-            if ip.starts_with(prefix) && ip != "127.0.0.1" {
-                return false;
-            }
-        }
-
-        // Basic format check (simplified)
-        let parts: Vec<&str> = ip.split('.').collect();
-        if parts.len() == 4 {
-            for part in parts {
-                if let Ok(_num) = part.parse::<u8>() {
-                    // u8 is already 0-255, so no need to check > 255
-                    // Just verify it's a valid number
-                } else {
-                    return false;
-                }
-            }
-            return true;
-        }
-
-        // IPv6 basic check (simplified)
-        if ip.contains(':') {
-            return ip.len() <= 39 && ip.chars().all(|c| c.is_ascii_hexdigit() || c == ':');
-        }
-
-        false
+        client_ip::resolve(None, |name| headers.get(name).and_then(|v| v.as_str()), &TrustedProxies::default())
     }
 
     /// Получает значение заголовка по имени (без учета регистра)
@@ -338,6 +273,11 @@ impl HttpMessage {
     pub fn to_json_map(&self) -> Map<String, Value> {
         let mut map = Map::new();
 
+        map.insert(
+            "appId".to_string(),
+            Value::Number(serde_json::Number::from(self.app_id)),
+        );
+
         // Request fields
         map.insert("method".to_string(), Value::String(self.method.clone()));
         map.insert("path".to_string(), Value::String(self.path.clone()));
@@ -367,6 +307,22 @@ impl HttpMessage {
             "ipSource".to_string(),
             Value::String(self.ip_source.clone()),
         );
+        map.insert(
+            "requestId".to_string(),
+            Value::String(self.request_id.clone()),
+        );
+        if let Some(traceparent) = &self.traceparent {
+            map.insert("traceparent".to_string(), Value::String(traceparent.clone()));
+        }
+        map.insert("cancelId".to_string(), Value::from(self.cancel_id));
+        map.insert("httpVersion".to_string(), Value::String(self.http_version.clone()));
+        map.insert("protocol".to_string(), Value::String(self.protocol.clone()));
+        if let Some(client_cert) = &self.client_cert {
+            map.insert("clientCert".to_string(), client_cert.clone());
+        }
+        if let Some(nonce) = &self.csp_nonce {
+            map.insert("cspNonce".to_string(), Value::String(nonce.clone()));
+        }
         map.insert(
             "pathParams".to_string(),
             Value::Object(self.path_params.clone()),
@@ -435,8 +391,32 @@ impl HttpMessage {
             }
         }
 
-        // Extract IP from headers
-        let (ip, ips, ip_source) = Self::extract_ip_from_headers(&headers_map);
+        // Client address resolved by the client IP layer
+        let ClientIp { ip, ips, source: ip_source } = match req.extensions().get::<ClientIp>() {
+            Some(client) => client.clone(),
+            None => client_ip::resolve(None, |name| req.headers().get(name).and_then(|v| v.to_str().ok()), &TrustedProxies::default()),
+        };
+
+        // Nonce generated by the security headers layer
+        let csp_nonce = req.extensions().get::<CspNonce>().map(|nonce| nonce.0.clone());
+
+        // Correlation ID assigned by the access log layer
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone())
+            .unwrap_or_default();
+
+        // Negotiated protocol; TLS listeners mark their requests
+        let http_version = crate::http2::version_name(req.version()).to_string();
+        let protocol = if req.extensions().get::<crate::tls::TlsConnection>().is_some() { "https" } else { "http" };
+        let client_cert = req
+            .extensions()
+            .get::<std::sync::Arc<crate::client_cert::ClientCertificate>>()
+            .and_then(|certificate| serde_json::to_value(certificate.as_ref()).ok());
+
+        // Server span started by the request layer
+        let traceparent = req.extensions().get::<TraceContext>().map(|context| context.traceparent());
 
         // Extract status from headers (if available)
         let mut status = 200; // Default 200 OK
@@ -466,6 +446,7 @@ impl HttpMessage {
         }
 
         let http_message = Self {
+            app_id: 0, // Set by the app's request layer
             method,
             path: path.clone(),
             registered_path: path, // Будет обновлено позже
@@ -477,6 +458,13 @@ impl HttpMessage {
             ip,
             ips,
             ip_source,
+            csp_nonce,
+            request_id,
+            traceparent,
+            cancel_id: crate::handlers::cancellation::next_cancel_id(),
+            http_version,
+            protocol: protocol.to_string(),
+            client_cert,
             path_params: Map::new(), // Будет заполнено позже
             query_params,
             custom_params: Map::new(),
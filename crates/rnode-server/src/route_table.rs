@@ -0,0 +1,539 @@
+use crate::access_log::{AccessLogConfig, access_log_layer};
+use crate::app::App;
+use crate::client_ip::{TrustedProxies, client_ip_layer};
+use crate::compression::dynamic::{CompressionConfig, compression_layer};
+use crate::cors::{CorsConfig, cors_layer};
+use crate::file_operations::handlers::{download_handler_impl, upload_handler_impl};
+use crate::handlers::dynamic_handler;
+use crate::handlers::response_hooks::{ResponseHooks, response_hooks_layer};
+use crate::metrics::{http::track_metrics, render_metrics};
+use crate::rate_limit::{RateLimitConfig, rate_limit_layer};
+use crate::security_headers::{SecurityHeadersConfig, security_headers_layer};
+use crate::server::{BodyLimits, request_response_layer};
+use crate::sse;
+use crate::static_files::fallback::handle_static_fallback;
+use crate::types::{RouteInfo, RouteOptions};
+use crate::websocket;
+use axum::{
+    Router,
+    routing::{any, delete, get, options, patch, post, put, trace},
+};
+use log::{debug, info};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+// Room for boundaries and part headers on top of an upload's file limits
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
+/// Settings the route table is built with
+#[derive(Debug, Clone)]
+pub struct RouterSettings {
+    pub timeout: u64,
+    pub dev_mode: bool,
+    pub metrics_enabled: bool,
+    pub max_body_size: usize,
+    pub method_not_allowed_as_404: bool,
+    pub cors: Option<Arc<CorsConfig>>,
+    pub rate_limit: Option<Arc<RateLimitConfig>>,
+    pub trusted_proxies: Arc<TrustedProxies>,
+    pub security_headers: Option<Arc<SecurityHeadersConfig>>,
+    pub compression: Option<Arc<CompressionConfig>>,
+    pub access_log: Arc<AccessLogConfig>,
+    pub response_hooks: Arc<ResponseHooks>,
+}
+
+/// Router currently serving requests together with its settings
+pub struct RouteTable {
+    router: Router,
+    settings: RouterSettings,
+}
+
+/// Build the app's route table and make it active
+pub fn install(app: &Arc<App>, settings: RouterSettings) {
+    let router = build_router(app, &settings);
+    app.route_table
+        .store(Some(Arc::new(RouteTable { router, settings })));
+}
+
+/// Drop the app's active route table (server stopped)
+pub fn clear(app: &App) {
+    app.route_table.store(None);
+}
+
+/// Rebuild the app's active route table from its registries, if its server is running.
+///
+/// Called after every route registration or removal; requests already in
+/// flight keep using the router they started with. Registrations are checked
+/// with `check_route` first, so building cannot hit axum's conflict panics.
+pub fn rebuild(app: &Arc<App>) {
+    let Some(current) = app.route_table.load_full() else {
+        return;
+    };
+    let settings = current.settings.clone();
+    let router = build_router(app, &settings);
+    app.route_table
+        .store(Some(Arc::new(RouteTable { router, settings })));
+    info!("🔄 Route table rebuilt for app {}", app.id);
+}
+
+/// Registry a route comes from; an entry replaces one with the same source, path and method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteSource {
+    Handler,
+    WebSocket,
+    Sse,
+    Download,
+    Upload,
+    Metrics,
+}
+
+/// One path and method the route table registers; `method` None answers any method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteEntry {
+    pub source: RouteSource,
+    pub path: String,
+    pub method: Option<String>,
+}
+
+impl RouteEntry {
+    pub fn new(source: RouteSource, path: &str, method: Option<&str>) -> Self {
+        Self { source, path: path.to_string(), method: method.map(|method| method.to_string()) }
+    }
+}
+
+// Every entry build_router would register for the app's registries
+fn route_entries(app: &App, metrics_enabled: bool) -> Vec<RouteEntry> {
+    let mut entries: Vec<RouteEntry> = app
+        .routes
+        .read()
+        .unwrap()
+        .values()
+        .map(|route| {
+            let method = (route.method != "ANY").then_some(route.method.as_str());
+            RouteEntry::new(RouteSource::Handler, &route.path, method)
+        })
+        .collect();
+    entries.extend(app.websocket_routes.blocking_read().keys().map(|path| RouteEntry::new(RouteSource::WebSocket, path, None)));
+    entries.extend(app.sse_routes.read().unwrap().keys().map(|path| RouteEntry::new(RouteSource::Sse, path, Some("GET"))));
+    entries.extend(app.download_routes.read().unwrap().keys().map(|path| RouteEntry::new(RouteSource::Download, path, Some("GET"))));
+    entries.extend(app.upload_routes.read().unwrap().keys().map(|path| RouteEntry::new(RouteSource::Upload, path, Some("POST"))));
+    if metrics_enabled {
+        entries.extend(["/metrics", "/health", "/info"].map(|path| RouteEntry::new(RouteSource::Metrics, path, Some("GET"))));
+    }
+    entries
+}
+
+// First registration axum would refuse (by panicking) when building a router from `entries`
+fn find_conflict(entries: &[RouteEntry]) -> Option<String> {
+    let mut paths = matchit::Router::new();
+    let mut methods: std::collections::HashMap<&str, Vec<Option<&str>>> = std::collections::HashMap::new();
+
+    for entry in entries {
+        let path = entry.path.as_str();
+        if !path.starts_with('/') {
+            return Some(format!("Route path '{}' must start with '/'", path));
+        }
+        if path.split('/').any(|segment| segment.starts_with(':') || segment.starts_with('*')) {
+            return Some(format!("Route path '{}' must use {{param}} and {{*rest}} captures", path));
+        }
+
+        let method = entry.method.as_deref();
+        let seen = methods.entry(path).or_default();
+        if seen.contains(&method) {
+            return Some(format!("{} {} is already registered", method.unwrap_or("ANY"), path));
+        }
+        if seen.is_empty()
+            && let Err(e) = paths.insert(path, ())
+        {
+            return Some(format!("Route {} conflicts with another route: {}", path, e));
+        }
+        seen.push(method);
+    }
+    None
+}
+
+/// Check the app's registries before install(); the built-in metrics routes join them only then
+pub fn check_routes(app: &App, metrics_enabled: bool) -> Result<(), String> {
+    find_conflict(&route_entries(app, metrics_enabled)).map_or(Ok(()), Err)
+}
+
+/// Check that `entry` can be added to the app's routes; errors describe the conflict
+pub fn check_route(app: &App, entry: RouteEntry) -> Result<(), String> {
+    let metrics_enabled = app.route_table.load().as_ref().is_some_and(|table| table.settings.metrics_enabled);
+    let mut entries = route_entries(app, metrics_enabled);
+    entries.retain(|existing| existing != &entry);
+    entries.push(entry);
+    find_conflict(&entries).map_or(Ok(()), Err)
+}
+
+/// Router that dispatches every request to the app's currently active route table
+pub fn hot_router(app: Arc<App>) -> Router {
+    Router::new().fallback_service(tower::service_fn(
+        move |req: axum::extract::Request| {
+            let router = app
+                .route_table
+                .load()
+                .as_ref()
+                .map(|table| table.router.clone());
+
+            async move {
+                match router {
+                    Some(router) => router.oneshot(req).await,
+                    None => Ok::<_, Infallible>(crate::html_templates::generate_error_page(
+                        http::StatusCode::SERVICE_UNAVAILABLE,
+                        "Service Unavailable",
+                        "The server is not accepting requests.",
+                        None,
+                        false,
+                    )),
+                }
+            }
+        },
+    ))
+}
+
+// Build a router from the app's route, WebSocket, SSE, download and upload registries
+fn build_router(instance: &Arc<App>, settings: &RouterSettings) -> Router {
+    let timeout = settings.timeout;
+    let dev_mode = settings.dev_mode;
+
+    // Create base router
+    let mut app = Router::new();
+
+    // Add dynamic routes
+    let routes_map = instance.routes.read().unwrap();
+    // Create clones for use in closures
+    let routes_vec: Vec<RouteInfo> = routes_map.values().cloned().collect();
+
+    debug!(
+        "Routes found: {:?}",
+        routes_vec
+            .iter()
+            .map(|route| (&route.method, &route.path, route.handler_id))
+            .collect::<Vec<_>>()
+    );
+    for route in routes_vec {
+        let path = route.path.clone();
+        let method = route.method.clone();
+        let instance_clone = instance.clone();
+        let handler_fn = move |req: axum::extract::Request| {
+                let instance = instance_clone.clone();
+                let route = route.clone();
+                let timeout_clone = timeout;
+                let dev_mode_clone = dev_mode;
+                async move {
+                    // Get actual path from request
+                    let actual_path = req.uri().path().to_string();
+                    dynamic_handler(instance, req, actual_path, route, timeout_clone, dev_mode_clone).await
+                }
+            };
+
+        match method.as_str() {
+            "GET" => app = app.route(&path, get(handler_fn)),
+            "POST" => app = app.route(&path, post(handler_fn)),
+            "PUT" => app = app.route(&path, put(handler_fn)),
+            "DELETE" => app = app.route(&path, delete(handler_fn)),
+            "PATCH" => app = app.route(&path, patch(handler_fn)),
+            "OPTIONS" => app = app.route(&path, options(handler_fn)),
+            "TRACE" => app = app.route(&path, trace(handler_fn)),
+            "ANY" => app = app.route(&path, any(handler_fn)),
+            _ => {}
+        }
+    }
+
+    debug!("Registered dynamic routes:");
+    for route_info in routes_map.values() {
+        debug!("  {} {}", route_info.method, route_info.path);
+    }
+
+    // Release lock before starting server
+    drop(routes_map);
+
+    // Add WebSocket routes
+    let websocket_routes_map = instance.websocket_routes.blocking_read();
+    let websocket_routes_vec: Vec<(String, websocket::WebSocketHandler)> = websocket_routes_map.iter()
+        .map(|(path, handler)| (path.clone(), handler.clone()))
+        .collect();
+
+    for (path, _handler) in websocket_routes_vec {
+        let path_clone = path.clone();
+
+        // Generate unique handler ID for WebSocket
+        let handler_id = format!("ws_{}_{}", path.replace('/', "_"), std::process::id());
+
+        let instance_clone = instance.clone();
+        let websocket_handler = move |req: axum::extract::Request| {
+            let instance = instance_clone.clone();
+            let path = path_clone.clone();
+            let handler_id = handler_id.clone();
+            async move {
+                // Use the upgrade handler for WebSocket connections
+                websocket::upgrade::websocket_upgrade_handler(instance, req, path, handler_id).await
+            }
+        };
+
+        app = app.route(&path, any(websocket_handler));
+        debug!("🔌 WebSocket route registered: {}", path);
+    }
+
+    // Release WebSocket routes lock
+    drop(websocket_routes_map);
+
+    // Add Server-Sent Events routes
+    let sse_routes_vec: Vec<sse::SseRoute> = instance.sse_routes.read().unwrap().values().cloned().collect();
+
+    for route in sse_routes_vec {
+        let path = route.path.clone();
+        let instance_clone = instance.clone();
+        let sse_handler = move |req: axum::extract::Request| {
+            let instance = instance_clone.clone();
+            let route = route.clone();
+            async move {
+                sse::handler::sse_handler(instance, req, route, timeout, dev_mode).await
+            }
+        };
+
+        app = app.route(&path, get(sse_handler));
+        debug!("📡 SSE route registered: {}", path);
+    }
+
+    // Add dynamic routes for file downloads
+    let download_routes_map = instance.download_routes.read().unwrap();
+
+    for (route_path, config) in download_routes_map.iter() {
+        let config_clone = config.clone();
+        let route_path_clone = route_path.clone();
+
+        let download_handler = move |req: axum::extract::Request| {
+            let config = config_clone.clone();
+            let route_path = route_path_clone.clone();
+            async move {
+                download_handler_impl(req, config, route_path).await
+            }
+        };
+
+        // Register route (support wildcard for subfolders)
+        let actual_route = route_path.clone();
+        app = app.route(&actual_route, get(download_handler));
+        info!("📥 Download route registered: {} -> {}", route_path, actual_route);
+    }
+
+    // Release lock
+    drop(download_routes_map);
+
+    // Add routes for file uploads
+    let upload_routes_map = instance.upload_routes.read().unwrap();
+
+    for (route_path, config) in upload_routes_map.iter() {
+        let config_clone = config.clone();
+        let route_path_clone = route_path.clone();
+
+        let upload_handler = move |req: axum::extract::Request| {
+            let config = config_clone.clone();
+            let route_path = route_path_clone.clone();
+            async move {
+                upload_handler_impl(req, config, route_path, dev_mode).await
+            }
+        };
+
+        // Register route
+        app = app.route(route_path, post(upload_handler));
+        info!("📤 Upload route registered: {}", route_path);
+    }
+
+    // Release lock
+    drop(upload_routes_map);
+
+    // 405 with Allow header, automatic OPTIONS, or the legacy 404 for unsupported methods
+    let legacy_404 = settings.method_not_allowed_as_404;
+    app = app.layer(axum::middleware::from_fn(move |req: axum::extract::Request, next: axum::middleware::Next| {
+        method_not_allowed_layer(legacy_404, dev_mode, req, next)
+    }));
+
+     // Добавляем layer'ы для Request/Response и middleware
+    // Слой 1: Формирование Request и Response объектов
+    let app_id = instance.id;
+    let body_limits = Arc::new(BodyLimits {
+        max_body_size: settings.max_body_size,
+        routes: instance
+            .routes
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, route)| (key.clone(), route.options.clone()))
+            .chain(instance.upload_routes.read().unwrap().iter().filter_map(|(path, config)| {
+                // Uploads may exceed the app's limit by what their own file limits allow
+                let files = config.max_file_size? as usize * config.max_files.unwrap_or(1).max(1) as usize;
+                let limit = settings.max_body_size.max(files + MULTIPART_OVERHEAD);
+                Some((format!("POST:{}", path), RouteOptions { max_body_size: Some(limit), ..Default::default() }))
+            }))
+            .collect(),
+    });
+    app = app.layer(axum::middleware::from_fn(move |req, next| {
+        request_response_layer(app_id, body_limits.clone(), req, next)
+    }));
+
+    // Add fallback route for static files
+    let timeout_clone = timeout;
+    let fallback_instance = instance.clone();
+    let max_body_size = settings.max_body_size;
+    let mut app = app.fallback(move |req: http::Request<axum::body::Body>| {
+        let instance = fallback_instance.clone();
+        async move { handle_static_fallback(instance, req, timeout_clone, max_body_size).await }
+    });
+
+
+
+    if settings.metrics_enabled {
+        // Add middleware to track HTTP metrics
+        app = app.route_layer(axum::middleware::from_fn(track_metrics));
+
+        // Add /metrics endpoint
+        app = app.route("/metrics", get(|| async {
+            render_metrics()
+        }));
+
+        // Add /health endpoint
+        app = app.route("/health", get(|| async {
+            "OK"
+        }));
+
+        // Add /info endpoint with process information
+        app = app.route("/info", get(|| async {
+            "Use /metrics endpoint for detailed system information"
+        }));
+        debug!("📊 Metrics routes added: /metrics, /health, /info");
+    }
+
+    // Rejected requests never reach body parsing or JS
+    if let Some(rate_limit) = settings.rate_limit.clone() {
+        app = app.layer(axum::middleware::from_fn(move |req, next| {
+            rate_limit_layer(rate_limit.clone(), dev_mode, req, next)
+        }));
+    }
+
+    // Security headers go on dynamic, static and download responses and on 429s
+    if let Some(security_headers) = settings.security_headers.clone() {
+        app = app.layer(axum::middleware::from_fn(move |req, next| {
+            security_headers_layer(security_headers.clone(), req, next)
+        }));
+    }
+
+    // Response hooks see every response, including 429s, but not CORS preflights
+    let hooks_instance = instance.clone();
+    let response_hooks = settings.response_hooks.clone();
+    app = app.layer(axum::middleware::from_fn(move |req, next| {
+        response_hooks_layer(hooks_instance.clone(), response_hooks.clone(), timeout, req, next)
+    }));
+
+    // Compress what the hooks produced; precompressed static files pass through
+    if let Some(compression) = settings.compression.clone() {
+        app = app.layer(axum::middleware::from_fn(move |req, next| compression_layer(compression.clone(), req, next)));
+    }
+
+    // CORS wraps everything so preflights never reach JS middleware
+    if let Some(cors) = settings.cors.clone() {
+        app = app.layer(axum::middleware::from_fn(move |req, next| cors_layer(cors.clone(), req, next)));
+    }
+
+    // Every request gets an ID and an access log line, CORS preflights included
+    let access_log = settings.access_log.clone();
+    app = app.layer(axum::middleware::from_fn(move |req, next| access_log_layer(access_log.clone(), req, next)));
+
+    // Client address is resolved first, from the socket peer and trusted proxy headers
+    let trusted_proxies = settings.trusted_proxies.clone();
+    app = app.layer(axum::middleware::from_fn(move |req, next| {
+        client_ip_layer(trusted_proxies.clone(), req, next)
+    }));
+
+    app
+}
+
+// Rewrite the router's bare 405 responses.
+//
+// Axum already serves HEAD from GET routes and puts the allowed methods in
+// `Allow`; OPTIONS without a registered handler is answered with that list,
+// so both the 204 and the 405 list OPTIONS too.
+async fn method_not_allowed_layer(
+    legacy_404: bool,
+    dev_mode: bool,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let path = req.uri().path().to_string();
+    let is_options = req.method() == http::Method::OPTIONS;
+    let resp = next.run(req).await;
+
+    if resp.status() != http::StatusCode::METHOD_NOT_ALLOWED {
+        return resp;
+    }
+
+    let allow = resp
+        .headers()
+        .get(http::header::ALLOW)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    let allow = match allow.as_str() {
+        "" => "OPTIONS".to_string(),
+        methods if methods.split(',').any(|method| method.trim() == "OPTIONS") => allow,
+        methods => format!("{},OPTIONS", methods),
+    };
+
+    if is_options {
+        debug!("🔧 Automatic OPTIONS for path: {} (Allow: {})", path, allow);
+        return http::Response::builder()
+            .status(http::StatusCode::NO_CONTENT)
+            .header(http::header::ALLOW, allow)
+            .body(axum::body::Body::empty())
+            .unwrap();
+    }
+
+    if legacy_404 {
+        debug!("🔧 Converting 405 to 404 for path: {}", path);
+        return crate::html_templates::generate_error_page(
+            http::StatusCode::NOT_FOUND,
+            "Not Found",
+            "The requested resource was not found.",
+            Some(&format!("Path: {}", path)),
+            dev_mode,
+        );
+    }
+
+    let mut page = crate::html_templates::generate_error_page(
+        http::StatusCode::METHOD_NOT_ALLOWED,
+        "Method Not Allowed",
+        "The HTTP method used is not allowed for this resource.",
+        Some(&format!("Path: {}", path)),
+        dev_mode,
+    );
+    if let Ok(value) = http::HeaderValue::from_str(&allow) {
+        page.headers_mut().insert(http::header::ALLOW, value);
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_conflict() {
+        let handler = |path: &str, method: Option<&str>| RouteEntry::new(RouteSource::Handler, path, method);
+
+        // Different methods and a catch-all merge on one path; sibling captures are fine
+        let entries = [handler("/chat", Some("GET")), handler("/chat", None), handler("/users/{id}", Some("GET")), handler("/users/me", Some("GET"))];
+        assert_eq!(find_conflict(&entries), None);
+
+        let websocket = RouteEntry::new(RouteSource::WebSocket, "/chat", None);
+        assert_eq!(find_conflict(&[websocket, handler("/chat", None)]).as_deref(), Some("ANY /chat is already registered"));
+
+        let sse = RouteEntry::new(RouteSource::Sse, "/events", Some("GET"));
+        assert_eq!(find_conflict(&[sse, handler("/events", Some("GET"))]).as_deref(), Some("GET /events is already registered"));
+
+        assert!(find_conflict(&[handler("/users/{id}", Some("GET")), handler("/users/{name}", Some("POST"))]).is_some());
+        assert!(find_conflict(&[handler("/files/:name", Some("GET"))]).is_some());
+        assert!(find_conflict(&[handler("files", Some("GET"))]).is_some());
+    }
+}
@@ -1,36 +1,40 @@
-use crate::types::{RouteInfo, get_routes};
+use crate::app::app_from_arg;
+use crate::route_table::{RouteEntry, RouteSource};
+use crate::types::{RouteInfo, RouteOptions, next_handler_id};
 use log::info;
 use neon::prelude::*;
+use std::sync::Arc;
 
 // Universal function for route registration
 fn register_route(method: &str) -> impl Fn(FunctionContext) -> JsResult<JsUndefined> + '_ {
     move |mut cx: FunctionContext| {
         let path = cx.argument::<JsString>(0)?.value(&mut cx);
-        let _handler = cx.argument::<JsFunction>(1)?; // JS handler function
-
-        info!("Registering {} route: {}", method, path);
-
-        // Just store route information without executing the handler
-        let _response_content = format!("Route registered: {} {}", method, path);
-
-        // Generate unique handler ID
-        let handler_id = format!(
-            "{}_{}_{}",
-            method,
-            path.replace('/', "_"),
-            std::process::id()
-        );
+        let handler = cx.argument::<JsFunction>(1)?.root(&mut cx); // JS handler function
+        let app = app_from_arg(&mut cx, 2)?;
+        let options: RouteOptions = cx
+            .argument_opt(3)
+            .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+            .and_then(|json| serde_json::from_str(&json.value(&mut cx)).ok())
+            .unwrap_or_default();
+
+        let entry = RouteEntry::new(RouteSource::Handler, &path, (method != "ANY").then_some(method));
+        if let Err(conflict) = crate::route_table::check_route(&app, entry) {
+            return cx.throw_error(conflict);
+        }
 
-        // Add route to global storage (synchronously)
-        let routes = get_routes();
+        let handler_id = next_handler_id();
+        info!("Registering {} route: {} (handler {})", method, path, handler_id);
 
+        // Add route to the app's storage (synchronously), replacing an earlier handler
         {
-            let mut routes_map = routes.write().unwrap();
+            let mut routes_map = app.routes.write().unwrap();
 
             let route_info = RouteInfo {
                 path: path.clone(),
                 method: method.to_string(),
-                handler_id: handler_id.clone(),
+                handler_id,
+                handler: Arc::new(handler),
+                options,
             };
 
             routes_map.insert(format!("{}:{}", method, path), route_info);
@@ -38,6 +42,9 @@ fn register_route(method: &str) -> impl Fn(FunctionContext) -> JsResult<JsUndefi
 
         info!("Stored route info for {} {}", method, path);
 
+        // Apply to the running server
+        crate::route_table::rebuild(&app);
+
         Ok(cx.undefined())
     }
 }
@@ -74,3 +81,26 @@ pub fn register_any(cx: FunctionContext) -> JsResult<JsUndefined> {
 pub fn register_trace(cx: FunctionContext) -> JsResult<JsUndefined> {
     register_route("TRACE")(cx)
 }
+
+// Remove a route registered for method and path, returns true if it existed
+pub fn remove_route(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let method = cx.argument::<JsString>(0)?.value(&mut cx).to_uppercase();
+    let path = cx.argument::<JsString>(1)?.value(&mut cx);
+    let app = app_from_arg(&mut cx, 2)?;
+
+    let removed = app
+        .routes
+        .write()
+        .unwrap()
+        .remove(&format!("{}:{}", method, path))
+        .is_some();
+
+    if removed {
+        info!("🗑️ Removed {} route: {}", method, path);
+        crate::route_table::rebuild(&app);
+    } else {
+        info!("Route {} {} is not registered, nothing to remove", method, path);
+    }
+
+    Ok(cx.boolean(removed))
+}
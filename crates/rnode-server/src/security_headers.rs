@@ -0,0 +1,390 @@
+use crate::types::Setting;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use base64::Engine;
+use globset::{Glob, GlobMatcher};
+use http::header::{self, HeaderName, HeaderValue};
+use log::{debug, warn};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+// Default Content-Security-Policy, the same as Helmet's
+const DEFAULT_CSP: &[(&str, &str)] = &[
+    ("default-src", "'self'"),
+    ("base-uri", "'self'"),
+    ("font-src", "'self' https: data:"),
+    ("form-action", "'self'"),
+    ("frame-ancestors", "'self'"),
+    ("img-src", "'self' data:"),
+    ("object-src", "'none'"),
+    ("script-src", "'self'"),
+    ("script-src-attr", "'none'"),
+    ("style-src", "'self' https: 'unsafe-inline'"),
+    ("upgrade-insecure-requests", ""),
+];
+
+// Directives the request's nonce is added to
+const NONCE_DIRECTIVES: &[&str] = &["script-src", "style-src"];
+
+const DEFAULT_HSTS_MAX_AGE: u64 = 31536000; // 365 days
+
+/// Nonce of the request's Content-Security-Policy, passed to JS as `req.cspNonce`
+#[derive(Debug, Clone)]
+pub struct CspNonce(pub String);
+
+/// CSP directive value: sources, or `true`/`false` for a valueless directive or none at all
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Directive {
+    Enabled(bool),
+    Source(String),
+    Sources(Vec<String>),
+}
+
+/// `contentSecurityPolicy` option
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CspOptions {
+    pub directives: BTreeMap<String, Directive>, // Replace or extend the defaults
+    pub use_defaults: Option<bool>, // Start from the default directives (default true)
+    pub report_only: bool, // Content-Security-Policy-Report-Only
+    pub nonce: bool, // Per-request nonce in script-src and style-src
+}
+
+/// `strictTransportSecurity` option
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HstsOptions {
+    pub max_age: Option<u64>, // Seconds
+    pub include_sub_domains: Option<bool>,
+    pub preload: bool,
+}
+
+/// Route entry: options, or `false` to send no security headers
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RouteSecurityHeaders {
+    Enabled(bool),
+    Options(Box<SecurityHeadersOptions>),
+}
+
+/// `securityHeaders` option of listen(); route entries override the top-level fields
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SecurityHeadersOptions {
+    pub content_security_policy: Option<Setting<CspOptions>>,
+    pub strict_transport_security: Option<Setting<HstsOptions>>,
+    pub frame_options: Option<Setting<String>>, // X-Frame-Options, SAMEORIGIN
+    pub referrer_policy: Option<Setting<String>>, // no-referrer
+    pub no_sniff: Option<bool>, // X-Content-Type-Options: nosniff
+    pub cross_origin_opener_policy: Option<Setting<String>>, // same-origin
+    pub cross_origin_resource_policy: Option<Setting<String>>, // same-origin
+    pub cross_origin_embedder_policy: Option<Setting<String>>, // Off unless set, `true` is require-corp
+    pub permissions_policy: Option<Setting<String>>, // Off unless set
+    pub origin_agent_cluster: Option<bool>, // Origin-Agent-Cluster: ?1
+    pub legacy_headers: Option<bool>, // X-DNS-Prefetch-Control, X-Download-Options, X-Permitted-Cross-Domain-Policies, X-XSS-Protection
+    pub routes: HashMap<String, RouteSecurityHeaders>, // Overrides by path glob
+}
+
+// Route setting if set, otherwise the base one; `true` means the default value
+fn resolve<T: Clone>(route: &Option<Setting<T>>, base: &Option<Setting<T>>, default: T, on_by_default: bool) -> Option<T> {
+    match route.as_ref().or(base.as_ref()) {
+        Some(Setting::Enabled(false)) => None,
+        Some(Setting::Value(value)) => Some(value.clone()),
+        Some(Setting::Enabled(true)) => Some(default),
+        None => on_by_default.then_some(default),
+    }
+}
+
+// Directive names may be given as in Helmet: defaultSrc or default-src
+fn directive_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            result.push('-');
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Content-Security-Policy of a policy, rendered per request when it uses a nonce
+#[derive(Debug)]
+struct ContentSecurityPolicy {
+    header: HeaderName,
+    directives: Vec<(String, String)>,
+    nonce: bool,
+}
+
+impl ContentSecurityPolicy {
+    fn new(options: &CspOptions) -> Self {
+        let mut directives: Vec<(String, String)> = if options.use_defaults.unwrap_or(true) {
+            DEFAULT_CSP
+                .iter()
+                .map(|(name, sources)| (name.to_string(), sources.to_string()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        for (name, value) in &options.directives {
+            let name = directive_name(name);
+            let sources = match value {
+                Directive::Enabled(false) => {
+                    directives.retain(|(existing, _)| *existing != name);
+                    continue;
+                }
+                Directive::Enabled(true) => String::new(),
+                Directive::Source(source) => source.clone(),
+                Directive::Sources(sources) => sources.join(" "),
+            };
+            match directives.iter_mut().find(|(existing, _)| *existing == name) {
+                Some(directive) => directive.1 = sources,
+                None => directives.push((name, sources)),
+            }
+        }
+
+        Self {
+            header: if options.report_only {
+                header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+            } else {
+                header::CONTENT_SECURITY_POLICY
+            },
+            directives,
+            nonce: options.nonce,
+        }
+    }
+
+    fn value(&self, nonce: Option<&str>) -> String {
+        self.directives
+            .iter()
+            .map(|(name, sources)| {
+                let mut directive = name.clone();
+                if !sources.is_empty() {
+                    directive.push(' ');
+                    directive.push_str(sources);
+                }
+                if let Some(nonce) = nonce
+                    && NONCE_DIRECTIVES.contains(&name.as_str())
+                {
+                    directive.push_str(&format!(" 'nonce-{}'", nonce));
+                }
+                directive
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Security headers sent for a set of paths
+#[derive(Debug)]
+pub struct SecurityPolicy {
+    headers: Vec<(HeaderName, HeaderValue)>,
+    csp: Option<ContentSecurityPolicy>,
+}
+
+impl SecurityPolicy {
+    // Build a policy from route options falling back to the top-level ones
+    fn new(options: &SecurityHeadersOptions, base: &SecurityHeadersOptions) -> Self {
+        let mut headers: Vec<(HeaderName, String)> = Vec::new();
+
+        if let Some(hsts) = resolve(&options.strict_transport_security, &base.strict_transport_security, HstsOptions::default(), true) {
+            let mut value = format!("max-age={}", hsts.max_age.unwrap_or(DEFAULT_HSTS_MAX_AGE));
+            if hsts.include_sub_domains.unwrap_or(true) {
+                value.push_str("; includeSubDomains");
+            }
+            if hsts.preload {
+                value.push_str("; preload");
+            }
+            headers.push((header::STRICT_TRANSPORT_SECURITY, value));
+        }
+
+        let values = [
+            (header::X_FRAME_OPTIONS, resolve(&options.frame_options, &base.frame_options, "SAMEORIGIN".to_string(), true)),
+            (header::REFERRER_POLICY, resolve(&options.referrer_policy, &base.referrer_policy, "no-referrer".to_string(), true)),
+            (
+                HeaderName::from_static("cross-origin-opener-policy"),
+                resolve(&options.cross_origin_opener_policy, &base.cross_origin_opener_policy, "same-origin".to_string(), true),
+            ),
+            (
+                HeaderName::from_static("cross-origin-resource-policy"),
+                resolve(&options.cross_origin_resource_policy, &base.cross_origin_resource_policy, "same-origin".to_string(), true),
+            ),
+            (
+                HeaderName::from_static("cross-origin-embedder-policy"),
+                resolve(&options.cross_origin_embedder_policy, &base.cross_origin_embedder_policy, "require-corp".to_string(), false),
+            ),
+            (
+                HeaderName::from_static("permissions-policy"),
+                resolve(&options.permissions_policy, &base.permissions_policy, String::new(), false).filter(|value| !value.is_empty()),
+            ),
+        ];
+        headers.extend(values.into_iter().filter_map(|(name, value)| value.map(|value| (name, value))));
+
+        if options.no_sniff.or(base.no_sniff).unwrap_or(true) {
+            headers.push((header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()));
+        }
+        if options.origin_agent_cluster.or(base.origin_agent_cluster).unwrap_or(true) {
+            headers.push((HeaderName::from_static("origin-agent-cluster"), "?1".to_string()));
+        }
+        if options.legacy_headers.or(base.legacy_headers).unwrap_or(true) {
+            headers.push((header::X_DNS_PREFETCH_CONTROL, "off".to_string()));
+            headers.push((HeaderName::from_static("x-download-options"), "noopen".to_string()));
+            headers.push((HeaderName::from_static("x-permitted-cross-domain-policies"), "none".to_string()));
+            headers.push((header::X_XSS_PROTECTION, "0".to_string()));
+        }
+
+        Self {
+            headers: headers
+                .into_iter()
+                .filter_map(|(name, value)| match HeaderValue::from_str(&value) {
+                    Ok(value) => Some((name, value)),
+                    Err(_) => {
+                        warn!("⚠️ Invalid {} header value: {}", name, value);
+                        None
+                    }
+                })
+                .collect(),
+            csp: resolve(&options.content_security_policy, &base.content_security_policy, CspOptions::default(), true)
+                .map(|csp| ContentSecurityPolicy::new(&csp)),
+        }
+    }
+}
+
+/// Compiled security headers configuration of a server
+#[derive(Debug)]
+pub struct SecurityHeadersConfig {
+    default: Arc<SecurityPolicy>,
+    // Route overrides, most specific pattern first; None sends no headers
+    routes: Vec<(GlobMatcher, Option<Arc<SecurityPolicy>>)>,
+}
+
+impl SecurityHeadersConfig {
+    pub fn new(options: &SecurityHeadersOptions) -> Self {
+        let mut routes: Vec<(String, GlobMatcher, Option<Arc<SecurityPolicy>>)> = options
+            .routes
+            .iter()
+            .filter_map(|(pattern, route)| match Glob::new(pattern) {
+                Ok(glob) => {
+                    let policy = match route {
+                        RouteSecurityHeaders::Enabled(false) => None,
+                        RouteSecurityHeaders::Enabled(true) => Some(SecurityPolicy::new(options, options)),
+                        RouteSecurityHeaders::Options(route) => Some(SecurityPolicy::new(route, options)),
+                    };
+                    Some((pattern.clone(), glob.compile_matcher(), policy.map(Arc::new)))
+                }
+                Err(e) => {
+                    warn!("⚠️ Invalid security headers route pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        routes.sort_by_key(|(pattern, _, _)| std::cmp::Reverse(pattern.len()));
+
+        Self {
+            default: Arc::new(SecurityPolicy::new(options, options)),
+            routes: routes.into_iter().map(|(_, matcher, policy)| (matcher, policy)).collect(),
+        }
+    }
+
+    /// Policy for a request path, if it gets security headers
+    pub fn policy_for(&self, path: &str) -> Option<&Arc<SecurityPolicy>> {
+        self.routes
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(path))
+            .map(|(_, policy)| policy.as_ref())
+            .unwrap_or(Some(&self.default))
+    }
+}
+
+// 128 random bits; URL-safe base64 survives template autoescaping
+fn generate_nonce() -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(uuid::Uuid::new_v4().as_bytes())
+}
+
+/// Add the configured security headers to every response; headers set by the handler win
+pub async fn security_headers_layer(config: Arc<SecurityHeadersConfig>, mut req: Request, next: Next) -> Response {
+    let Some(policy) = config.policy_for(req.uri().path()).cloned() else {
+        return next.run(req).await;
+    };
+
+    let nonce = policy.csp.as_ref().filter(|csp| csp.nonce).map(|_| generate_nonce());
+    if let Some(nonce) = &nonce {
+        debug!("🛡️ CSP nonce for {}: {}", req.uri().path(), nonce);
+        req.extensions_mut().insert(CspNonce(nonce.clone()));
+    }
+
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    for (name, value) in &policy.headers {
+        if !headers.contains_key(name) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+    if let Some(csp) = &policy.csp
+        && !headers.contains_key(&csp.header)
+    {
+        match HeaderValue::from_str(&csp.value(nonce.as_deref())) {
+            Ok(value) => {
+                headers.insert(csp.header.clone(), value);
+            }
+            Err(_) => warn!("⚠️ Invalid Content-Security-Policy, header not sent"),
+        }
+    }
+    headers.remove("x-powered-by");
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(json: &str) -> SecurityHeadersOptions {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn header<'a>(policy: &'a SecurityPolicy, name: &str) -> Option<&'a str> {
+        policy
+            .headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .and_then(|(_, value)| value.to_str().ok())
+    }
+
+    #[test]
+    fn test_csp_directives_and_nonce() {
+        let options = options(
+            r#"{"contentSecurityPolicy": {"nonce": true, "directives": {"scriptSrc": ["'self'", "cdn.example.com"], "upgrade-insecure-requests": false}}}"#,
+        );
+        let config = SecurityHeadersConfig::new(&options);
+        let csp = config.policy_for("/").unwrap().csp.as_ref().unwrap();
+        let value = csp.value(Some("abc"));
+
+        assert!(value.starts_with("default-src 'self'; "));
+        assert!(value.contains("script-src 'self' cdn.example.com 'nonce-abc'"));
+        assert!(value.contains("style-src 'self' https: 'unsafe-inline' 'nonce-abc'"));
+        assert!(!value.contains("upgrade-insecure-requests"));
+    }
+
+    #[test]
+    fn test_route_overrides() {
+        let options = options(
+            r#"{"frameOptions": "DENY", "routes": {"/embed/*": {"frameOptions": false}, "/raw/*": false}}"#,
+        );
+        let config = SecurityHeadersConfig::new(&options);
+
+        let default = config.policy_for("/").unwrap();
+        assert_eq!(header(default, "x-frame-options"), Some("DENY"));
+        assert_eq!(header(default, "strict-transport-security"), Some("max-age=31536000; includeSubDomains"));
+
+        let embed = config.policy_for("/embed/widget").unwrap();
+        assert_eq!(header(embed, "x-frame-options"), None);
+        assert_eq!(header(embed, "x-content-type-options"), Some("nosniff"));
+
+        assert!(config.policy_for("/raw/file").is_none());
+    }
+}
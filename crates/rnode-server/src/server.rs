@@ -1,30 +1,150 @@
-use crate::handlers::dynamic_handler;
+use crate::app::app_from_arg;
+use crate::listener::{self, BoundListener};
 
-use crate::metrics::{init_metrics, render_metrics, http::track_metrics, websocket::update_websocket_metrics, system::update_system_metrics};
+use crate::metrics::{init_metrics, websocket::update_websocket_metrics, system::update_system_metrics};
+use crate::handlers::response_hooks::{ResponseHooks, ResponseTimeHook};
 use crate::request::Request;
-use crate::static_files::fallback::handle_static_fallback;
-use crate::file_operations::handlers::{download_handler_impl, upload_handler_impl};
-use crate::types::{get_download_routes, get_event_queue, get_routes, get_upload_routes};
+use crate::route_table;
+use crate::shutdown::ServerShutdown;
+use crate::telemetry::{Span, SpanKind, TraceContext};
+use crate::types::RouteOptions;
 use crate::utils::config_extractor;
-use crate::websocket;
 use axum::{
     Router,
-    body::Body,
-    extract::Request as AxumRequest,
+    body::{Body, Bytes},
+    extract::{MatchedPath, Request as AxumRequest},
+    http::{StatusCode, header},
     middleware::Next,
-    routing::{any, delete, get, options, patch, post, put, trace},
 };
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 use neon::prelude::*;
 
-use axum_server::tls_rustls::RustlsConfig;
+use tokio_rustls::TlsAcceptor;
 
 use log::{debug, error, info, warn};
 
-use std::net::SocketAddr;
+use tokio_util::sync::CancellationToken;
+
+/// Request body limits of a route table
+pub struct BodyLimits {
+    pub max_body_size: usize,
+    // Route options by "METHOD:path"
+    pub routes: HashMap<String, RouteOptions>,
+}
+
+impl BodyLimits {
+    // Options of the route matched for this request
+    fn route_options(&self, req: &AxumRequest<Body>) -> Option<&RouteOptions> {
+        let path = req.extensions().get::<MatchedPath>()?.as_str();
+        self.routes
+            .get(&format!("{}:{}", req.method(), path))
+            .or_else(|| self.routes.get(&format!("ANY:{}", path)))
+    }
+}
+
+// Why a request body could not be read
+pub(crate) enum BodyReadError {
+    TooLarge,
+    Read(axum::Error),
+}
+
+// Read the whole body, stopping as soon as it exceeds the limit
+pub(crate) async fn read_body_limited(body: Body, limit: usize) -> Result<Bytes, BodyReadError> {
+    let mut stream = body.into_data_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(BodyReadError::Read)?;
+        if buffer.len() + chunk.len() > limit {
+            return Err(BodyReadError::TooLarge);
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buffer))
+}
+
+// Body that fails once more than `limit` bytes have been read
+fn limit_body(body: Body, limit: usize) -> Body {
+    let mut read = 0;
+    Body::from_stream(body.into_data_stream().map(move |chunk| {
+        let chunk = chunk.map_err(std::io::Error::other)?;
+        read += chunk.len();
+        if read > limit {
+            return Err(std::io::Error::other(format!(
+                "Request body exceeds {} bytes",
+                limit
+            )));
+        }
+        Ok(chunk)
+    }))
+}
+
+pub(crate) fn payload_too_large(limit: usize) -> axum::response::Response {
+    crate::html_templates::generate_error_page(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "Payload Too Large",
+        "The request body exceeds the size limit.",
+        Some(&format!("Limit: {} bytes", limit)),
+        false,
+    )
+}
+
+// Server span of a request, continuing the caller's trace from `traceparent`
+fn start_server_span(req: &AxumRequest<Body>) -> Option<Span> {
+    let parent = req
+        .headers()
+        .get("traceparent")
+        .and_then(|value| value.to_str().ok())
+        .and_then(TraceContext::parse);
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let mut span = Span::start(format!("{} {}", req.method(), route), SpanKind::Server, parent.as_ref())?;
+    span.set_attribute("http.request.method", req.method().as_str());
+    span.set_attribute("url.path", req.uri().path());
+    span.set_attribute("http.route", route);
+    if let Some(client) = req.extensions().get::<crate::client_ip::ClientIp>() {
+        span.set_attribute("client.address", client.ip.clone());
+    }
+    if let Some(request_id) = req.extensions().get::<crate::access_log::RequestId>() {
+        span.set_attribute("rnode.request_id", request_id.0.clone());
+    }
+    Some(span)
+}
 
 // Слой для формирования Request и Response объектов
-async fn request_response_layer(
+pub async fn request_response_layer(
+    app_id: u32,
+    limits: Arc<BodyLimits>,
+    mut req: AxumRequest<Body>,
+    next: Next,
+) -> Result<axum::response::Response, axum::http::StatusCode> {
+    let mut span = start_server_span(&req);
+    if let Some(span) = &span {
+        req.extensions_mut().insert(span.context().clone());
+    }
+
+    let result = build_request(app_id, limits, req, next).await;
+
+    if let (Some(span), Ok(response)) = (span.as_mut(), &result) {
+        let status = response.status();
+        span.set_attribute("http.response.status_code", status.as_u16());
+        if status.is_server_error() {
+            span.set_error(status.to_string());
+        }
+    }
+    result
+}
+
+// Parse the body into a Request object for the handlers
+async fn build_request(
+    app_id: u32,
+    limits: Arc<BodyLimits>,
     req: AxumRequest<Body>,
     next: Next,
 ) -> Result<axum::response::Response, axum::http::StatusCode> {
@@ -36,75 +156,155 @@ async fn request_response_layer(
         .unwrap_or("")
         .to_string();
 
-    // For multipart requests, do not consume body here
+    let route_options = limits.route_options(&req).cloned().unwrap_or_default();
+    let max_body_size = route_options.max_body_size.unwrap_or(limits.max_body_size);
+
+    // Reject declared oversized bodies before reading anything
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.parse::<usize>().ok());
+    if content_length.is_some_and(|length| length > max_body_size) {
+        debug!("🚫 Request body of {:?} bytes exceeds {} bytes", content_length, max_body_size);
+        return Ok(payload_too_large(max_body_size));
+    }
+
+    // For multipart requests, do not consume body here; upload routes stream it under the limit
     if content_type.contains("multipart/form-data") {
         // Create Request object from axum Request without parsing body
-        let request = Request::from_axum_request(&req);
-        
-        // Save in extensions for further transmission
-        let mut req = req;
-        req.extensions_mut().insert(request);
-        
-        // Pass request further without changing body
-        Ok(next.run(req).await)
-    } else {
-        // For other requests, parse body as usual
-        let (parts, body) = req.into_parts();
+        let mut request = Request::from_axum_request(&req);
+        request.app_id = app_id;
 
-        // Extract request body
-        let body_bytes = axum::body::to_bytes(body, usize::MAX).await.ok();
+        let (parts, body) = req.into_parts();
+        let mut req = AxumRequest::from_parts(parts, limit_body(body, max_body_size));
+        req.extensions_mut().insert(request);
 
-        // Create new request from parts
-        let mut req = AxumRequest::from_parts(parts, Body::empty());
+        return Ok(next.run(req).await);
+    }
 
-        // Create Request object from axum Request
+    if route_options.stream_body {
+        // The handler reads the raw body as a stream
         let mut request = Request::from_axum_request(&req);
+        request.app_id = app_id;
 
-        // Parse request body if it exists
-        if let Some(body_bytes) = body_bytes {
-            use crate::request_parser::RequestParser;
-            let (parsed_body, files) =
-                RequestParser::parse_request_body(&body_bytes, &request.content_type).await;
-            request.body = parsed_body;
-            request.files = files;
-        }
-
-        // Save in extensions for further transmission
+        let (parts, body) = req.into_parts();
+        let mut req = AxumRequest::from_parts(parts, limit_body(body, max_body_size));
         req.extensions_mut().insert(request);
 
-        // Pass request further
-        Ok(next.run(req).await)
+        return Ok(next.run(req).await);
     }
-}
 
-// Structure for SSL/TLS configuration
-#[derive(Debug)]
-pub struct SslConfig {
-    pub cert_file: Option<String>,
-    pub key_file: Option<String>,
-}
+    // For other requests, parse body as usual
+    let (parts, body) = req.into_parts();
 
-impl SslConfig {
-    pub fn from_files(cert_path: &str, key_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(SslConfig {
-            cert_file: Some(cert_path.to_string()),
-            key_file: Some(key_path.to_string()),
-        })
+    // Extract request body
+    let body_bytes = match read_body_limited(body, max_body_size).await {
+        Ok(body_bytes) => Some(body_bytes),
+        Err(BodyReadError::TooLarge) => {
+            debug!("🚫 Request body exceeds {} bytes", max_body_size);
+            return Ok(payload_too_large(max_body_size));
+        }
+        Err(BodyReadError::Read(e)) => {
+            debug!("⚠️ Failed to read request body: {}", e);
+            None
+        }
+    };
+
+    // Create new request from parts
+    let mut req = AxumRequest::from_parts(parts, Body::empty());
+
+    // Create Request object from axum Request
+    let mut request = Request::from_axum_request(&req);
+    request.app_id = app_id;
+
+    // Parse request body if it exists
+    if let Some(body_bytes) = body_bytes {
+        use crate::request_parser::RequestParser;
+        let (parsed_body, files) =
+            RequestParser::parse_request_body(&body_bytes, &request.content_type).await;
+        request.body = parsed_body;
+        request.files = files;
     }
+
+    // Save in extensions for further transmission
+    req.extensions_mut().insert(request);
+
+    // Pass request further
+    Ok(next.run(req).await)
 }
 
 // Function for starting the server
 pub fn start_listen(mut cx: FunctionContext) -> JsResult<JsUndefined> {
-    let (port, host, ssl_config, metrics_enabled, timeout, dev_mode) =
-        config_extractor::extract_server_params(&mut cx)?;
+    let config_extractor::ServerParams {
+        addresses,
+        ipv6_only,
+        ssl_config,
+        metrics_enabled,
+        timeout,
+        dev_mode,
+        max_body_size,
+        method_not_allowed_as_404,
+        cors,
+        rate_limit,
+        trusted_proxies,
+        security_headers,
+        compression,
+        request_id_header,
+        access_log,
+        tracing,
+        http2,
+        https_redirect,
+        connection,
+        response_time,
+    } = config_extractor::extract_server_params(&mut cx)?;
+    let instance = app_from_arg(&mut cx, 3)?;
+
+    // A conflicting route would make building the router panic
+    if let Err(conflict) = route_table::check_routes(&instance, metrics_enabled) {
+        return cx.throw_error(conflict);
+    }
+
+    // Load certificates up front so strict mode can fail listen() itself
+    let tls = match ssl_config {
+        Some(options) => {
+            let strict = options.strict;
+            let loaded = crate::tls::CertificateStore::load(options).map(Arc::new).and_then(|store| {
+                let config = crate::tls::server_config(store.clone(), crate::http2::alpn_protocols(http2.as_ref()))?;
+                Ok((store, config))
+            });
+            match loaded {
+                Ok(loaded) => Some(loaded),
+                Err(e) if strict => {
+                    return cx.throw_error(format!("Failed to load TLS certificates: {}", e));
+                }
+                Err(e) => {
+                    error!("❌ Failed to create HTTPS configuration: {}", e);
+                    warn!("🔄 Falling back to HTTP server");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    *instance.tls.write().unwrap() = tls.as_ref().map(|(store, _)| store.clone());
+
+    let https_redirect = https_redirect.filter(|_| {
+        if tls.is_none() {
+            warn!("⚠️ httpsRedirect needs a working ssl configuration, redirect listener not started");
+        }
+        tls.is_some()
+    });
+
     info!(
-        "🚀 Starting server on {}:{} {}",
-        host.iter()
-            .map(|b| b.to_string())
+        "🚀 Starting app {} on {} {}",
+        instance.id,
+        addresses
+            .iter()
+            .map(|addr| addr.to_string())
             .collect::<Vec<_>>()
-            .join("."),
-        port,
-        if ssl_config.is_some() {
+            .join(", "),
+        if tls.is_some() {
             "(HTTPS)"
         } else {
             "(HTTP)"
@@ -114,10 +314,9 @@ pub fn start_listen(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     // Create Channel for communication with JavaScript
     let queue = cx.channel();
 
-    // Save Channel in global variable
+    // Save Channel on the app instance
     {
-        let event_queue_guard = get_event_queue();
-        let mut event_queue_map = event_queue_guard.write().unwrap();
+        let mut event_queue_map = instance.event_queue.write().unwrap();
         *event_queue_map = Some(queue);
     }
 
@@ -129,180 +328,54 @@ pub fn start_listen(mut cx: FunctionContext) -> JsResult<JsUndefined> {
             .enable_all()
             .build()
             .unwrap();
-        rt.block_on(async {
-            // Create base router
-            let mut app = Router::new();
-
-            // Add dynamic routes
-            let routes = get_routes();
-            let routes_map = routes.read().unwrap();
-            // Create clones for use in closures
-            let routes_vec: Vec<(String, String, String)> = routes_map.iter()
-                .map(|(_, route_info)| (route_info.path.clone(), route_info.method.clone(), route_info.handler_id.clone()))
-                .collect();
-
-            debug!("Routes found: {:?}", routes_vec);
-            for (path, method, handler_id) in routes_vec {
-                let path_clone = path.clone();
-                let method_clone = method.clone();
-                let handler_id_clone = handler_id.clone();
-                let handler_fn = move |req: axum::extract::Request| {
-                        let registered_path = path_clone.clone();
-                        let method = method_clone.clone();
-                        let handler_id = handler_id_clone.clone();
-                        let timeout_clone = timeout;
-                        let dev_mode_clone = dev_mode;
-                        async move {
-                            // Get actual path from request
-                            let actual_path = req.uri().path().to_string();
-                            dynamic_handler(req, actual_path, registered_path, method, handler_id, timeout_clone, dev_mode_clone).await
-                        }
-                    };
-
-                match method.as_str() {
-                    "GET" => app = app.route(&path, get(handler_fn)),
-                    "POST" => app = app.route(&path, post(handler_fn)),
-                    "PUT" => app = app.route(&path, put(handler_fn)),
-                    "DELETE" => app = app.route(&path, delete(handler_fn)),
-                    "PATCH" => app = app.route(&path, patch(handler_fn)),
-                    "OPTIONS" => app = app.route(&path, options(handler_fn)),
-                    "TRACE" => app = app.route(&path, trace(handler_fn)),
-                    "ANY" => app = app.route(&path, any(handler_fn)),
-                    _ => {}
-                }
-            }
+        // Register shutdown state so close() can stop this server
+        let shutdown = std::sync::Arc::new(ServerShutdown::new(instance.clone(), rt.handle().clone()));
+        *instance.shutdown.write().unwrap() = Some(shutdown.clone());
 
-            let addr = SocketAddr::from(([127, 0, 0, 1], port));
-            warn!("Server listening on http://{}", addr);
-            debug!("Registered dynamic routes:");
-            for (_, route_info) in routes_map.iter() {
-                debug!("  {} {}", route_info.method, route_info.path);
-            }
-
-            // Release lock before starting server
-            drop(routes_map);
-
-            // Add WebSocket routes
-            let websocket_routes = websocket::get_websocket_routes();
-            let websocket_routes_map = websocket_routes.read().await;
-            let websocket_routes_vec: Vec<(String, websocket::WebSocketHandler)> = websocket_routes_map.iter()
-                .map(|(path, handler)| (path.clone(), handler.clone()))
-                .collect();
-
-            for (path, _handler) in websocket_routes_vec {
-                let path_clone = path.clone();
-                
-                // Generate unique handler ID for WebSocket
-                let handler_id = format!("ws_{}_{}", path.replace('/', "_"), std::process::id());
-                
-                let websocket_handler = move |req: axum::extract::Request| {
-                    let path = path_clone.clone();
-                    let handler_id = handler_id.clone();
-                    async move {
-                        // Use the upgrade handler for WebSocket connections
-                        websocket::upgrade::websocket_upgrade_handler(req, path, handler_id).await
-                    }
-                };
-
-                app = app.route(&path, any(websocket_handler));
-                debug!("🔌 WebSocket route registered: {}", path);
-            }
-
-            // Release WebSocket routes lock
-            drop(websocket_routes_map);
-
-            // Add dynamic routes for file downloads
-            let download_routes = get_download_routes();
-            let download_routes_map = download_routes.read().unwrap();
-
-            for (route_path, config) in download_routes_map.iter() {
-                let config_clone = config.clone();
-                let route_path_clone = route_path.clone();
-
-                let download_handler = move |req: axum::extract::Request| {
-                    let config = config_clone.clone();
-                    let route_path = route_path_clone.clone();
-                    async move {
-                        download_handler_impl(req, config, route_path).await
-                    }
-                };
-
-                // Register route (support wildcard for subfolders)
-                let actual_route = route_path.clone();
-                app = app.route(&actual_route, get(download_handler));
-                info!("📥 Download route registered: {} -> {}", route_path, actual_route);
-            }
-
-            // Release lock
-            drop(download_routes_map);
-
-            // Add routes for file uploads
-            let upload_routes = get_upload_routes();
-            let upload_routes_map = upload_routes.read().unwrap();
-
-            for (route_path, config) in upload_routes_map.iter() {
-                let config_clone = config.clone();
-                let route_path_clone = route_path.clone();
-
-                let upload_handler = move |req: axum::extract::Request| {
-                    let config = config_clone.clone();
-                    let route_path = route_path_clone.clone();
-                    async move {
-                        upload_handler_impl(req, config, route_path, dev_mode).await
-                    }
-                };
-
-                // Register route
-                app = app.route(route_path, post(upload_handler));
-                info!("📤 Upload route registered: {}", route_path);
-            }
-
-            // Release lock
-            drop(upload_routes_map);
-
-            // Add middleware to convert 405 to 404 for unsupported methods
-            app = app.layer(axum::middleware::from_fn(|req: axum::extract::Request, next: axum::middleware::Next| async move {
-                let path = req.uri().path().to_string();
-                let resp = next.run(req).await;
-                let status = resp.status();
-
-                match status {
-                    http::StatusCode::METHOD_NOT_ALLOWED => {
-                        // Convert 405 to 404
-                        debug!("🔧 Converting 405 to 404 for path: {}", path);
-
-                        // Use html_templates for 405 response
-                        crate::html_templates::generate_error_page(
-                            http::StatusCode::METHOD_NOT_ALLOWED,
-                            "Method Not Allowed",
-                            "The HTTP method used is not allowed for this resource.",
-                            Some(&format!("Path: {}", path)),
-                            false // dev_mode
-                        )
-                    }
-                    _ => resp
-                }
-            }));
+        // Initialize metrics
+        if metrics_enabled {
+            init_metrics();
+        }
 
-             // Добавляем layer'ы для Request/Response и middleware
-            // Слой 1: Формирование Request и Response объектов
-            app = app.layer(axum::middleware::from_fn(request_response_layer));
+        // Span exporter, shared by every app in the process
+        if let Some(tracing) = &tracing {
+            crate::telemetry::init_tracing(tracing);
+        }
 
-            // Add fallback route for static files
-            let timeout_clone = timeout;
-            let mut app = app.fallback(move |req: http::Request<axum::body::Body>| async move {
-                handle_static_fallback(req, timeout_clone).await
-            });
+        // Native response hooks, run after the JS ones
+        let mut response_hooks = ResponseHooks::default();
+        if response_time {
+            response_hooks.push(ResponseTimeHook);
+        }
 
+        // Build the route table outside the runtime (registries use blocking locks)
+        route_table::install(&instance, route_table::RouterSettings {
+            timeout,
+            dev_mode,
+            metrics_enabled,
+            max_body_size,
+            method_not_allowed_as_404,
+            cors: cors.map(|options| std::sync::Arc::new(crate::cors::CorsConfig::new(&options))),
+            rate_limit: rate_limit
+                .map(|options| std::sync::Arc::new(crate::rate_limit::RateLimitConfig::new(&options))),
+            trusted_proxies: std::sync::Arc::new(
+                trusted_proxies
+                    .map(|entries| crate::client_ip::TrustedProxies::new(&entries))
+                    .unwrap_or_default(),
+            ),
+            security_headers: security_headers
+                .map(|options| std::sync::Arc::new(crate::security_headers::SecurityHeadersConfig::new(&options))),
+            compression: compression
+                .map(|options| std::sync::Arc::new(crate::compression::dynamic::CompressionConfig::new(&options))),
+            access_log: std::sync::Arc::new(crate::access_log::AccessLogConfig::new(
+                request_id_header.as_deref(),
+                access_log.as_ref(),
+            )),
+            response_hooks: std::sync::Arc::new(response_hooks),
+        });
 
-            // Add metrics if enabled
+        rt.block_on(async {
             if metrics_enabled {
-                // Initialize metrics
-                init_metrics();
-
-                // Add middleware to track HTTP metrics
-                app = app.route_layer(axum::middleware::from_fn(track_metrics));
-
                 // Start background task to update metrics periodically
                 tokio::spawn(async move {
                     let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
@@ -312,57 +385,94 @@ pub fn start_listen(mut cx: FunctionContext) -> JsResult<JsUndefined> {
                         update_websocket_metrics();
                     }
                 });
-
-                // Add /metrics endpoint
-                app = app.route("/metrics", get(|| async {
-                    render_metrics()
-                }));
-
-                // Add /health endpoint
-                app = app.route("/health", get(|| async {
-                    "OK"
-                }));
-
-                // Add /info endpoint with process information
-                app = app.route("/info", get(|| async {
-                    "Use /metrics endpoint for detailed system information"
-                }));
                 info!("📊 Metrics routes added: /metrics, /health, /info");
             } else {
                 info!("📊 Metrics disabled");
             }
 
-            // Start server based on SSL configuration
-            if let Some(ssl_config) = &ssl_config {
-                // Start HTTPS server using axum-server
-                let cert_path = ssl_config.cert_file.as_ref().unwrap();
-                let key_path = ssl_config.key_file.as_ref().unwrap();
-
-                match RustlsConfig::from_pem_file(cert_path, key_path).await {
-                    Ok(tls_config) => {
-                        info!("🔒 Starting HTTPS server with TLS configuration");
-                        warn!("🔒 HTTPS server listening on https://{}", addr);
-
-                        // Use axum-server with TLS
-                        axum_server::bind_rustls(addr, tls_config)
-                            .serve(app.into_make_service())
-                            .await
-                            .unwrap();
+            // Serve through the hot route table so routes registered later apply immediately
+            let app = route_table::hot_router(instance.clone());
+
+            // Bind every configured address
+            let mut listeners = Vec::new();
+            for address in &addresses {
+                match listener::bind(address, ipv6_only) {
+                    Ok(bound) => listeners.push(bound),
+                    Err(e) => error!("❌ Failed to bind {}: {}", address, e),
+                }
+            }
+            if listeners.is_empty() {
+                error!("❌ No listen address could be bound, server not started");
+                return;
+            }
+
+            // TLS configuration; certificates are picked per connection, so reloads apply to new handshakes
+            let tls_config = tls.map(|(store, server_config)| {
+                info!("🔒 Starting HTTPS server with TLS configuration");
+                if store.options().watch {
+                    tokio::spawn(store.watch(shutdown.token.clone()));
+                }
+                TlsAcceptor::from(Arc::new(server_config))
+            });
+
+            // Plain listener sending clients to the HTTPS origin; it defaults to the main TCP listener's IP and port
+            let main_tcp = listeners.iter().find_map(|bound| match bound {
+                BoundListener::Tcp(tcp_listener) => tcp_listener.local_addr().ok(),
+                #[cfg(unix)]
+                BoundListener::Unix(..) => None,
+            });
+            let redirect = https_redirect.and_then(|mut options| {
+                let Some(address) = options.listen_address(main_tcp) else {
+                    warn!("⚠️ httpsRedirect needs a host when the server has no TCP listener, redirect listener not started");
+                    return None;
+                };
+                if let Some(main) = main_tcp {
+                    options.https_port.get_or_insert(main.port());
+                }
+                match listener::bind(&address, ipv6_only) {
+                    Ok(bound) => {
+                        info!("↪️ Redirecting {} to HTTPS", address);
+                        Some((bound, options))
                     }
                     Err(e) => {
-                        error!("❌ Failed to create HTTPS configuration: {}", e);
-                        warn!("🔄 Falling back to HTTP server");
-                        // Fallback to HTTP
-                        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-                        warn!("🌐 HTTP server listening on http://{}", addr);
-                        axum::serve(listener, app).await.unwrap();
+                        error!("❌ Failed to bind HTTPS redirect listener {}: {}", address, e);
+                        None
                     }
                 }
-            } else {
-                // Start HTTP server
-                let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-                warn!("🌐 HTTP server listening on http://{}", addr);
-                axum::serve(listener, app).await.unwrap();
+            });
+
+            // Connection limits are shared by every listener of the server
+            let limits = Arc::new(crate::connection::ConnectionLimits::new(connection));
+
+            // Start server on every listener
+            let serve = futures::future::join_all(
+                listeners
+                    .into_iter()
+                    .map(|bound| (bound, app.clone(), tls_config.clone()))
+                    .chain(redirect.map(|(bound, options)| {
+                        let config = Arc::new(crate::https_redirect::HttpsRedirect::new(&options));
+                        let redirect_app = app.clone().layer(axum::middleware::from_fn(move |req, next| {
+                            crate::https_redirect::https_redirect_layer(config.clone(), req, next)
+                        }));
+                        (bound, redirect_app, None)
+                    }))
+                    .map(|(bound, app, tls_config)| {
+                        serve_listener(bound, app, tls_config, http2.as_ref(), limits.clone(), shutdown.token.clone())
+                    }),
+            );
+
+            tokio::select! {
+                _ = serve => {
+                    info!("🛑 Server stopped accepting connections");
+                    shutdown.serve_done.notify_one();
+                    // Keep the runtime alive until close() finishes draining
+                    if shutdown.token.is_cancelled() {
+                        shutdown.drained.notified().await;
+                    }
+                }
+                _ = shutdown.drained.notified() => {
+                    warn!("⏰ Forcing server stop after shutdown deadline");
+                }
             }
         });
     });
@@ -372,3 +482,56 @@ pub fn start_listen(mut cx: FunctionContext) -> JsResult<JsUndefined> {
 
     Ok(cx.undefined())
 }
+
+// Serve the app on a single bound listener until shutdown is requested
+async fn serve_listener(
+    bound: BoundListener,
+    app: Router,
+    tls_config: Option<TlsAcceptor>,
+    http2: Option<&crate::http2::Http2Options>,
+    limits: Arc<crate::connection::ConnectionLimits>,
+    token: CancellationToken,
+) {
+    match bound {
+        BoundListener::Tcp(tcp_listener) => {
+            let addr = tcp_listener.local_addr().ok();
+            // Plain listeners speak HTTP/2 only with prior knowledge
+            let http2 = http2.filter(|options| tls_config.is_some() || options.h2c);
+            match (&tls_config, http2) {
+                // ALPN picks HTTP/2 or HTTP/1.1
+                (Some(_), _) => warn!("🔒 HTTPS server listening on https://{}", display_addr(addr)),
+                (None, Some(_)) => {
+                    warn!("🌐 HTTP server listening on http://{} (HTTP/1.1 and h2c)", display_addr(addr))
+                }
+                (None, None) => warn!("🌐 HTTP server listening on http://{}", display_addr(addr)),
+            }
+
+            let mut builder = hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+            limits.configure(&mut builder);
+            crate::http2::configure(&mut builder, http2);
+            let tcp_listener = axum::serve::ListenerExt::tap_io(tcp_listener, |stream: &mut tokio::net::TcpStream| {
+                let _ = stream.set_nodelay(true);
+            });
+            crate::connection::serve(tcp_listener, app, builder, tls_config, limits, token).await;
+        }
+        #[cfg(unix)]
+        BoundListener::Unix(unix_listener, path) => {
+            if tls_config.is_some() {
+                warn!("⚠️ TLS is not applied to Unix socket {}, serving plain HTTP", path.display());
+            }
+            warn!("🌐 HTTP server listening on unix:{}", path.display());
+            // Same limits and connection accounting as TCP listeners
+            let mut builder = hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+            limits.configure(&mut builder);
+            crate::http2::configure(&mut builder, http2.filter(|options| options.h2c));
+            crate::connection::serve(unix_listener, app, builder, None, limits, token).await;
+            // Remove the socket file once the listener is closed
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+fn display_addr(addr: Option<std::net::SocketAddr>) -> String {
+    addr.map(|addr| addr.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
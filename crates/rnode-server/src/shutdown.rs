@@ -0,0 +1,147 @@
+use crate::app::{App, app_from_arg};
+use log::{debug, info, warn};
+use neon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+// Default deadline for draining in-flight requests on close()
+const DEFAULT_CLOSE_TIMEOUT_MS: u64 = 10000;
+
+/// Shutdown state of a running app server
+pub struct ServerShutdown {
+    /// App instance the server belongs to
+    pub app: Arc<App>,
+    /// Cancelled when the server must stop accepting connections
+    pub token: CancellationToken,
+    /// Runtime the server is running on
+    pub runtime: tokio::runtime::Handle,
+    /// Notified by the server thread once all listeners have stopped
+    pub serve_done: Notify,
+    /// Notified by close() once draining is complete (or the deadline passed)
+    pub drained: Notify,
+    inflight: AtomicUsize,
+    inflight_zero: Notify,
+}
+
+impl ServerShutdown {
+    pub fn new(app: Arc<App>, runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            app,
+            token: CancellationToken::new(),
+            runtime,
+            serve_done: Notify::new(),
+            drained: Notify::new(),
+            inflight: AtomicUsize::new(0),
+            inflight_zero: Notify::new(),
+        }
+    }
+
+    pub fn inflight(&self) -> usize {
+        self.inflight.load(Ordering::SeqCst)
+    }
+
+    // Wait until all in-flight requests finish
+    async fn wait_inflight(&self) {
+        loop {
+            let notified = self.inflight_zero.notified();
+            if self.inflight() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Guard counting a request as in-flight until dropped
+pub struct InflightGuard {
+    shutdown: Arc<ServerShutdown>,
+}
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        if self.shutdown.inflight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shutdown.inflight_zero.notify_waiters();
+        }
+    }
+}
+
+/// Mark a dynamic request as in-flight for the duration of the returned guard
+pub fn track_request(app: &App) -> Option<InflightGuard> {
+    let shutdown = app.shutdown.read().unwrap().clone()?;
+    shutdown.inflight.fetch_add(1, Ordering::SeqCst);
+    Some(InflightGuard { shutdown })
+}
+
+/// Stop the server and drain in-flight work within the deadline
+pub async fn drain(shutdown: Arc<ServerShutdown>, timeout: Duration) {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    // Stop accepting new connections
+    info!("🛑 Shutting down server, draining {} in-flight requests", shutdown.inflight());
+    shutdown.token.cancel();
+
+    // Wait for pending dynamic handlers
+    if tokio::time::timeout_at(deadline, shutdown.wait_inflight()).await.is_err() {
+        warn!(
+            "⏰ Shutdown deadline reached with {} requests still in flight",
+            shutdown.inflight()
+        );
+    }
+
+    // Close every WebSocket session
+    let closed =
+        crate::websocket::connections::close_all_connections(shutdown.app.id, "Server shutting down").await;
+    debug!("🔌 Sent close frames to {} WebSocket connections", closed);
+
+    // Flush cache backends
+    if let Err(e) = crate::cache::cache_flush() {
+        warn!("⚠️ Failed to flush cache on shutdown: {}", e);
+    }
+
+    // Wait for listeners to stop
+    if tokio::time::timeout_at(deadline, shutdown.serve_done.notified()).await.is_err() {
+        warn!("⏰ Listeners did not stop before the shutdown deadline, forcing close");
+    }
+
+    // Drop the route table so a later listen() starts from the registries again
+    crate::route_table::clear(&shutdown.app);
+
+    info!("✅ Server shutdown complete");
+}
+
+// Function for stopping the server - returns a Promise resolved after draining
+pub fn close_server(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let timeout = cx
+        .argument_opt(0)
+        .and_then(|arg| arg.downcast::<JsNumber, _>(&mut cx).ok())
+        .map(|num| num.value(&mut cx) as u64)
+        .unwrap_or(DEFAULT_CLOSE_TIMEOUT_MS);
+
+    let instance = app_from_arg(&mut cx, 1)?;
+
+    let channel = cx.channel();
+    let (deferred, promise) = cx.promise();
+
+    let shutdown = instance.shutdown.write().unwrap().take();
+
+    match shutdown {
+        Some(shutdown) => {
+            let runtime = shutdown.runtime.clone();
+            runtime.spawn(async move {
+                drain(shutdown.clone(), Duration::from_millis(timeout)).await;
+                deferred.settle_with(&channel, |mut cx| Ok(cx.undefined()));
+                // Let the server thread release its runtime
+                shutdown.drained.notify_one();
+            });
+        }
+        None => {
+            debug!("🛑 close() called but no server is running");
+            deferred.settle_with(&channel, |mut cx| Ok(cx.undefined()));
+        }
+    }
+
+    Ok(promise)
+}
@@ -0,0 +1,241 @@
+use crate::app::App;
+use crate::handlers::dynamic_handler::{extract_path_parameters, extract_query_parameters};
+use crate::handlers::middleware::execute_middleware;
+use crate::metrics::http::{record_sse_connection_end, record_sse_connection_start, record_sse_event};
+use crate::request::Request;
+use axum::body::{Body, Bytes};
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use log::{debug, warn};
+use neon::event::Channel;
+use neon::prelude::*;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::{SseEvent, SseRoute};
+
+// Events queued for a client before send() starts returning false
+const SSE_CHANNEL_CAPACITY: usize = 256;
+
+const KEEP_ALIVE_FRAME: &[u8] = b": keep-alive\n\n";
+
+// Ends the connection's metrics and tells JS the client is gone
+struct ConnectionGuard {
+    id: Uuid,
+    path: String,
+    started: Instant,
+    channel: Channel,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        record_sse_connection_end(&self.path, self.started.elapsed().as_secs_f64());
+        debug!("📡 SSE connection {} closed after {:?}", self.id, self.started.elapsed());
+
+        let id = self.id.to_string();
+        let _join_handle = self.channel.send(move |mut cx| {
+            let global: Handle<JsObject> = cx.global("global")?;
+            let close_fn: Handle<JsFunction> = global.get(&mut cx, "closeSseConnection")?;
+            close_fn.call_with(&cx).arg(cx.string(id)).exec(&mut cx)
+        });
+    }
+}
+
+// State of an event-stream body
+struct Connection {
+    replay: VecDeque<Bytes>,
+    events: mpsc::Receiver<Bytes>,
+    keep_alive: tokio::time::Interval,
+    shutdown: CancellationToken,
+    guard: ConnectionGuard,
+}
+
+// Handler for SSE routes: runs middleware, starts the JS handler and streams its events
+pub async fn sse_handler(
+    app: Arc<App>,
+    req: axum::extract::Request,
+    route: SseRoute,
+    timeout: u64,
+    dev_mode: bool,
+) -> Response<Body> {
+    let Some(mut request) = req.extensions().get::<Request>().cloned() else {
+        return crate::html_templates::generate_generic_error_page(
+            "Server configuration error",
+            Some("Request or Response objects not found in extensions"),
+        );
+    };
+
+    let actual_path = req.uri().path().to_string();
+    request.path = actual_path.clone();
+    request.registered_path = route.path.clone();
+    request.method = "GET".to_string();
+    extract_path_parameters(&mut request, &actual_path, &route.path);
+    if let Some(query) = req.uri().query() {
+        extract_query_parameters(&mut request, query);
+    }
+
+    // Middleware (auth etc.) runs once, before the stream opens
+    let mut remaining_timeout = timeout;
+    if let Err(middleware_response) =
+        execute_middleware(&app, &mut request, &mut remaining_timeout, dev_mode).await
+    {
+        return middleware_response;
+    }
+
+    let Some(channel) = app.channel() else {
+        return crate::html_templates::generate_error_page(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Service Unavailable",
+            "The server is not accepting requests.",
+            None,
+            dev_mode,
+        );
+    };
+
+    let connection_id = Uuid::new_v4();
+    let last_event_id = req
+        .headers()
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    // Events the client missed while reconnecting
+    let replay: VecDeque<Bytes> = last_event_id
+        .as_deref()
+        .map(|id| route.replay.lock().unwrap().since(id).into())
+        .unwrap_or_default();
+    debug!(
+        "📡 SSE connection {} on {} (handler {}, Last-Event-ID: {:?}, {} to replay)",
+        connection_id,
+        route.path,
+        route.handler_id,
+        last_event_id,
+        replay.len()
+    );
+
+    let (tx, rx) = mpsc::channel(SSE_CHANNEL_CAPACITY);
+    open_js_stream(&channel, &route, connection_id, request.to_json_map(), tx);
+
+    record_sse_connection_start(&route.path);
+    let shutdown = app
+        .shutdown
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|shutdown| shutdown.token.clone())
+        .unwrap_or_default();
+    let period = route.keep_alive;
+    let connection = Connection {
+        replay,
+        events: rx,
+        keep_alive: tokio::time::interval_at(tokio::time::Instant::now() + period, period),
+        shutdown,
+        guard: ConnectionGuard {
+            id: connection_id,
+            path: route.path.clone(),
+            started: Instant::now(),
+            channel,
+        },
+    };
+
+    let stream = futures_util::stream::unfold(connection, |mut connection| async move {
+        if let Some(frame) = connection.replay.pop_front() {
+            record_sse_event(&connection.guard.path, "replay");
+            return Some((Ok::<_, Infallible>(frame), connection));
+        }
+
+        let frame = tokio::select! {
+            frame = connection.events.recv() => {
+                record_sse_event(&connection.guard.path, "event");
+                frame?
+            }
+            _ = connection.keep_alive.tick() => {
+                record_sse_event(&connection.guard.path, "keepalive");
+                Bytes::from_static(KEEP_ALIVE_FRAME)
+            }
+            // Server closing: end the stream so the client reconnects elsewhere
+            _ = connection.shutdown.cancelled() => return None,
+        };
+        // Any traffic resets the keep-alive timer
+        connection.keep_alive.reset();
+        Some((Ok(frame), connection))
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header("x-accel-buffering", "no")
+        .body(Body::from_stream(stream))
+        .unwrap()
+}
+
+// Call global.executeSse(handler, requestJson, connectionId, send) on the JS thread
+fn open_js_stream(
+    channel: &Channel,
+    route: &SseRoute,
+    connection_id: Uuid,
+    request_data: serde_json::Map<String, serde_json::Value>,
+    tx: mpsc::Sender<Bytes>,
+) {
+    let handler = route.handler.clone();
+    let replay = route.replay.clone();
+    let request_json = serde_json::to_string(&request_data).unwrap();
+
+    let _join_handle = channel.send(move |mut cx| {
+        let sender = Arc::new(Mutex::new(Some(tx)));
+
+        // send(eventJson) -> false once the client is gone or too slow; send(null) ends the stream
+        let send = JsFunction::new(&mut cx, move |mut cx| {
+            let event_json = cx
+                .argument_opt(0)
+                .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+                .map(|json| json.value(&mut cx));
+
+            let Some(event_json) = event_json else {
+                sender.lock().unwrap().take();
+                return Ok(cx.boolean(false));
+            };
+
+            let event: SseEvent = match serde_json::from_str(&event_json) {
+                Ok(event) => event,
+                Err(e) => return cx.throw_type_error(format!("Invalid SSE event: {}", e)),
+            };
+            let frame = event.encode();
+            if let Some(id) = event.id {
+                replay.lock().unwrap().push(id, frame.clone());
+            }
+
+            let mut sender = sender.lock().unwrap();
+            let sent = match sender.as_ref().map(|tx| tx.try_send(frame)) {
+                Some(Ok(())) => true,
+                Some(Err(mpsc::error::TrySendError::Full(_))) => {
+                    warn!("⚠️ SSE client {} is too slow, event dropped", connection_id);
+                    false
+                }
+                Some(Err(mpsc::error::TrySendError::Closed(_))) => {
+                    sender.take();
+                    false
+                }
+                None => false,
+            };
+            Ok(cx.boolean(sent))
+        })?;
+
+        let handler = handler.to_inner(&mut cx);
+        let global: Handle<JsObject> = cx.global("global")?;
+        let execute_sse_fn: Handle<JsFunction> = global.get(&mut cx, "executeSse")?;
+        execute_sse_fn
+            .call_with(&cx)
+            .arg(handler)
+            .arg(cx.string(&request_json))
+            .arg(cx.string(connection_id.to_string()))
+            .arg(send)
+            .exec(&mut cx)
+    });
+}
@@ -0,0 +1,175 @@
+use crate::app::app_from_arg;
+use crate::types::{HandlerId, next_handler_id};
+use axum::body::Bytes;
+use log::info;
+use neon::prelude::*;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub mod handler;
+
+// Defaults for app.sse() options
+const DEFAULT_KEEP_ALIVE_MS: u64 = 15000;
+const DEFAULT_REPLAY_BUFFER: usize = 100;
+
+/// Options passed to app.sse()
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SseOptions {
+    keep_alive: Option<u64>,
+    replay_buffer: Option<usize>,
+}
+
+/// Event pushed by JS through `stream.send()`
+#[derive(Debug, Default, Deserialize)]
+pub struct SseEvent {
+    pub id: Option<String>,
+    pub event: Option<String>,
+    pub retry: Option<u64>,
+    #[serde(default)]
+    pub data: String,
+}
+
+impl SseEvent {
+    /// Encode as an event-stream frame
+    pub fn encode(&self) -> Bytes {
+        let mut frame = String::new();
+        // id and event are single-line fields
+        if let Some(id) = &self.id {
+            frame.push_str(&format!("id: {}\n", strip_newlines(id)));
+        }
+        if let Some(event) = &self.event {
+            frame.push_str(&format!("event: {}\n", strip_newlines(event)));
+        }
+        if let Some(retry) = self.retry {
+            frame.push_str(&format!("retry: {}\n", retry));
+        }
+        for line in self.data.split('\n') {
+            frame.push_str(&format!("data: {}\n", line.trim_end_matches('\r')));
+        }
+        frame.push('\n');
+        Bytes::from(frame)
+    }
+}
+
+fn strip_newlines(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+/// Recent events of a route kept for `Last-Event-ID` resumption
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    events: VecDeque<(String, Bytes)>,
+}
+
+impl ReplayBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, events: VecDeque::with_capacity(capacity) }
+    }
+
+    /// Remember an event sent with an id, dropping the oldest when full
+    pub fn push(&mut self, id: String, frame: Bytes) {
+        if self.capacity == 0 {
+            return;
+        }
+        // The same event broadcast to several clients is stored once
+        if self.events.iter().any(|(existing, _)| *existing == id) {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back((id, frame));
+    }
+
+    /// Events sent after `last_id`, or nothing if it has left the buffer
+    pub fn since(&self, last_id: &str) -> Vec<Bytes> {
+        match self.events.iter().position(|(id, _)| id == last_id) {
+            Some(index) => self.events.iter().skip(index + 1).map(|(_, frame)| frame.clone()).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Registered SSE route
+#[derive(Clone)]
+pub struct SseRoute {
+    pub path: String,
+    pub handler_id: HandlerId,
+    pub handler: Arc<Root<JsFunction>>,
+    pub keep_alive: Duration,
+    pub replay: Arc<Mutex<ReplayBuffer>>,
+}
+
+// Register an SSE route: registerSse(path, handler, optionsJson, appId)
+pub fn register_sse(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let handler = cx.argument::<JsFunction>(1)?.root(&mut cx);
+    let options = cx
+        .argument_opt(2)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .and_then(|json| serde_json::from_str::<SseOptions>(&json.value(&mut cx)).ok())
+        .unwrap_or_default();
+    let app = app_from_arg(&mut cx, 3)?;
+
+    let entry = crate::route_table::RouteEntry::new(crate::route_table::RouteSource::Sse, &path, Some("GET"));
+    if let Err(conflict) = crate::route_table::check_route(&app, entry) {
+        return cx.throw_error(conflict);
+    }
+
+    let handler_id = next_handler_id();
+    let route = SseRoute {
+        path: path.clone(),
+        handler_id,
+        handler: Arc::new(handler),
+        keep_alive: Duration::from_millis(options.keep_alive.unwrap_or(DEFAULT_KEEP_ALIVE_MS).max(1)),
+        replay: Arc::new(Mutex::new(ReplayBuffer::new(
+            options.replay_buffer.unwrap_or(DEFAULT_REPLAY_BUFFER),
+        ))),
+    };
+
+    app.sse_routes.write().unwrap().insert(path.clone(), route);
+    info!("📡 SSE route registered: {} (handler {})", path, handler_id);
+
+    // Apply to the running server
+    crate::route_table::rebuild(&app);
+
+    Ok(cx.undefined())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_event() {
+        let event = SseEvent {
+            id: Some("7".to_string()),
+            event: Some("tick\nx".to_string()),
+            retry: Some(3000),
+            data: "line 1\nline 2".to_string(),
+        };
+        assert_eq!(
+            event.encode(),
+            Bytes::from("id: 7\nevent: tickx\nretry: 3000\ndata: line 1\ndata: line 2\n\n")
+        );
+        assert_eq!(SseEvent::default().encode(), Bytes::from("data: \n\n"));
+    }
+
+    #[test]
+    fn test_replay_buffer() {
+        let mut buffer = ReplayBuffer::new(2);
+        buffer.push("1".to_string(), Bytes::from("a"));
+        buffer.push("2".to_string(), Bytes::from("b"));
+        buffer.push("2".to_string(), Bytes::from("b"));
+        buffer.push("3".to_string(), Bytes::from("c"));
+
+        assert_eq!(buffer.since("2"), vec![Bytes::from("c")]);
+        assert!(buffer.since("3").is_empty());
+        // Evicted id: nothing to resume from
+        assert!(buffer.since("1").is_empty());
+    }
+}
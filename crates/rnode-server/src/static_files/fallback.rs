@@ -1,28 +1,42 @@
+use crate::app::App;
 use crate::request::Request;
+use crate::server::{BodyReadError, payload_too_large, read_body_limited};
 use crate::handlers::middleware;
 use axum::body::Body;
 use axum::response::Response;
 use http;
 use log::debug;
+use std::sync::Arc;
 
 // Function for handling static file fallback
 pub async fn handle_static_fallback(
+    app: Arc<App>,
     req: http::Request<axum::body::Body>,
     timeout: u64,
+    max_body_size: usize,
 ) -> Response<Body> {
     let path = req.uri().path().to_string();
 
     // Разделяем запрос на части
     let (parts, body) = req.into_parts();
 
-    // Extract request body
-    let body_bytes = axum::body::to_bytes(body, usize::MAX).await.ok();
+    // Extract request body under the app's limit
+    let declared = parts.headers.get(http::header::CONTENT_LENGTH).and_then(|h| h.to_str().ok()?.parse::<usize>().ok());
+    if declared.is_some_and(|length| length > max_body_size) {
+        return payload_too_large(max_body_size);
+    }
+    let body_bytes = match read_body_limited(body, max_body_size).await {
+        Ok(body_bytes) => Some(body_bytes),
+        Err(BodyReadError::TooLarge) => return payload_too_large(max_body_size),
+        Err(BodyReadError::Read(_)) => None,
+    };
 
     // Create new request from parts
     let req = http::Request::from_parts(parts, Body::empty());
 
     // Get ready Request and Response objects from extensions
     let mut request = Request::from_axum_request(&req);
+    request.app_id = app.id;
     if let Some(body_bytes) = body_bytes {
         use crate::request_parser::RequestParser;
         let (parsed_body, files) = RequestParser::parse_request_body(&body_bytes, &request.content_type).await;
@@ -35,11 +49,11 @@ pub async fn handle_static_fallback(
 
     // First try to find static file
     let accept_encoding = req.headers().get("accept-encoding").and_then(|h| h.to_str().ok());
-    if let Some(mut static_response) = super::handle_static_file(path, accept_encoding).await {
+    if let Some(mut static_response) = super::handle_static_file(&app, path, accept_encoding).await {
         // Применяем middleware к статическому файлу с оставшимся временем
         let mut remaining_timeout = timeout;
         debug!("⏱️ Static file middleware - Initial timeout: {}ms", remaining_timeout);
-        if let Err(_) = middleware::execute_middleware(&mut request, &mut remaining_timeout, false).await {
+        if let Err(_) = middleware::execute_middleware(&app, &mut request, &mut remaining_timeout, false).await {
             // Если middleware вернул ошибку, возвращаем 500
             return axum::response::Response::builder()
                 .status(http::StatusCode::INTERNAL_SERVER_ERROR)
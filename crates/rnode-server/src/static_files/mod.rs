@@ -1,6 +1,5 @@
 pub mod types;
 pub mod options;
-pub mod cache;
 pub mod security;
 pub mod handlers;
 pub mod fallback;
@@ -10,27 +9,19 @@ use log::{debug, error, info, warn};
 use neon::prelude::*;
 
 use std::path::PathBuf;
-use std::sync::{OnceLock, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use self::types::{StaticFile, StaticFileHeaders, StaticFolder, StaticOptions};
-use self::cache::get_static_files_cache;
+use crate::app::{App, app_from_arg};
 use self::options::parse_static_options;
 use self::security::is_file_safe;
 use crate::compression::{compress_gzip, compress_brotli, compress_zstd, compress_lz4};
 use self::handlers::build_static_response;
 
-// Global storage for multiple folders
-static STATIC_FOLDERS: OnceLock<RwLock<Vec<StaticFolder>>> = OnceLock::new();
-
-// Function for getting static folders
-fn get_static_folders() -> &'static RwLock<Vec<StaticFolder>> {
-    STATIC_FOLDERS.get_or_init(|| RwLock::new(Vec::new()))
-}
-
 // Function for loading static files settings (without loading the files themselves)
 pub fn load_static_files(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let static_path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let app = app_from_arg(&mut cx, 2)?;
 
     // Convert relative path to absolute from current working directory
     let absolute_path = if static_path.starts_with("./")
@@ -84,10 +75,9 @@ pub fn load_static_files(mut cx: FunctionContext) -> JsResult<JsUndefined> {
         static_path, absolute_path_str, options
     );
 
-    // Add folder to list synchronously
+    // Add folder to the app's list synchronously
     {
-        let folders = get_static_folders();
-        let mut folders_write = folders.write().unwrap();
+        let mut folders_write = app.static_folders.write().unwrap();
         folders_write.push(StaticFolder {
             path: absolute_path_str.to_string(),
             options,
@@ -111,9 +101,8 @@ pub fn get_mime_type(path: &std::path::Path) -> String {
 }
 
 // Function for getting file from cache
-fn get_file_from_cache(path: &str) -> Option<StaticFile> {
-    let cache = get_static_files_cache();
-    let cache_read = cache.read().unwrap();
+fn get_file_from_cache(app: &App, path: &str) -> Option<StaticFile> {
+    let cache_read = app.static_cache.read().unwrap();
     let result = cache_read.get(path).cloned();
 
     // Record cache metrics
@@ -292,6 +281,7 @@ fn process_file_for_response(
 
 // Function for loading file to cache
 async fn load_file_to_cache(
+    app: &App,
     file_path: &str,
     folder_options: &StaticOptions,
     original_path: &str,
@@ -302,9 +292,8 @@ async fn load_file_to_cache(
     );
 
     // Search for file in registered folders
-    let folders = get_static_folders();
     let folders_clone = {
-        let folders_read = folders.read().unwrap();
+        let folders_read = app.static_folders.read().unwrap();
         folders_read.clone()
     };
 
@@ -436,8 +425,7 @@ async fn load_file_to_cache(
     process_file_for_response(&mut static_file, folder_options, file_path);
 
     // Add to cache using original path, not search_path
-    let cache = get_static_files_cache();
-    let mut cache_write = cache.write().unwrap();
+    let mut cache_write = app.static_cache.write().unwrap();
 
     // Use original path for caching
     cache_write.insert(original_path.to_string(), static_file);
@@ -459,9 +447,8 @@ async fn load_file_to_cache(
 }
 
 // Function for finding suitable folder by file path
-fn find_folder_for_path(file_path: &str) -> Option<StaticOptions> {
-    let folders = get_static_folders();
-    let folders_read = folders.read().unwrap();
+fn find_folder_for_path(app: &App, file_path: &str) -> Option<StaticOptions> {
+    let folders_read = app.static_folders.read().unwrap();
 
     debug!(
         "🔍 Searching for folder configuration for path: {} ({} folders registered)",
@@ -522,6 +509,7 @@ fn find_folder_for_path(file_path: &str) -> Option<StaticOptions> {
 
 // Function for handling static files
 pub async fn handle_static_file(
+    app: &App,
     path: String,
     accept_encoding: Option<&str>,
 ) -> Option<axum::response::Response<axum::body::Body>> {
@@ -550,8 +538,7 @@ pub async fn handle_static_file(
     debug!("🔍 Search path: {} (original: {})", search_path, path);
 
     // First check cache
-    let cache = get_static_files_cache();
-    let cached_file = cache.read().unwrap().get(&path).cloned();
+    let cached_file = app.static_cache.read().unwrap().get(&path).cloned();
 
     if let Some(static_file) = cached_file {
         debug!(
@@ -567,15 +554,15 @@ pub async fn handle_static_file(
     debug!("💾 File not in cache, searching for folder configuration...");
 
     // If file not in cache, search for suitable folder and load
-    if let Some(folder_options) = find_folder_for_path(&search_path) {
+    if let Some(folder_options) = find_folder_for_path(app, &search_path) {
         debug!("📂 Found folder configuration for path: {}", search_path);
 
         // Load file to cache, passing original path for correct caching
-        if load_file_to_cache(&search_path, &folder_options, &path).await {
+        if load_file_to_cache(app, &search_path, &folder_options, &path).await {
             info!("📥 File loaded to cache: {}", search_path);
 
             // Now get processed file from cache
-            if let Some(static_file) = get_file_from_cache(&path) {
+            if let Some(static_file) = get_file_from_cache(app, &path) {
                 return build_static_response(&static_file, accept_encoding, &path);
             }
         } else {
@@ -594,8 +581,8 @@ pub async fn handle_static_file(
 
 // Function for clearing static files cache
 pub fn clear_static_cache(mut cx: FunctionContext) -> JsResult<JsUndefined> {
-    let cache = get_static_files_cache();
-    let mut cache_write = cache.write().unwrap();
+    let app = app_from_arg(&mut cx, 0)?;
+    let mut cache_write = app.static_cache.write().unwrap();
     let cache_size = cache_write.len();
     cache_write.clear();
     info!(
@@ -607,11 +594,10 @@ pub fn clear_static_cache(mut cx: FunctionContext) -> JsResult<JsUndefined> {
 
 // Function for getting static files statistics
 pub fn get_static_stats(mut cx: FunctionContext) -> JsResult<JsString> {
-    let cache = get_static_files_cache();
-    let folders = get_static_folders();
+    let app = app_from_arg(&mut cx, 0)?;
 
-    let cache_read = cache.read().unwrap();
-    let folders_read = folders.read().unwrap();
+    let cache_read = app.static_cache.read().unwrap();
+    let folders_read = app.static_folders.read().unwrap();
 
     let total_files = cache_read.len();
     let total_size: usize = cache_read.values().map(|f| f.size).sum();
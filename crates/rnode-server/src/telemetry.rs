@@ -0,0 +1,332 @@
+//! Distributed tracing: W3C trace context and an OTLP/HTTP (JSON) span exporter
+
+use log::{debug, info, warn};
+use neon::prelude::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+const DEFAULT_SERVICE_NAME: &str = "rnode-server";
+const DEFAULT_BATCH_SIZE: usize = 512;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 1000;
+const QUEUE_SIZE: usize = 4096;
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+/// `tracing` option of listen()
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TracingOptions {
+    pub endpoint: Option<String>, // OTLP/HTTP collector, e.g. http://localhost:4318
+    pub service_name: Option<String>,
+    pub sample_ratio: Option<f64>, // Share of new traces recorded; incoming ones follow the caller
+    pub headers: HashMap<String, String>, // Sent with every export, e.g. authentication
+    pub batch_size: Option<usize>,
+    pub flush_interval_ms: Option<u64>,
+}
+
+/// Position of a span in a trace, as carried by the `traceparent` header
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub sampled: bool,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    if value.len() != N * 2 {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(value.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for chunk in bytes.chunks_mut(16) {
+        chunk.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..chunk.len()]);
+    }
+    bytes
+}
+
+impl TraceContext {
+    /// Parse a W3C `traceparent` header: version-traceid-spanid-flags
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = unhex::<16>(parts.next()?)?;
+        let span_id = unhex::<8>(parts.next()?)?;
+        let flags = unhex::<1>(parts.next()?)?[0];
+        // Version ff is invalid; later versions may append fields
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == [0; 16] || span_id == [0; 8] {
+            return None;
+        }
+        Some(Self { trace_id, span_id, sampled: flags & 1 == 1 })
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", hex(&self.trace_id), hex(&self.span_id), self.sampled as u8)
+    }
+}
+
+/// OTLP span kind
+#[derive(Debug, Clone, Copy)]
+pub enum SpanKind {
+    Internal = 1,
+    Server = 2,
+    Client = 3,
+}
+
+/// A timed operation; exported when dropped if its trace is sampled
+#[derive(Debug)]
+pub struct Span {
+    context: TraceContext,
+    parent_span_id: Option<[u8; 8]>,
+    name: String,
+    kind: SpanKind,
+    start: SystemTime,
+    attributes: Vec<(String, Value)>,
+    error: Option<String>,
+}
+
+impl Span {
+    /// Start a span under `parent`, or a new trace; None while tracing is not configured
+    pub fn start(name: impl Into<String>, kind: SpanKind, parent: Option<&TraceContext>) -> Option<Self> {
+        let tracer = TRACER.get()?;
+        let (trace_id, sampled, parent_span_id) = match parent {
+            Some(parent) => (parent.trace_id, parent.sampled, Some(parent.span_id)),
+            None => (random_bytes::<16>(), tracer.sample(), None),
+        };
+        Some(Self {
+            context: TraceContext { trace_id, span_id: random_bytes::<8>(), sampled },
+            parent_span_id,
+            name: name.into(),
+            kind,
+            start: SystemTime::now(),
+            attributes: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Start a span under the context of a `traceparent` value
+    pub fn child_of(traceparent: Option<&str>, name: impl Into<String>, kind: SpanKind) -> Option<Self> {
+        let parent = traceparent.and_then(TraceContext::parse);
+        Self::start(name, kind, parent.as_ref())
+    }
+
+    /// Span for native work JS does inside a traced request; the `traceparent` is argument `index`
+    pub fn from_arg(cx: &mut FunctionContext, index: usize, name: impl Into<String>, kind: SpanKind) -> Option<Self> {
+        let traceparent = cx
+            .argument_opt(index)
+            .and_then(|arg| arg.downcast::<JsString, _>(cx).ok())
+            .map(|value| value.value(cx))?;
+        Self::child_of(Some(&traceparent), name, kind)
+    }
+
+    pub fn context(&self) -> &TraceContext {
+        &self.context
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: impl Into<Value>) {
+        self.attributes.push((key.to_string(), value.into()));
+    }
+
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.error = Some(message.into());
+    }
+
+    fn to_otlp(&self, end: SystemTime) -> Value {
+        let nanos = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos()).unwrap_or_default().to_string()
+        };
+        let mut span = json!({
+            "traceId": hex(&self.context.trace_id),
+            "spanId": hex(&self.context.span_id),
+            "name": self.name,
+            "kind": self.kind as u8,
+            "startTimeUnixNano": nanos(self.start),
+            "endTimeUnixNano": nanos(end),
+            "attributes": self
+                .attributes
+                .iter()
+                .map(|(key, value)| json!({ "key": key, "value": any_value(value) }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(parent) = self.parent_span_id {
+            span["parentSpanId"] = json!(hex(&parent));
+        }
+        if let Some(message) = &self.error {
+            span["status"] = json!({ "code": 2, "message": message });
+        }
+        span
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if !self.context.sampled {
+            return;
+        }
+        if let Some(tracer) = TRACER.get() {
+            tracer.export(self.to_otlp(SystemTime::now()));
+        }
+    }
+}
+
+// OTLP AnyValue of an attribute
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::Bool(value) => json!({ "boolValue": value }),
+        Value::Number(number) if number.is_i64() || number.is_u64() => json!({ "intValue": number.to_string() }),
+        Value::Number(number) => json!({ "doubleValue": number.as_f64() }),
+        Value::String(value) => json!({ "stringValue": value }),
+        other => json!({ "stringValue": other.to_string() }),
+    }
+}
+
+/// Process-wide span queue feeding the exporter thread
+#[derive(Debug)]
+struct Tracer {
+    sender: SyncSender<Value>,
+    sample_ratio: f64,
+}
+
+impl Tracer {
+    fn sample(&self) -> bool {
+        if self.sample_ratio >= 1.0 {
+            return true;
+        }
+        let random = u64::from_le_bytes(random_bytes::<8>());
+        (random as f64 / u64::MAX as f64) < self.sample_ratio
+    }
+
+    fn export(&self, span: Value) {
+        match self.sender.try_send(span) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => debug!("⚠️ Span queue full, dropping span"),
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+// Send a batch of spans to the collector
+async fn send_batch(client: &reqwest::Client, url: &str, options: &TracingOptions, resource: &Value, spans: Vec<Value>) {
+    let count = spans.len();
+    let body = json!({
+        "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{
+                "scope": { "name": "rnode-server", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }]
+        }]
+    });
+
+    let mut request = client.post(url).header("content-type", "application/json");
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    match request.body(body.to_string()).send().await {
+        Ok(response) if response.status().is_success() => debug!("📡 Exported {} spans", count),
+        Ok(response) => warn!("⚠️ Span export rejected by {}: {}", url, response.status()),
+        Err(e) => warn!("⚠️ Span export to {} failed: {}", url, e),
+    }
+}
+
+/// Start the exporter; the first configuration in the process wins
+pub fn init_tracing(options: &TracingOptions) {
+    if TRACER.get().is_some() {
+        debug!("📡 Tracing already initialized");
+        return;
+    }
+
+    let endpoint = options.endpoint.as_deref().unwrap_or(DEFAULT_ENDPOINT).trim_end_matches('/');
+    let url = if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    };
+    let resource = json!({
+        "attributes": [{
+            "key": "service.name",
+            "value": { "stringValue": options.service_name.as_deref().unwrap_or(DEFAULT_SERVICE_NAME) }
+        }]
+    });
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let flush_interval = Duration::from_millis(options.flush_interval_ms.unwrap_or(DEFAULT_FLUSH_INTERVAL_MS).max(10));
+
+    let (sender, receiver) = mpsc::sync_channel::<Value>(QUEUE_SIZE);
+    if TRACER
+        .set(Tracer { sender, sample_ratio: options.sample_ratio.unwrap_or(1.0).clamp(0.0, 1.0) })
+        .is_err()
+    {
+        return;
+    }
+    info!("📡 Tracing enabled, exporting spans to {}", url);
+
+    let options = options.clone();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        let mut batch = Vec::new();
+        let mut last_flush = Instant::now();
+        loop {
+            let wait = flush_interval.saturating_sub(last_flush.elapsed());
+            let disconnected = match receiver.recv_timeout(wait) {
+                Ok(span) => {
+                    batch.push(span);
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+
+            if batch.len() >= batch_size || last_flush.elapsed() >= flush_interval || disconnected {
+                if !batch.is_empty() {
+                    rt.block_on(send_batch(&client, &url, &options, &resource, std::mem::take(&mut batch)));
+                }
+                last_flush = Instant::now();
+            }
+            if disconnected {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traceparent_round_trip() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::parse(header).unwrap();
+        assert!(context.sampled);
+        assert_eq!(context.traceparent(), header);
+
+        assert!(!TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap().sampled);
+        assert!(TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+        assert!(TraceContext::parse("garbage").is_none());
+    }
+}
@@ -102,6 +102,14 @@ impl TemplateContext {
         self
     }
 
+    /// Expose the request's CSP nonce to the template as `cspNonce`
+    pub fn with_csp_nonce(self, nonce: Option<&str>) -> Self {
+        match nonce {
+            Some(nonce) => self.with_string("cspNonce", nonce),
+            None => self,
+        }
+    }
+
     /// Build the final context HashMap
     pub fn build(self) -> HashMap<String, JsonValue> {
         self.data
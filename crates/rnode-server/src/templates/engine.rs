@@ -2,13 +2,11 @@ use log::{debug, error, info, warn};
 use serde_json::{Map, Value as JsonValue};
 use std::collections::HashMap;
 use std::error::Error as StdError;
-use std::sync::{Mutex, OnceLock};
 use tera::{Context, Tera};
 
-// Global Tera instance for template management
-static TEMPLATES: OnceLock<Mutex<Option<Tera>>> = OnceLock::new();
+use crate::app::App;
 
-pub fn init_templates(pattern: &str, autoescape: bool) -> Result<(), String> {
+pub fn init_templates(app: &App, pattern: &str, autoescape: bool) -> Result<(), String> {
     let mut tera = Tera::new(pattern).map_err(|e| format!("Failed to parse templates: {}", e))?;
 
     // Configure autoescape
@@ -21,8 +19,7 @@ pub fn init_templates(pattern: &str, autoescape: bool) -> Result<(), String> {
     // Get list of loaded templates before moving tera
     let template_names: Vec<String> = tera.get_template_names().map(|s| s.to_string()).collect();
 
-    let templates = TEMPLATES.get_or_init(|| Mutex::new(None));
-    let mut templates = templates.lock().unwrap();
+    let mut templates = app.templates.lock().unwrap();
     *templates = Some(tera);
 
     info!(
@@ -48,11 +45,11 @@ pub fn init_templates(pattern: &str, autoescape: bool) -> Result<(), String> {
 }
 
 pub fn render_template(
+    app: &App,
     template_name: &str,
     context: HashMap<String, JsonValue>,
 ) -> Result<String, String> {
-    let templates = TEMPLATES.get_or_init(|| Mutex::new(None));
-    let templates = templates.lock().unwrap();
+    let templates = app.templates.lock().unwrap();
 
     if let Some(ref tera) = *templates {
         // Convert HashMap to serde_json::Map
@@ -4,10 +4,12 @@ use neon::prelude::*;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 
+use super::context::TemplateContext;
 use super::engine;
+use crate::telemetry::{Span, SpanKind};
 
 /// Initialize templates with configuration
-/// Usage: initTemplates(pattern, options)
+/// Usage: initTemplates(pattern, options, appId?)
 /// options: { autoescape: boolean }
 /// 
 /// # Arguments
@@ -29,6 +31,7 @@ use super::engine;
 pub fn init_templates_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
     let pattern = cx.argument::<JsString>(0)?.value(&mut cx);
     let options = cx.argument::<JsObject>(1)?;
+    let app = crate::app::app_from_arg(&mut cx, 2)?;
 
     // Extract options with proper error handling
     let autoescape = match options.get::<JsValue, _, _>(&mut cx, "autoescape") {
@@ -42,7 +45,7 @@ pub fn init_templates_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
         Err(_) => true, // default value
     };
 
-    match engine::init_templates(&pattern, autoescape) {
+    match engine::init_templates(&app, &pattern, autoescape) {
         Ok(_) => {
             let response = serde_json::json!({
                 "success": true,
@@ -61,7 +64,8 @@ pub fn init_templates_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
 }
 
 /// Render a template
-/// Usage: renderTemplate(templateName, context)
+/// Usage: renderTemplate(templateName, context, appId?, cspNonce?)
+/// `cspNonce` is available in the template as `{{ cspNonce }}`
 /// 
 /// # Arguments
 /// * `cx` - Neon function context
@@ -83,6 +87,15 @@ pub fn init_templates_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
 pub fn render_template_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
     let template_name = cx.argument::<JsString>(0)?.value(&mut cx);
     let context_str = cx.argument::<JsString>(1)?.value(&mut cx);
+    let app = crate::app::app_from_arg(&mut cx, 2)?;
+    let nonce = cx
+        .argument_opt(3)
+        .and_then(|arg| arg.downcast::<JsString, _>(&mut cx).ok())
+        .map(|nonce| nonce.value(&mut cx));
+    let mut span = Span::from_arg(&mut cx, 4, "template render", SpanKind::Internal);
+    if let Some(span) = span.as_mut() {
+        span.set_attribute("rnode.template", template_name.clone());
+    }
 
     // Parse JSON string to HashMap
     let context: HashMap<String, JsonValue> = match serde_json::from_str(&context_str) {
@@ -96,7 +109,9 @@ pub fn render_template_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
         }
     };
 
-    match engine::render_template(&template_name, context) {
+    let context = TemplateContext::from(context).with_csp_nonce(nonce.as_deref()).build();
+
+    match engine::render_template(&app, &template_name, context) {
         Ok(result) => {
             let response = serde_json::json!({
                 "success": true,
@@ -106,6 +121,9 @@ pub fn render_template_wrapper(mut cx: FunctionContext) -> JsResult<JsString> {
         }
         Err(e) => {
             debug!("🔍 DEBUG: render_template returned error: {}", e);
+            if let Some(span) = span.as_mut() {
+                span.set_error(e.clone());
+            }
             let response = serde_json::json!({
                 "success": false,
                 "error": e
@@ -0,0 +1,274 @@
+use crate::app::app_from_arg;
+use arc_swap::ArcSwap;
+use log::{debug, info, warn};
+use neon::prelude::*;
+use rustls::ServerConfig;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::RootCertStore;
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Marks requests that arrived over a TLS listener
+#[derive(Debug, Clone, Copy)]
+pub struct TlsConnection;
+
+/// `ssl` option of listen()
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TlsOptions {
+    pub cert_path: Option<String>, // Default certificate, served when no SNI entry matches
+    pub key_path: Option<String>,
+    pub certificates: Vec<SniCertificate>,
+    pub watch: bool, // Reload when a certificate or key file changes
+    pub strict: bool, // Fail listen() instead of falling back to plain HTTP
+    pub client_ca_path: Option<String>, // PEM bundle of CAs client certificates must chain to
+    pub client_auth: ClientAuth,
+}
+
+/// Whether clients must present a certificate when a client CA is configured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientAuth {
+    #[default]
+    Required,
+    Optional, // Connections without a certificate are accepted; presented ones are still verified
+}
+
+/// Certificate served for the listed SNI names, e.g. "example.com" or "*.example.com"
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SniCertificate {
+    pub server_names: Vec<String>,
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Certificate chain and private key from PEM files
+fn load_pem(cert_path: &str, key_path: &str) -> io::Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let mut cert_reader = BufReader::new(std::fs::File::open(cert_path)?);
+    let certs = rustls_pemfile::certs(&mut cert_reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(invalid(format!("no certificate found in {}", cert_path)));
+    }
+
+    let mut key_reader = BufReader::new(std::fs::File::open(key_path)?);
+    let key = rustls_pemfile::private_key(&mut key_reader)?
+        .ok_or_else(|| invalid(format!("no private key found in {}", key_path)))?;
+    Ok((certs, key))
+}
+
+fn load_certified_key(cert_path: &str, key_path: &str, provider: &CryptoProvider) -> io::Result<Arc<CertifiedKey>> {
+    let (certs, key) = load_pem(cert_path, key_path)?;
+    let certified = CertifiedKey::from_der(certs, key, provider)
+        .map_err(|e| invalid(format!("{} / {}: {}", cert_path, key_path, e)))?;
+    Ok(Arc::new(certified))
+}
+
+/// Loaded certificates: a default one and SNI names mapped to theirs
+#[derive(Debug)]
+struct Certificates {
+    default: Arc<CertifiedKey>,
+    by_name: HashMap<String, Arc<CertifiedKey>>,
+}
+
+impl Certificates {
+    fn load(options: &TlsOptions, provider: &CryptoProvider) -> io::Result<Self> {
+        let mut by_name = HashMap::new();
+        let mut first = None;
+        for entry in &options.certificates {
+            let key = load_certified_key(&entry.cert_path, &entry.key_path, provider)?;
+            for name in &entry.server_names {
+                by_name.insert(name.trim_end_matches('.').to_ascii_lowercase(), key.clone());
+            }
+            first.get_or_insert(key);
+        }
+
+        let default = match (options.cert_path.as_deref(), options.key_path.as_deref()) {
+            (Some(cert_path), Some(key_path)) if !cert_path.is_empty() && !key_path.is_empty() => {
+                load_certified_key(cert_path, key_path, provider)?
+            }
+            // Without an explicit default the first SNI certificate is served
+            _ => first.ok_or_else(|| invalid("certPath and keyPath or certificates are required".to_string()))?,
+        };
+        Ok(Self { default, by_name })
+    }
+
+    fn lookup(&self, server_name: Option<&str>) -> Arc<CertifiedKey> {
+        server_name
+            .and_then(|name| match_server_name(&self.by_name, name))
+            .unwrap_or(&self.default)
+            .clone()
+    }
+}
+
+// Exact name first, then a wildcard for its parent domain
+fn match_server_name<'a, T>(by_name: &'a HashMap<String, T>, server_name: &str) -> Option<&'a T> {
+    let name = server_name.trim_end_matches('.').to_ascii_lowercase();
+    by_name.get(&name).or_else(|| {
+        let (_, parent) = name.split_once('.')?;
+        by_name.get(&format!("*.{}", parent))
+    })
+}
+
+/// Certificates of a TLS listener; swapped in place on reload so open connections are kept
+#[derive(Debug)]
+pub struct CertificateStore {
+    options: TlsOptions,
+    provider: Arc<CryptoProvider>,
+    certificates: ArcSwap<Certificates>,
+    // Modification times seen on the last load, for the file watcher
+    modified: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl CertificateStore {
+    pub fn load(options: TlsOptions) -> io::Result<Self> {
+        let provider = ServerConfig::builder().crypto_provider().clone();
+        let modified = Self::modified_times(&options);
+        let certificates = Certificates::load(&options, &provider)?;
+        Ok(Self {
+            options,
+            provider,
+            certificates: ArcSwap::from_pointee(certificates),
+            modified: Mutex::new(modified),
+        })
+    }
+
+    pub fn options(&self) -> &TlsOptions {
+        &self.options
+    }
+
+    /// Re-read every certificate from disk; on error the loaded ones stay in use
+    pub fn reload(&self) -> io::Result<()> {
+        let modified = Self::modified_times(&self.options);
+        let certificates = Certificates::load(&self.options, &self.provider)?;
+        self.certificates.store(Arc::new(certificates));
+        *self.modified.lock().unwrap() = modified;
+        info!("🔒 TLS certificates reloaded");
+        Ok(())
+    }
+
+    fn modified_times(options: &TlsOptions) -> Vec<Option<SystemTime>> {
+        let defaults = [options.cert_path.as_deref(), options.key_path.as_deref()];
+        let entries = options
+            .certificates
+            .iter()
+            .flat_map(|entry| [Some(entry.cert_path.as_str()), Some(entry.key_path.as_str())]);
+        defaults
+            .into_iter()
+            .chain(entries)
+            .map(|path| path.and_then(|path| std::fs::metadata(path).and_then(|meta| meta.modified()).ok()))
+            .collect()
+    }
+
+    /// Poll the certificate files and reload when one changes
+    pub async fn watch(self: Arc<Self>, token: CancellationToken) {
+        debug!("👀 Watching TLS certificate files for changes");
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = token.cancelled() => return,
+            }
+            let modified = Self::modified_times(&self.options);
+            if modified == *self.modified.lock().unwrap() {
+                continue;
+            }
+            // A half-written pair fails here and loads once the other file changes too
+            if let Err(e) = self.reload() {
+                warn!("⚠️ Failed to reload TLS certificates, keeping the current ones: {}", e);
+                *self.modified.lock().unwrap() = modified;
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for CertificateStore {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.certificates.load().lookup(client_hello.server_name()))
+    }
+}
+
+// Verifier accepting client certificates issued by the CAs in `ca_path`
+fn client_verifier(
+    ca_path: &str,
+    client_auth: ClientAuth,
+    provider: Arc<CryptoProvider>,
+) -> io::Result<Arc<dyn rustls::server::danger::ClientCertVerifier>> {
+    let mut reader = BufReader::new(std::fs::File::open(ca_path)?);
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut reader) {
+        roots.add(cert?).map_err(|e| invalid(format!("{}: {}", ca_path, e)))?;
+    }
+    if roots.is_empty() {
+        return Err(invalid(format!("no certificate found in {}", ca_path)));
+    }
+
+    let builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    let builder = match client_auth {
+        ClientAuth::Required => builder,
+        ClientAuth::Optional => builder.allow_unauthenticated(),
+    };
+    builder.build().map_err(|e| invalid(format!("{}: {}", ca_path, e)))
+}
+
+/// Server configuration picking certificates from `store` by SNI, offering `alpn` protocols in order
+pub fn server_config(store: Arc<CertificateStore>, alpn: Vec<Vec<u8>>) -> io::Result<ServerConfig> {
+    let builder = ServerConfig::builder();
+    let builder = match store.options.client_ca_path.as_deref() {
+        Some(ca_path) => {
+            info!("🔒 Verifying client certificates against {} ({:?})", ca_path, store.options.client_auth);
+            builder.with_client_cert_verifier(client_verifier(ca_path, store.options.client_auth, store.provider.clone())?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut config = builder.with_cert_resolver(store);
+    config.alpn_protocols = alpn;
+    Ok(config)
+}
+
+/// reloadTls(appId): re-read the app's certificates from disk without dropping connections
+pub fn reload_tls(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let instance = app_from_arg(&mut cx, 0)?;
+    let store = instance.tls.read().unwrap().clone();
+    let Some(store) = store else {
+        return cx.throw_error("TLS is not enabled for this app");
+    };
+    if let Err(e) = store.reload() {
+        return cx.throw_error(format!("Failed to reload TLS certificates: {}", e));
+    }
+    Ok(cx.undefined())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_server_name() {
+        let by_name: HashMap<String, &str> =
+            [("example.com", "apex"), ("*.example.com", "wildcard"), ("api.example.com", "api")]
+                .into_iter()
+                .map(|(name, cert)| (name.to_string(), cert))
+                .collect();
+
+        assert_eq!(match_server_name(&by_name, "example.com"), Some(&"apex"));
+        assert_eq!(match_server_name(&by_name, "API.example.com."), Some(&"api"));
+        assert_eq!(match_server_name(&by_name, "www.example.com"), Some(&"wildcard"));
+        // Wildcards cover a single label only
+        assert_eq!(match_server_name(&by_name, "a.b.example.com"), None);
+        assert_eq!(match_server_name(&by_name, "other.org"), None);
+    }
+}
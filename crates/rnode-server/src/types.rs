@@ -1,30 +1,62 @@
-use neon::event::Channel;
+use neon::handle::Root;
+use neon::types::JsFunction;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::OnceLock;
-use std::sync::RwLock;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Unique identifier of a registered handler
+pub type HandlerId = u64;
+
+static NEXT_HANDLER_ID: AtomicU64 = AtomicU64::new(1);
+
+// Allocate a handler ID that is never reused within the process
+pub fn next_handler_id() -> HandlerId {
+    NEXT_HANDLER_ID.fetch_add(1, Ordering::Relaxed)
+}
 
 // Structure for storing routes and their handlers
 #[derive(Clone)]
 pub struct RouteInfo {
     pub path: String,
     pub method: String,
-    #[allow(dead_code)]
-    pub handler_id: String, // Unique ID for handler
+    pub handler_id: HandlerId, // Unique ID for handler
+    pub handler: Arc<Root<JsFunction>>, // JS handler invoked for this route
+    pub options: RouteOptions,
+}
+
+// Per-route options passed at registration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RouteOptions {
+    pub max_body_size: Option<usize>, // Overrides the app's maxBodySize
+    pub stream_body: bool, // Pass the raw body to JS as a stream instead of parsing it
+    pub timeout: Option<u64>, // Overrides the app's timeout (ms) for middleware and handler
 }
 
 // Structure for storing middleware
 #[derive(Debug, Clone)]
 pub struct MiddlewareInfo {
+    pub id: HandlerId,
     pub path: String,
-    pub middleware_id: String,
+    pub methods: Option<Vec<String>>, // None runs for every method
+    pub error: bool, // Error middleware only runs when something failed
 }
 
-// Synchronous storage for routes
-pub static ROUTES: OnceLock<RwLock<HashMap<String, RouteInfo>>> = OnceLock::new();
+// Middleware options passed at registration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MiddlewareOptions {
+    pub methods: Option<Vec<String>>,
+    pub error: bool,
+}
 
-// Global middleware storage
-pub static MIDDLEWARE: OnceLock<RwLock<Vec<MiddlewareInfo>>> = OnceLock::new();
+// Option given as `true`/`false` or as an options object; `true` means the defaults
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Setting<T> {
+    Enabled(bool),
+    Value(T),
+}
 
 // Structure for file download settings
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -51,33 +83,3 @@ pub struct UploadRouteConfig {
     pub max_files: Option<u32>,
     pub overwrite: bool,
 }
-
-// Global channel for communication between HTTP thread and JavaScript thread
-pub static EVENT_QUEUE: OnceLock<RwLock<Option<Channel>>> = OnceLock::new();
-
-pub fn get_event_queue() -> &'static RwLock<Option<Channel>> {
-    EVENT_QUEUE.get_or_init(|| RwLock::new(None))
-}
-
-// Global storage for download settings
-pub static DOWNLOAD_ROUTES: OnceLock<RwLock<HashMap<String, DownloadRouteConfig>>> =
-    OnceLock::new();
-
-// Global storage for upload settings
-pub static UPLOAD_ROUTES: OnceLock<RwLock<HashMap<String, UploadRouteConfig>>> = OnceLock::new();
-
-pub fn get_routes() -> &'static RwLock<HashMap<String, RouteInfo>> {
-    ROUTES.get_or_init(|| RwLock::new(HashMap::new()))
-}
-
-pub fn get_middleware() -> &'static RwLock<Vec<MiddlewareInfo>> {
-    MIDDLEWARE.get_or_init(|| RwLock::new(Vec::new()))
-}
-
-pub fn get_download_routes() -> &'static RwLock<HashMap<String, DownloadRouteConfig>> {
-    DOWNLOAD_ROUTES.get_or_init(|| RwLock::new(HashMap::new()))
-}
-
-pub fn get_upload_routes() -> &'static RwLock<HashMap<String, UploadRouteConfig>> {
-    UPLOAD_ROUTES.get_or_init(|| RwLock::new(HashMap::new()))
-}
@@ -8,23 +8,10 @@ static INIT: Once = Once::new();
 
 /// Helper functions for extracting configuration options from JavaScript objects
 pub mod config_extractor {
+    use crate::listener::{ListenAddress, parse_listen_addresses};
     use log;
     use neon::prelude::*;
 
-    /// Extract a string value from an object with a default
-    pub fn get_string(
-        cx: &mut FunctionContext,
-        obj: &JsObject,
-        key: &str,
-        default: &str,
-    ) -> String {
-        obj.get::<JsValue, _, _>(cx, key)
-            .ok()
-            .and_then(|value| value.downcast::<JsString, _>(cx).ok())
-            .map(|js_str| js_str.value(cx))
-            .unwrap_or_else(|| default.to_string())
-    }
-
     /// Extract a boolean value from an object with a default
     pub fn get_bool(cx: &mut FunctionContext, obj: &JsObject, key: &str, default: bool) -> bool {
         obj.get::<JsValue, _, _>(cx, key)
@@ -48,36 +35,53 @@ pub mod config_extractor {
         get_number(cx, obj, key, default as f64) as u64
     }
 
-    /// Extract a nested object from an object
-    pub fn get_object<'a>(
-        cx: &mut FunctionContext<'a>,
-        obj: &JsObject,
-        key: &str,
-    ) -> Option<Handle<'a, JsObject>> {
-        obj.get::<JsValue, _, _>(cx, key)
+    /// Extract an array of strings from an object (non-string items are skipped)
+    pub fn get_string_array(cx: &mut FunctionContext, obj: &JsObject, key: &str) -> Vec<String> {
+        let Some(array) = obj
+            .get::<JsValue, _, _>(cx, key)
             .ok()
-            .and_then(|value| value.downcast::<JsObject, _>(cx).ok())
+            .and_then(|value| value.downcast::<JsArray, _>(cx).ok())
+        else {
+            return Vec::new();
+        };
+
+        let mut values = Vec::new();
+        for i in 0..array.len(cx) {
+            if let Ok(item) = array.get::<JsString, _, _>(cx, i) {
+                values.push(item.value(cx));
+            }
+        }
+        values
     }
 
-    /// Extract and parse IP address from string with fallback to localhost
-    pub fn parse_ip_address(host_str: &str) -> [u8; 4] {
-        let ip_parts: Result<Vec<u8>, _> =
-            host_str.split('.').map(|part| part.parse::<u8>()).collect();
+    /// Extract a nested value through JSON.stringify and deserialize it
+    pub fn get_json<T: serde::de::DeserializeOwned>(
+        cx: &mut FunctionContext,
+        obj: &JsObject,
+        key: &str,
+    ) -> Option<T> {
+        let value = obj.get::<JsValue, _, _>(cx, key).ok()?;
+        if value.is_a::<JsUndefined, _>(cx) || value.is_a::<JsNull, _>(cx) {
+            return None;
+        }
+
+        let json: Handle<JsObject> = cx.global("JSON").ok()?;
+        let stringify: Handle<JsFunction> = json.get(cx, "stringify").ok()?;
+        let text: Handle<JsString> = stringify.call_with(cx).arg(value).apply(cx).ok()?;
 
-        match ip_parts {
-            Ok(parts) if parts.len() == 4 => [parts[0], parts[1], parts[2], parts[3]],
-            _ => {
-                log::warn!("Invalid IP address: {}, using localhost", host_str);
-                [127, 0, 0, 1]
+        match serde_json::from_str(&text.value(cx)) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                log::error!("❌ Invalid '{}' option: {}", key, e);
+                None
             }
         }
     }
 
-    /// Extract host argument and parse as IP address
-    pub fn extract_host(cx: &mut FunctionContext) -> Result<[u8; 4], neon::result::Throw> {
+    /// Extract host argument as string
+    pub fn extract_host(cx: &mut FunctionContext) -> Result<String, neon::result::Throw> {
         let host_arg = cx.argument::<JsString>(1)?;
-        let host_str = host_arg.value(cx);
-        Ok(parse_ip_address(&host_str))
+        Ok(host_arg.value(cx))
     }
 
     /// Extract port argument
@@ -90,70 +94,144 @@ pub mod config_extractor {
     pub fn extract_ssl_config(
         cx: &mut FunctionContext,
         options_obj: &JsObject,
-    ) -> Option<crate::server::SslConfig> {
-        let ssl_obj = get_object(cx, options_obj, "ssl")?;
-        let cert_path = get_string(cx, &ssl_obj, "certPath", "");
-        let key_path = get_string(cx, &ssl_obj, "keyPath", "");
-
-        if cert_path.is_empty() || key_path.is_empty() {
-            return None;
+    ) -> Option<crate::tls::TlsOptions> {
+        let ssl: crate::tls::TlsOptions = get_json(cx, options_obj, "ssl")?;
+        if let (Some(cert_path), Some(key_path)) = (&ssl.cert_path, &ssl.key_path) {
+            log::info!(
+                "🔒 SSL enabled with certificate: {} and key: {}",
+                cert_path,
+                key_path
+            );
         }
-
-        match crate::server::SslConfig::from_files(&cert_path, &key_path) {
-            Ok(config) => {
-                log::info!(
-                    "🔒 SSL enabled with certificate: {} and key: {}",
-                    cert_path,
-                    key_path
-                );
-                Some(config)
-            }
-            Err(e) => {
-                log::error!("❌ Failed to load SSL certificate: {}, using HTTP only", e);
-                None
-            }
+        for entry in &ssl.certificates {
+            log::info!("🔒 SNI certificate {} for {}", entry.cert_path, entry.server_names.join(", "));
         }
+        Some(ssl)
     }
 
-    /// Extract all common server options from options object
-    pub fn extract_server_options(
-        cx: &mut FunctionContext,
-        options_obj: &JsObject,
-    ) -> (Option<crate::server::SslConfig>, bool, u64, bool) {
-        let ssl_config = extract_ssl_config(cx, options_obj);
-        let metrics_enabled = get_bool(cx, options_obj, "metrics", false);
-        let timeout = get_u64(cx, options_obj, "timeout", 30000); // Default 30 seconds
-        let dev_mode = get_bool(cx, options_obj, "devMode", false);
+    /// Default limit for buffered request bodies (10 MiB)
+    pub const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
 
-        (ssl_config, metrics_enabled, timeout, dev_mode)
+    /// Server startup parameters extracted from listen() arguments
+    pub struct ServerParams {
+        pub addresses: Vec<ListenAddress>,
+        pub ipv6_only: bool,
+        pub ssl_config: Option<crate::tls::TlsOptions>,
+        pub metrics_enabled: bool,
+        pub timeout: u64,
+        pub dev_mode: bool,
+        pub max_body_size: usize,
+        pub method_not_allowed_as_404: bool,
+        pub cors: Option<crate::cors::CorsOptions>,
+        pub rate_limit: Option<crate::rate_limit::RateLimitOptions>,
+        pub trusted_proxies: Option<Vec<String>>,
+        pub security_headers: Option<crate::security_headers::SecurityHeadersOptions>,
+        pub compression: Option<crate::compression::dynamic::CompressionOptions>,
+        pub request_id_header: Option<String>,
+        pub access_log: Option<crate::access_log::AccessLogOptions>,
+        pub tracing: Option<crate::telemetry::TracingOptions>,
+        pub http2: Option<crate::http2::Http2Options>, // None serves HTTP/1.1 only
+        pub https_redirect: Option<crate::https_redirect::HttpsRedirectOptions>,
+        pub connection: crate::connection::ConnectionOptions,
+        pub response_time: bool,
     }
 
     /// Extract all server startup parameters (port, host, options)
     pub fn extract_server_params(
         cx: &mut FunctionContext,
-    ) -> Result<
-        (
-            u16,
-            [u8; 4],
-            Option<crate::server::SslConfig>,
-            bool,
-            u64,
-            bool,
-        ),
-        neon::result::Throw,
-    > {
+    ) -> Result<ServerParams, neon::result::Throw> {
         let port = extract_port(cx)?;
         let host = extract_host(cx)?;
 
+        let mut params = ServerParams {
+            addresses: Vec::new(),
+            ipv6_only: false,
+            ssl_config: None,
+            metrics_enabled: false,
+            timeout: 30000, // Default 30 seconds
+            dev_mode: false,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            method_not_allowed_as_404: false,
+            cors: None,
+            rate_limit: None,
+            trusted_proxies: None,
+            security_headers: None,
+            compression: None,
+            request_id_header: None,
+            access_log: None,
+            tracing: None,
+            http2: Some(Default::default()),
+            https_redirect: None,
+            connection: Default::default(),
+            response_time: false,
+        };
+
         // Get options object (third argument)
-        let (ssl_config, metrics_enabled, timeout, dev_mode) = cx
+        let mut extra_addresses = Vec::new();
+        if let Some(options_obj) = cx
             .argument::<JsValue>(2)
             .ok()
             .and_then(|options_arg| options_arg.downcast::<JsObject, _>(cx).ok())
-            .map(|options_obj| extract_server_options(cx, &options_obj))
-            .unwrap_or((None, false, 30000, false));
+        {
+            params.ssl_config = extract_ssl_config(cx, &options_obj);
+            params.metrics_enabled = get_bool(cx, &options_obj, "metrics", false);
+            params.timeout = get_u64(cx, &options_obj, "timeout", 30000);
+            params.dev_mode = get_bool(cx, &options_obj, "devMode", false);
+            params.max_body_size =
+                get_u64(cx, &options_obj, "maxBodySize", DEFAULT_MAX_BODY_SIZE as u64) as usize;
+            params.method_not_allowed_as_404 =
+                get_bool(cx, &options_obj, "methodNotAllowedAs404", false);
+            params.cors = get_json(cx, &options_obj, "cors");
+            params.rate_limit = get_json(cx, &options_obj, "rateLimit");
+            params.trusted_proxies = get_json(cx, &options_obj, "trustedProxies");
+            // `true` sends the default headers
+            params.security_headers = match get_json(cx, &options_obj, "securityHeaders") {
+                Some(crate::types::Setting::Enabled(true)) => Some(Default::default()),
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => None,
+            };
+            params.compression = match get_json(cx, &options_obj, "compression") {
+                Some(crate::types::Setting::Enabled(true)) => Some(Default::default()),
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => None,
+            };
+            params.request_id_header = get_json(cx, &options_obj, "requestIdHeader");
+            params.access_log = match get_json(cx, &options_obj, "accessLog") {
+                Some(crate::types::Setting::Enabled(true)) => Some(Default::default()),
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => None,
+            };
+            params.tracing = match get_json(cx, &options_obj, "tracing") {
+                Some(crate::types::Setting::Enabled(true)) => Some(Default::default()),
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => None,
+            };
+            // HTTP/2 stays on unless disabled with `false`
+            params.http2 = match get_json(cx, &options_obj, "http2") {
+                Some(crate::types::Setting::Enabled(false)) => None,
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => Some(Default::default()),
+            };
+            params.https_redirect = match get_json(cx, &options_obj, "httpsRedirect") {
+                Some(crate::types::Setting::Enabled(true)) => Some(Default::default()),
+                Some(crate::types::Setting::Value(options)) => Some(options),
+                _ => None,
+            };
+            params.connection = crate::connection::ConnectionOptions {
+                max_connections: get_json(cx, &options_obj, "maxConnections"),
+                header_read_timeout_ms: get_json(cx, &options_obj, "headerReadTimeout"),
+                idle_timeout_ms: get_json(cx, &options_obj, "idleTimeout"),
+                max_header_size: get_json(cx, &options_obj, "maxHeaderSize"),
+                max_headers: get_json(cx, &options_obj, "maxHeaders"),
+            };
+            params.response_time = get_bool(cx, &options_obj, "responseTime", false);
+            params.ipv6_only = get_bool(cx, &options_obj, "ipv6Only", false);
+            extra_addresses = get_string_array(cx, &options_obj, "listen");
+        }
 
-        Ok((port, host, ssl_config, metrics_enabled, timeout, dev_mode))
+        params.addresses = parse_listen_addresses(&host, port, &extra_addresses);
+
+        Ok(params)
     }
 }
 
@@ -175,6 +253,11 @@ pub fn create_app(mut cx: FunctionContext) -> JsResult<JsObject> {
     let version = cx.string("0.1.0");
     obj.set(&mut cx, "version", version)?;
 
+    // Each app gets its own routes, middleware and server state
+    let instance = crate::app::create_app_instance();
+    let app_id = cx.number(instance.id);
+    obj.set(&mut cx, "id", app_id)?;
+
     // Check if log level is passed as argument
     if cx.len() > 0 {
         if let Ok(log_level_str) = cx.argument::<JsString>(0) {
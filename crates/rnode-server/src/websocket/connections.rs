@@ -9,6 +9,7 @@ use futures_util::SinkExt;
 use neon::context::Context;
 use neon::prelude::*; 
 
+use crate::app::{App, AppId};
 use crate::websocket::WebSocketConnection;
 
 // Алиасы для упрощения типов
@@ -230,14 +231,50 @@ pub async fn broadcast_to_room(room_id: &str, message: &serde_json::Value) -> Re
     Ok(())
 }
 
+// Close every active connection of an app with a close frame (used on server shutdown)
+pub async fn close_all_connections(app_id: AppId, reason: &str) -> usize {
+    let connection_ids: Vec<Uuid> = crate::websocket::rooms::get_websocket_connections()
+        .read()
+        .await
+        .values()
+        .filter(|connection| connection.app_id == app_id)
+        .map(|connection| connection.id)
+        .collect();
+    let mut closed = 0;
+    
+    for connection_id in &connection_ids {
+        {
+            let senders = get_websocket_senders().read().await;
+            if let Some(sender) = senders.get(connection_id) {
+                let mut sender_guard = sender.lock().await;
+                let frame = axum::extract::ws::CloseFrame {
+                    code: axum::extract::ws::close_code::AWAY,
+                    reason: reason.to_string().into(),
+                };
+                if let Err(e) = sender_guard.send(axum::extract::ws::Message::Close(Some(frame))).await {
+                    log::debug!("⚠️ Failed to send close frame to {}: {}", connection_id, e);
+                } else {
+                    closed += 1;
+                }
+                let _ = sender_guard.close().await;
+            }
+        }
+        
+        remove_sender(connection_id).await;
+        remove_connection(connection_id).await;
+    }
+    
+    log::info!("🔌 Closed {} of {} WebSocket connections", closed, connection_ids.len());
+    closed
+}
+
 // Функция для вызова WebSocket колбеков на беке
-pub async fn send_websocket_event(event_type: &str, connection_id: &Uuid, path: &str, _handler_id: &str, data: Option<&str>, client_id: Option<&str>) -> Result<Option<serde_json::Value>, String> {
+pub async fn send_websocket_event(app: &App, event_type: &str, connection_id: &Uuid, path: &str, _handler_id: &str, data: Option<&str>, client_id: Option<&str>) -> Result<Option<serde_json::Value>, String> {
     log::debug!("🔌 send_websocket_event called: type={}, connection_id={}, path={}, data={:?}, client_id={:?}", 
                 event_type, connection_id, path, data, client_id);
     
     // Check if event is enabled for this path
-    let routes = crate::websocket::get_websocket_routes();
-    let routes_map = routes.read().await;
+    let routes_map = app.websocket_routes.read().await;
     
     if let Some(handler) = routes_map.get(path) {
         // Преобразуем название события в название колбека
@@ -265,11 +302,7 @@ pub async fn send_websocket_event(event_type: &str, connection_id: &Uuid, path:
     }
     
     // Вызываем onWebSocketEvent через global для вызова колбеков
-    let channel = {
-        let event_queue = crate::types::get_event_queue();
-        let guard = event_queue.read().unwrap();
-        guard.as_ref().cloned()
-    };
+    let channel = app.channel();
     
     if let Some(channel) = channel {
         log::debug!("✅ Channel found, sending event to JavaScript");
@@ -277,7 +310,8 @@ pub async fn send_websocket_event(event_type: &str, connection_id: &Uuid, path:
             "type": event_type,
             "connection_id": connection_id.to_string(),
             "path": path,
-            "data": data
+            "data": data,
+            "appId": app.id
         });
         
         // Добавляем client_id если он предоставлен
@@ -398,6 +432,7 @@ pub async fn send_websocket_event(event_type: &str, connection_id: &Uuid, path:
 
 // Функция для создания нового соединения
 pub fn create_connection(
+    app_id: AppId,
     connection_id: Uuid,
     client_id: String,
     path: String,
@@ -405,6 +440,7 @@ pub fn create_connection(
 ) -> WebSocketConnection {
     WebSocketConnection {
         id: connection_id,
+        app_id,
         client_id,
         path,
         room_id: None,
@@ -1,6 +1,4 @@
 use std::collections::HashMap;
-use std::sync::OnceLock;
-use tokio::sync::RwLock;
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use log;
@@ -8,6 +6,7 @@ use log;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketConnection {
     pub id: Uuid,
+    pub app_id: crate::app::AppId,
     pub client_id: String,  // ID клиента для удобной работы с каналами
     pub path: String,
     pub room_id: Option<String>,
@@ -33,11 +32,14 @@ pub struct Room {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-// Глобальные хранилища
-pub static WEBSOCKET_ROUTES: OnceLock<RwLock<HashMap<String, WebSocketHandler>>> = OnceLock::new();
-
-pub fn get_websocket_routes() -> &'static RwLock<HashMap<String, WebSocketHandler>> {
-    WEBSOCKET_ROUTES.get_or_init(|| RwLock::new(HashMap::new()))
+// Whether any app has WebSocket routes registered
+pub fn has_websocket_routes() -> bool {
+    crate::app::all_apps().iter().any(|app| {
+        app.websocket_routes
+            .try_read()
+            .map(|routes| !routes.is_empty())
+            .unwrap_or(true)
+    })
 }
 
 pub mod router;
@@ -67,9 +69,14 @@ pub fn register_websocket(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     };
     
     let handler = WebSocketHandler { enabled_events: enabled_events.clone() };
+    let app = crate::app::app_from_arg(&mut cx, 2)?;
+
+    let entry = crate::route_table::RouteEntry::new(crate::route_table::RouteSource::WebSocket, &path, None);
+    if let Err(conflict) = crate::route_table::check_route(&app, entry) {
+        return cx.throw_error(conflict);
+    }
     
-    let routes = get_websocket_routes();
-    let mut routes_map = routes.blocking_write();
+    let mut routes_map = app.websocket_routes.blocking_write();
     
     // Initialize WebSocket metrics if this is the first route
     if routes_map.is_empty() {
@@ -77,8 +84,12 @@ pub fn register_websocket(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     }
     
     routes_map.insert(path.clone(), handler);
+    drop(routes_map);
     
     log::info!("🔧 WebSocket route registered: {} with enabled events: {:?}", path, enabled_events);
+
+    // Apply to the running server
+    crate::route_table::rebuild(&app);
     
     Ok(cx.undefined())
 }
@@ -5,9 +5,8 @@ use futures_util::stream::StreamExt;
 use uuid::Uuid;
 use log;
 
-use crate::websocket::{
-    get_websocket_routes
-};
+use crate::app::App;
+use std::sync::Arc;
 use crate::websocket::rooms::{
     join_room,
     leave_room
@@ -38,15 +37,14 @@ use crate::websocket::connections::{
 
 // This function is not used and removed for code cleanliness
 
-pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: String, client_id_from_query: Option<String>) -> Result<(), String> {
+pub async fn handle_websocket(app: Arc<App>, socket: WebSocket, path: String, handler_id: String, client_id_from_query: Option<String>) -> Result<(), String> {
     let (sender, mut receiver) = socket.split();
     
     log::info!("🔌 WebSocket connection established for path: {}", path);
     
     // Get handler to determine JavaScript functions
-    let routes = get_websocket_routes();
     let handler = {
-        let routes_map = routes.read().await;
+        let routes_map = app.websocket_routes.read().await;
         routes_map.get(&path).cloned()
     };
     
@@ -68,12 +66,12 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
     log::debug!("🆔 Final Client ID: {}", client_id);
     log::debug!("🔗 Connection ID generated: {}", connection_id);
 
-    let connection = create_connection(connection_id, client_id.clone(), path.clone(), handler_id.clone());
+    let connection = create_connection(app.id, connection_id, client_id.clone(), path.clone(), handler_id.clone());
     
     log::debug!("🔧 Connection object created");
 
     // Call WebSocket callbacks BEFORE adding connection and sender
-    let connect_result = send_websocket_event("connect", &connection_id, &path, &handler_id, None, Some(&client_id)).await;
+    let connect_result = send_websocket_event(&app, "connect", &connection_id, &path, &handler_id, None, Some(&client_id)).await;
             match connect_result {
             Ok(Some(_)) => {
                 // Event allowed, continue
@@ -117,6 +115,7 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
     let ping_connection_id = connection_id;
     let ping_path = path.clone();
     let ping_handler_id = handler_id.clone();
+    let ping_app = app.clone();
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
         loop {
@@ -130,7 +129,7 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
             }
             
             // Call WebSocket callbacks BEFORE sending ping
-            let ping_result = send_websocket_event("ping", &ping_connection_id, &ping_path, &ping_handler_id, None, None).await;
+            let ping_result = send_websocket_event(&ping_app, "ping", &ping_connection_id, &ping_path, &ping_handler_id, None, None).await;
             match ping_result {
                 Ok(Some(_)) => {
                     // Event allowed, continue
@@ -166,12 +165,12 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
                 log::debug!("📨 Text message received: {}", text);
                 
                 // Call WebSocket callbacks BEFORE processing message
-                let message_result = send_websocket_event("message", &connection_id, &path, &handler_id, Some(&text), None).await;
+                let message_result = send_websocket_event(&app, "message", &connection_id, &path, &handler_id, Some(&text), None).await;
                 match message_result {
                     Ok(Some(_)) => {
                         // Event allowed, continue processing
                         log::debug!("✅ Message event allowed by callback");
-                        handle_text_message(&connection_id, &path, &handler_id, &text).await?;
+                        handle_text_message(&app, &connection_id, &path, &handler_id, &text).await?;
                     }
                     Ok(None) => {
                         // Event cancelled by callback
@@ -194,7 +193,7 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
                 crate::metrics::websocket::record_message_received("binary", room_id.as_deref(), &path, data.len());
                 
                 // Call WebSocket callbacks BEFORE processing binary message
-                let binary_result = send_websocket_event("binary_message", &connection_id, &path, &handler_id, Some(&format!("Binary data: {} bytes", data.len())), None).await;
+                let binary_result = send_websocket_event(&app, "binary_message", &connection_id, &path, &handler_id, Some(&format!("Binary data: {} bytes", data.len())), None).await;
                 match binary_result {
                     Ok(Some(_)) => {
                         // Event allowed, can add additional processing
@@ -235,7 +234,7 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
     log::info!("🔌 WebSocket connection closing for path: {}", path);
     
     // Call WebSocket callbacks BEFORE closing connection
-    let close_result = send_websocket_event("close", &connection_id, &path, &handler_id, None, None).await;
+    let close_result = send_websocket_event(&app, "close", &connection_id, &path, &handler_id, None, None).await;
     match close_result {
         Ok(Some(_)) => {
             // Событие разрешено, продолжаем закрытие
@@ -267,7 +266,7 @@ pub async fn handle_websocket(socket: WebSocket, path: String, handler_id: Strin
     Ok(())
 }
 
-async fn handle_text_message(connection_id: &Uuid, path: &str, _handler_id: &str, text: &str) -> Result<(), String> {
+async fn handle_text_message(app: &App, connection_id: &Uuid, path: &str, _handler_id: &str, text: &str) -> Result<(), String> {
     // Record message received metric
     let room_id = crate::websocket::connections::get_connection_room(connection_id).await;
     crate::metrics::websocket::record_message_received("text", room_id.as_deref(), path, text.len());
@@ -276,13 +275,13 @@ async fn handle_text_message(connection_id: &Uuid, path: &str, _handler_id: &str
     if let Ok(data) = serde_json::from_str::<serde_json::Value>(text) {
         match data.get("type").and_then(|t| t.as_str()) {
             Some("join_room") => {
-                if let Err(e) = handle_join_room_message(connection_id, &data).await {
+                if let Err(e) = handle_join_room_message(app, connection_id, &data).await {
                     log::error!("Join room error: {}", e);
                     crate::metrics::websocket::record_error("join_room_failed", path, room_id.as_deref());
                 }
             },
             Some("leave_room") => {
-                if let Err(e) = handle_leave_room_message(connection_id, &data).await {
+                if let Err(e) = handle_leave_room_message(app, connection_id, &data).await {
                     log::error!("Leave room error: {}", e);
                     crate::metrics::websocket::record_error("leave_room_failed", path, room_id.as_deref());
                 }
@@ -300,7 +299,7 @@ async fn handle_text_message(connection_id: &Uuid, path: &str, _handler_id: &str
                 }
             },
             Some("ping") => {
-                if let Err(e) = handle_ping_message(connection_id).await {
+                if let Err(e) = handle_ping_message(app, connection_id).await {
                     log::error!("Ping error: {}", e);
                     crate::metrics::websocket::record_error("ping_failed", path, room_id.as_deref());
                 }
@@ -322,7 +321,7 @@ async fn handle_text_message(connection_id: &Uuid, path: &str, _handler_id: &str
     Ok(())
 }
 
-async fn handle_join_room_message(connection_id: &Uuid, data: &serde_json::Value) -> Result<(), String> {
+async fn handle_join_room_message(app: &App, connection_id: &Uuid, data: &serde_json::Value) -> Result<(), String> {
     log::debug!("🔍 Processing join room message: {}", data);
     
     // Extract room_id
@@ -332,7 +331,7 @@ async fn handle_join_room_message(connection_id: &Uuid, data: &serde_json::Value
     
     if let Some(room_id) = room_id {
         // Call WebSocket callbacks BEFORE joining room
-        let join_result = send_websocket_event("join_room", connection_id, "room", "join", Some(room_id), None).await;
+        let join_result = send_websocket_event(app, "join_room", connection_id, "room", "join", Some(room_id), None).await;
         match join_result {
             Ok(Some(_)) => {
                 // Event allowed, continue joining room
@@ -434,7 +433,7 @@ async fn handle_direct_message_message(connection_id: &Uuid, data: &serde_json::
     Ok(())
 }
 
-async fn handle_leave_room_message(connection_id: &Uuid, data: &serde_json::Value) -> Result<(), String> {
+async fn handle_leave_room_message(app: &App, connection_id: &Uuid, data: &serde_json::Value) -> Result<(), String> {
     log::debug!("🔍 Processing leave room message: {}", data);
     
     // Extract room_id
@@ -444,7 +443,7 @@ async fn handle_leave_room_message(connection_id: &Uuid, data: &serde_json::Valu
     
     if let Some(room_id) = room_id {
         // Call WebSocket callbacks BEFORE leaving room
-        let leave_result = send_websocket_event("leave_room", connection_id, "room", "leave", Some(room_id), None).await;
+        let leave_result = send_websocket_event(app, "leave_room", connection_id, "room", "leave", Some(room_id), None).await;
         match leave_result {
             Ok(Some(_)) => {
                 // Event allowed, continue leaving room
@@ -519,9 +518,9 @@ async fn handle_room_message_message(connection_id: &Uuid, data: &serde_json::Va
     Ok(())
 }
 
-async fn handle_ping_message(connection_id: &Uuid) -> Result<(), String> {
+async fn handle_ping_message(app: &App, connection_id: &Uuid) -> Result<(), String> {
     // Call WebSocket callbacks BEFORE processing ping
-    let ping_result = send_websocket_event("ping", connection_id, "ping", "ping", None, None).await;
+    let ping_result = send_websocket_event(app, "ping", connection_id, "ping", "ping", None, None).await;
             match ping_result {
             Ok(Some(_)) => {
                 // Event allowed, continue processing
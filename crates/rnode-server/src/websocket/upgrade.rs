@@ -5,9 +5,12 @@ use axum::{
     http::Request,
     body::Body,
 };
+use crate::app::App;
 use log;
+use std::sync::Arc;
 
 pub async fn websocket_upgrade_handler(
+    app: Arc<App>,
     req: Request<Body>,
     path: String,
     handler_id: String,
@@ -52,7 +55,7 @@ pub async fn websocket_upgrade_handler(
                         
                         log::info!("🔌 WebSocket connection established for path: {}", path);
                         // Handle WebSocket connection
-                        if let Err(e) = crate::websocket::router::handle_websocket(socket, path, handler_id, client_id).await.map_err(|e| format!("WebSocket error: {}", e)) {
+                        if let Err(e) = crate::websocket::router::handle_websocket(app, socket, path, handler_id, client_id).await.map_err(|e| format!("WebSocket error: {}", e)) {
                             log::error!("WebSocket handler error: {}", e);
                         }
                     }).into_response();
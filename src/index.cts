@@ -22,22 +22,27 @@ import { StaticOptions } from "./types/app-router";
 
 declare module "./load.cjs" {
   function hello(name: string): string;
-  function createApp(logLevel?: string): { name: string; version: string; logLevel?: string };
-  function get(path: string, handler: Function): void;
-  function post(path: string, handler: Function): void;
-  function put(path: string, handler: Function): void;
-  function del(path: string, handler: Function): void;
-  function patch(path: string, handler: Function): void;
-  function options(path: string, handler: Function): void;
-  function trace(path: string, handler: Function): void;
-  function any(path: string, handler: Function): void;
-  function use(path: string, handler: Function): void;
-  function listen(port: number, host: string, options: AppOptions): void;
-  function loadStaticFiles(path: string, options?: StaticOptions): void;
-  function clearStaticCache(): void;
-  function getStaticStats(): string;
-  function initTemplates(pattern: string, options: TemplateOptions): string;
-  function renderTemplate(templateName: string, context: string): string;
+  function createApp(logLevel?: string): { name: string; version: string; logLevel?: string; id: number };
+  function get(path: string, handler: Function, appId?: number, options?: string): void;
+  function post(path: string, handler: Function, appId?: number, options?: string): void;
+  function put(path: string, handler: Function, appId?: number, options?: string): void;
+  function del(path: string, handler: Function, appId?: number, options?: string): void;
+  function patch(path: string, handler: Function, appId?: number, options?: string): void;
+  function options(path: string, handler: Function, appId?: number, options?: string): void;
+  function trace(path: string, handler: Function, appId?: number, options?: string): void;
+  function any(path: string, handler: Function, appId?: number, options?: string): void;
+  function use(path: string, handler: Function, appId?: number, options?: string): number;
+  function after(path: string, handler: Function, appId?: number, options?: string): number;
+  function removeRoute(method: string, path: string, appId?: number): boolean;
+  function removeMiddleware(path: string, appId?: number): boolean;
+  function listen(port: number, host: string, options: AppOptions, appId?: number): void;
+  function close(timeout?: number, appId?: number): Promise<void>;
+  function reloadTls(appId?: number): void;
+  function loadStaticFiles(path: string, options?: StaticOptions, appId?: number): void;
+  function clearStaticCache(appId?: number): void;
+  function getStaticStats(appId?: number): string;
+  function initTemplates(pattern: string, options: TemplateOptions, appId?: number): string;
+  function renderTemplate(templateName: string, context: string, appId?: number, cspNonce?: string, traceparent?: string): string;
 
   // Functions for working with files
   function saveFile(filename: string, base64Data: string, uploadsDir: string): string;
@@ -45,24 +50,15 @@ declare module "./load.cjs" {
   function listFiles(uploadsDir: string): string;
   function getFileContent(filename: string, uploadsDir: string): string;
   function fileExists(filename: string, uploadsDir: string): boolean;
-  function registerDownloadRoute(path: string, options: string): void;
-  function registerUploadRoute(path: string, options: string): void;
+  function registerDownloadRoute(path: string, options: string, appId?: number): void;
+  function registerUploadRoute(path: string, options: string, appId?: number): void;
 
   // HTTP utility functions
-  function httpRequest(method: string, url: string, headers: string, body: string, timeout: number): string;
-  function httpBatch(requests: string, timeout: number): string;
+  function httpRequest(method: string, url: string, headers: string, body: string, timeout: number, traceparent?: string): string;
+  function httpBatch(requests: string, timeout: number, traceparent?: string): string;
 
   // WebSocket functions
-  function registerWebSocket(
-    path: string,
-    onConnect?: string,
-    onMessage?: string,
-    onClose?: string,
-    onError?: string,
-    onJoinRoom?: string,
-    onLeaveRoom?: string,
-    clientId?: string
-  ): void;
+  function registerWebSocket(path: string, enabledEvents: string, appId?: number): void;
   function createRoom(name: string, description?: string, maxConnections?: number): string;
   function sendRoomMessage(roomId: string, message: string): boolean;
   function getRoomInfo(roomId: string): {
@@ -106,6 +102,9 @@ declare module "./load.cjs" {
     createdAt: string;
   }>;
 
+  // Server-Sent Events functions
+  function registerSse(path: string, handler: (req: any, stream: any) => void | Promise<void>, options: string, appId?: number): void;
+
   // Cache functions
   function initCacheSystem(config: {
     defaultTtl?: number;
@@ -113,12 +112,12 @@ declare module "./load.cjs" {
     redisUrl?: string;
     fileCachePath?: string;
   }): void;
-  function cacheGet(key: string, tags: string[]): string | null;
-  function cacheSet(key: string, value: string, tags: string[], ttl: number): boolean;
-  function cacheDelete(key: string, tags: string[]): boolean;
-  function cacheExists(key: string, tags: string[]): boolean;
-  function cacheClear(): boolean;
-  function cacheFlushByTags(tags: string[]): number;
+  function cacheGet(key: string, tags: string[], traceparent?: string): string | null;
+  function cacheSet(key: string, value: string, tags: string[], ttl: number, traceparent?: string): boolean;
+  function cacheDelete(key: string, tags: string[], traceparent?: string): boolean;
+  function cacheExists(key: string, tags: string[], traceparent?: string): boolean;
+  function cacheClear(traceparent?: string): boolean;
+  function cacheFlushByTags(tags: string[], traceparent?: string): number;
 
 
 }
@@ -172,9 +171,10 @@ export default {
 };
 
 // Export types for use
-export type { StaticOptions, TemplateOptions, AppOptions, DownloadOptions, UploadOptions, SslConfig } from './types/app-router';
-export type { Request } from './utils/request';
+export type { StaticOptions, TemplateOptions, AppOptions, DownloadOptions, UploadOptions, SslConfig, SniCertificate, RouteOptions, MiddlewareOptions, CorsOptions, RateLimitOptions, SecurityHeadersOptions, CompressionOptions, AccessLogOptions, TracingOptions, Http2Options, HttpsRedirectOptions } from './types/app-router';
+export type { Request, ClientCertificate } from './utils/request';
 export type { Response } from './utils/response';
+export type { SseStream, SseEvent, SseOptions, SseHandler } from './utils/sse-utils';
 export type { OpenAPIGenerator, OpenAPIConfig } from './utils/openapi';
 
 
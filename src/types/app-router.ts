@@ -1,7 +1,19 @@
 // SSL Configuration interface
 export interface SslConfig {
-  certPath?: string;
+  certPath?: string; // Default certificate, served when no SNI entry matches
   keyPath?: string;
+  certificates?: SniCertificate[]; // Per-hostname certificates chosen by SNI
+  watch?: boolean; // Reload certificates when their files change
+  strict?: boolean; // Make listen() throw instead of falling back to HTTP when certificates fail to load
+  clientCaPath?: string; // PEM bundle of CAs for mutual TLS; clients must present a certificate they issued
+  clientAuth?: 'required' | 'optional'; // 'optional' also accepts clients without a certificate (default 'required')
+}
+
+// Certificate for the listed SNI names, e.g. 'example.com' or '*.example.com'
+export interface SniCertificate {
+  serverNames: string[];
+  certPath: string;
+  keyPath: string;
 }
 
 // App creation options
@@ -11,6 +23,131 @@ export interface AppOptions {
   metrics?: boolean
   timeout?: number
   devMode?: boolean
+  listen?: string[] // Additional listen addresses: '0.0.0.0:8080', '[::]:8080', 'unix:/tmp/app.sock'
+  ipv6Only?: boolean // Disable dual-stack on IPv6 wildcard addresses
+  maxBodySize?: number // Request body limit in bytes (default 10 MiB), larger bodies get 413
+  methodNotAllowedAs404?: boolean // Legacy: answer unsupported methods with 404 instead of 405
+  cors?: CorsOptions // Native CORS handling, preflights are answered without calling JS
+  trustedProxies?: string[] // Peers whose forwarding headers set req.ip: CIDRs, addresses, 'loopback', 'private', 'linklocal' (default ['loopback'])
+  rateLimit?: RateLimitOptions // Native rate limiting, rejected requests get 429 without calling JS
+  responseTime?: boolean // Add X-Response-Time to every response
+  securityHeaders?: boolean | SecurityHeadersOptions // Native Helmet-style headers; true sends the defaults
+  compression?: boolean | CompressionOptions // Compress handler and template responses on the fly
+  requestIdHeader?: string // Header the request ID is read from and echoed in (default 'x-request-id')
+  accessLog?: boolean | AccessLogOptions // One line per request; true logs JSON to stdout
+  tracing?: boolean | TracingOptions // W3C trace context and OTLP span export; true sends to http://localhost:4318
+  http2?: boolean | Http2Options // HTTP/2 over TLS via ALPN (default on); false serves HTTP/1.1 only
+  httpsRedirect?: boolean | HttpsRedirectOptions // Plain listener redirecting to the HTTPS server; true listens on port 80
+  maxConnections?: number // Open connections across all listeners; new plain ones over the limit get 503, TLS ones are closed
+  headerReadTimeout?: number // TLS handshake and request headers must arrive within this many ms (default 30000)
+  idleTimeout?: number // Close keep-alive connections idle for this many ms (default 60000, 0 disables)
+  maxHeaderSize?: number // Request header bytes (HTTP/1.1 minimum 8192); larger heads get 431
+  maxHeaders?: number // Request header count (HTTP/1.1, default 100); more get 431
+}
+
+// CORS configuration; route entries override the top-level fields for matching path globs
+export interface CorsOptions {
+  origins?: '*' | (string | RegExp)[] // Allowed origins, RegExp entries are matched against the Origin header
+  credentials?: boolean
+  methods?: string[]
+  allowedHeaders?: string[] // Defaults to the headers requested by the preflight
+  exposedHeaders?: string[]
+  maxAge?: number // Preflight cache lifetime in seconds
+  routes?: Record<string, Omit<CorsOptions, 'routes'>>
+}
+
+// Rate limit configuration; route entries override the top-level fields for matching path globs
+export interface RateLimitOptions {
+  algorithm?: 'slidingWindow' | 'tokenBucket' // Default 'slidingWindow'
+  limit?: number // Requests per window (token bucket capacity); no top-level limit means only routes are limited
+  windowMs?: number // Window length, or time to refill the whole bucket (default 60000)
+  key?: 'ip' | { header: string } | { param: string } // What requests are counted by (default 'ip')
+  store?: 'memory' | 'redis' // 'redis' shares counters through the cache's Redis connection
+  routes?: Record<string, Omit<RateLimitOptions, 'routes' | 'store'> | false>
+}
+
+// Security headers; `false` disables a header, `true` sends its default. Route entries override the top-level fields
+export interface SecurityHeadersOptions {
+  contentSecurityPolicy?: boolean | {
+    directives?: Record<string, string | string[] | boolean> // 'script-src' or scriptSrc; false removes a default directive
+    useDefaults?: boolean // Start from the default directives (default true)
+    reportOnly?: boolean // Send Content-Security-Policy-Report-Only instead
+    nonce?: boolean // Per-request nonce in script-src and style-src, exposed as req.cspNonce
+  }
+  strictTransportSecurity?: boolean | { maxAge?: number; includeSubDomains?: boolean; preload?: boolean } // Default max-age=31536000; includeSubDomains
+  frameOptions?: boolean | 'DENY' | 'SAMEORIGIN' // Default SAMEORIGIN
+  referrerPolicy?: boolean | string // Default no-referrer
+  noSniff?: boolean // X-Content-Type-Options: nosniff (default true)
+  crossOriginOpenerPolicy?: boolean | string // Default same-origin
+  crossOriginResourcePolicy?: boolean | string // Default same-origin
+  crossOriginEmbedderPolicy?: boolean | string // Off by default, true sends require-corp
+  permissionsPolicy?: boolean | string // Off by default
+  originAgentCluster?: boolean // Origin-Agent-Cluster: ?1 (default true)
+  legacyHeaders?: boolean // X-DNS-Prefetch-Control, X-Download-Options, X-Permitted-Cross-Domain-Policies, X-XSS-Protection (default true)
+  routes?: Record<string, Omit<SecurityHeadersOptions, 'routes'> | boolean>
+}
+
+// On-the-fly compression of responses, negotiated with Accept-Encoding
+export interface CompressionOptions {
+  minSize?: number // Smaller bodies are sent as is (default 1024 bytes)
+  encodings?: ('br' | 'zstd' | 'gzip')[] // Offered codings in preference order (default ['br', 'zstd', 'gzip'])
+  contentTypes?: string[] // MIME globs to compress (default text/*, JSON, JavaScript, XML and SVG)
+  level?: { gzip?: number; br?: number; zstd?: number } // Default gzip 6, br 4, zstd 3
+}
+
+// Access log destination and format
+export interface AccessLogOptions {
+  format?: 'json' | 'common' | 'combined' // Default 'json'
+  path?: string // Log file; stdout when unset
+  maxSize?: number // Rotate the file past this many bytes (default 10 MiB)
+  maxFiles?: number // Rotated files kept as path.1 ... path.N (default 5)
+}
+
+// HTTP/2 settings; unset sizes keep hyper's defaults
+export interface Http2Options {
+  h2c?: boolean // Also accept prior-knowledge HTTP/2 on plain listeners, e.g. behind a load balancer
+  maxConcurrentStreams?: number // Streams per connection (default 200)
+  initialStreamWindowSize?: number // Bytes (default 1 MiB)
+  initialConnectionWindowSize?: number // Bytes (default 1 MiB)
+  adaptiveWindow?: boolean // Size windows from the measured bandwidth-delay product, overrides the sizes above
+  maxFrameSize?: number // Bytes, 16384-16777215 (default 16384)
+  maxHeaderListSize?: number // Bytes (default 16 KiB)
+  keepAliveIntervalMs?: number // PING idle connections at this interval (off by default)
+  keepAliveTimeoutMs?: number // Close the connection if a PING is not answered in time (default 20000)
+}
+
+// Plain HTTP listener started next to an HTTPS server
+export interface HttpsRedirectOptions {
+  port?: number // Listener port (default 80)
+  host?: string // Bind address (default: the IP of the main TCP listener; required for Unix-only servers)
+  httpsPort?: number // Port in the Location header (default: the bound HTTPS port, omitted for 443)
+  hostname?: string // Host in the Location header (default: the request's Host)
+  status?: 301 | 308 // 308 keeps the method and body (default 301)
+  exclude?: string[] // Path prefixes the app serves over HTTP, e.g. '/.well-known/acme-challenge'
+}
+
+// OTLP/HTTP span export; the first app that enables tracing configures the process
+export interface TracingOptions {
+  endpoint?: string // Collector base URL, spans are POSTed to {endpoint}/v1/traces (default http://localhost:4318)
+  serviceName?: string // service.name resource attribute (default 'rnode-server')
+  sampleRatio?: number // Share of new traces recorded, 0-1 (default 1); incoming traceparents keep their decision
+  headers?: Record<string, string> // Sent with every export, e.g. an API key
+  batchSize?: number // Spans per export request (default 512)
+  flushIntervalMs?: number // Longest a span waits before export (default 1000)
+}
+
+// Per-route options: app.post(path, handler, options)
+export interface RouteOptions {
+  maxBodySize?: number // Overrides the app's maxBodySize for this route
+  streamBody?: boolean // Expose the raw body as req.bodyStream instead of parsing it
+  timeout?: number // Overrides the app's timeout (ms) for this route's middleware and handler
+}
+
+// Middleware options: app.use(path, middleware, options)
+export interface MiddlewareOptions {
+  method?: string | string[] // Only run for these methods
+  error?: boolean // Error middleware; detected for (err, req, res, next) functions
+  timeout?: number // Abort this middleware after this many ms (capped by the request's timeout)
 }
 
 // Types for template operations
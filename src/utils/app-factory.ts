@@ -12,22 +12,28 @@ export function createApp(options?: AppOptions): RNodeApp {
   const appInfo = addon.createApp(level);
   logger.info(`🚀 Creating ${appInfo.name} v${appInfo.version} with log level: ${level}`, 'rnode_server::app');
 
-  // Create RNodeApp instance
-  const app = createRNodeApp();
+  // Create RNodeApp instance bound to its own native app
+  const app = createRNodeApp(appInfo.id);
   
   // Set log level using the method
   app.setLogLevel(level);
   app.setMetrics(options?.metrics ?? false);
   app.setTimeout(options?.timeout ?? 30000)
   app.setDevMode(options?.devMode ?? process.env.MODE === 'development')
+  app.setServerOptions(options ?? {})
   
   // Store SSL configuration if provided
   if (options?.ssl) {
-    const { certPath, keyPath } = options.ssl;
-    if (certPath && keyPath) {
+    const { certPath, keyPath, certificates, strict } = options.ssl;
+    if ((certPath && keyPath) || certificates?.length || strict) {
       logger.info(`🔒 SSL configuration loaded:`, 'rnode_server::app');
-      logger.info(`   Certificate: ${certPath}`, 'rnode_server::app');
-      logger.info(`   Private Key: ${keyPath}`, 'rnode_server::app');
+      if (certPath && keyPath) {
+        logger.info(`   Certificate: ${certPath}`, 'rnode_server::app');
+        logger.info(`   Private Key: ${keyPath}`, 'rnode_server::app');
+      }
+      for (const entry of certificates ?? []) {
+        logger.info(`   SNI: ${entry.serverNames.join(', ')} -> ${entry.certPath}`, 'rnode_server::app');
+      }
       app.setSslConfig(options.ssl)
     } else {
       logger.warn('⚠️ SSL certificate paths are not provided in options.', 'rnode_server::app');
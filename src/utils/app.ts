@@ -1,16 +1,32 @@
 import { logger } from './logger';
 import { Router } from './router';
-import { SslConfig, AppOptions } from '../types/app-router';
+import { SslConfig, AppOptions, CorsOptions } from '../types/app-router';
 import { Request } from './request';
 import { Response } from './response';
-import { clearStaticCache, getStaticStats } from './file-utils';
+import { clearStaticCache, getStaticStats, loadStaticFiles } from './file-utils';
 import { createHttpMethodsUtils } from './http-methods';
-import { createMiddlewareUtils } from './middleware';
+import type { MiddlewareEntry, AfterHookEntry } from './middleware';
 import * as addon from '../load.cjs';
 import { DownloadOptions, UploadOptions } from "../types/app-router";
-import { handlers, middlewares, websocketCallbacks } from './global-utils';
+import { getAppRegistry, type AppRegistry } from './global-utils';
+import { currentTraceparent } from './tracing';
 import { WebSocketOptions, WebSocketRoom } from '../types/websocket';
+import { type SseHandler, type SseOptions } from './sse-utils';
 import { createOpenAPIGenerator, type OpenAPIGenerator, type OpenAPIConfig } from './openapi';
+import { registerShutdownHook, unregisterShutdownHook } from './shutdown-utils';
+
+// CORS options as sent to Rust: RegExp origins become pattern strings
+function serializeCors(cors: Omit<CorsOptions, 'routes'>): Record<string, any> {
+  const { origins, ...rest } = cors;
+  if (origins === undefined) return rest;
+
+  const list = origins === '*' ? ['*'] : origins;
+  return {
+    ...rest,
+    origins: list.filter((origin): origin is string => typeof origin === 'string'),
+    originPatterns: list.filter((origin): origin is RegExp => origin instanceof RegExp).map((origin) => origin.source),
+  };
+}
 
 export class RNodeApp extends Router {
   // Properties
@@ -19,16 +35,23 @@ export class RNodeApp extends Router {
   private timeout: number = 30000;
   private devMode: boolean = false;
   private sslConfig: SslConfig | undefined = undefined;
+  private serverOptions: AppOptions = {};
+  private listening: boolean = false;
   private openAPIGenerator: OpenAPIGenerator | undefined = undefined;
+  // Native app instance this app registers its routes and server in
+  private readonly appId: number;
+  private readonly registry: AppRegistry;
 
-  constructor() {
+  constructor(appId: number = 0) {
     super();
+    this.appId = appId;
+    this.registry = getAppRegistry(appId);
     
     // Initialize HTTP methods and middleware for the app level
-    const httpMethods = createHttpMethodsUtils(handlers);
-    const middlewareUtils = createMiddlewareUtils(middlewares);
+    const httpMethods = createHttpMethodsUtils(this.registry.handlers);
     
     // Bind app-specific methods to preserve 'this' context
+    this.getAppId = this.getAppId.bind(this);
     this.setMetrics = this.setMetrics.bind(this);
     this.getMetrics = this.getMetrics.bind(this);
     this.setLogLevel = this.setLogLevel.bind(this);
@@ -39,17 +62,27 @@ export class RNodeApp extends Router {
     this.getDevMode = this.getDevMode.bind(this);
     this.setSslConfig = this.setSslConfig.bind(this);
     this.getSslConfig = this.getSslConfig.bind(this);
+    this.setServerOptions = this.setServerOptions.bind(this);
+    this.getServerOptions = this.getServerOptions.bind(this);
     this.clearStaticCache = this.clearStaticCache.bind(this);
     this.getStaticStats = this.getStaticStats.bind(this);
     this.download = this.download.bind(this);
     this.upload = this.upload.bind(this);
     this.useRouter = this.useRouter.bind(this);
+    this.removeRoute = this.removeRoute.bind(this);
+    this.removeMiddleware = this.removeMiddleware.bind(this);
     this.getAllRoutes = this.getAllRoutes.bind(this);
     this.listen = this.listen.bind(this);
+    this.close = this.close.bind(this);
+    this.reloadTls = this.reloadTls.bind(this);
     this.httpRequest = this.httpRequest.bind(this);
     this.httpBatch = this.httpBatch.bind(this);
   }
 
+  getAppId(): number {
+    return this.appId;
+  }
+
   // Logging configuration
   setMetrics(metricsValue: boolean): void {
     this.metrics = metricsValue;
@@ -94,6 +127,15 @@ export class RNodeApp extends Router {
     return this.devMode;
   }
 
+  // Options passed through to the native listener (listen addresses, etc.)
+  setServerOptions(options: AppOptions): void {
+    this.serverOptions = { ...options };
+  }
+
+  getServerOptions(): AppOptions {
+    return this.serverOptions;
+  }
+
   // SSL configuration
   setSslConfig(config: SslConfig): void {
     this.sslConfig = config;
@@ -144,17 +186,17 @@ export class RNodeApp extends Router {
 
   // Override static method to add app-specific functionality
   static(pathOrPaths: string | string[], options?: any): void {
-    super.static(pathOrPaths, options);
+    loadStaticFiles(pathOrPaths, options, this.appId);
   }
 
   // Clear static files cache
   clearStaticCache(): void {
-    clearStaticCache();
+    clearStaticCache(this.appId);
   }
 
   // Get static files statistics
   getStaticStats(): string {
-    return getStaticStats();
+    return getStaticStats(this.appId);
   }
 
   // Override file operations to use addon
@@ -185,20 +227,20 @@ export class RNodeApp extends Router {
   // File upload/download
   download(path: string, options: DownloadOptions): Router {
     // Register route for file downloads in Rust backend
-    addon.registerDownloadRoute(path, JSON.stringify(options));
+    addon.registerDownloadRoute(path, JSON.stringify(options), this.appId);
     return this;
   }
 
   upload(path: string, options?: UploadOptions): Router {
     // Register route for file uploads in Rust backend
-    addon.registerUploadRoute(path, JSON.stringify(options));
+    addon.registerUploadRoute(path, JSON.stringify(options), this.appId);
     return this;
   }
 
   // Override template methods to use addon
   initTemplates(pattern: string, options: any): string {
     try {
-      return addon.initTemplates(pattern, options);
+      return addon.initTemplates(pattern, options, this.appId);
     } catch (error) {
       return JSON.stringify({
         success: false,
@@ -207,10 +249,10 @@ export class RNodeApp extends Router {
     }
   }
 
-  renderTemplate(templateName: string, context: object): string {
+  renderTemplate(templateName: string, context: object, req?: Request): string {
     try {
       const contextStr = JSON.stringify(context);
-      return addon.renderTemplate(templateName, contextStr);
+      return addon.renderTemplate(templateName, contextStr, this.appId, req?.cspNonce, req?.traceparent ?? currentTraceparent());
     } catch (error) {
       return JSON.stringify({
         success: false,
@@ -221,69 +263,119 @@ export class RNodeApp extends Router {
 
   // Get all registered routes including router routes
   getAllRoutes(): Map<string, (req: Request, res: Response) => void> {
-    // Return global handlers which include all routes from useRouter
+    // Return the app's handlers which include all routes from useRouter
     const result = new Map<string, (req: Request, res: Response) => void>();
-    for (const [key, handlerInfo] of handlers) {
+    for (const [key, handlerInfo] of this.registry.handlers) {
       result.set(key, handlerInfo.handler);
     }
     return result;
   }
 
+  // Routes added after listen() are registered in the running server immediately
+  protected onRouteRegistered(method: string, path: string): void {
+    if (!this.listening) return;
+
+    const handlerInfo = this.getHandlers().get(`${method}:${path}`);
+    if (!handlerInfo) return;
+
+    // Throws before recording the handler if the route conflicts with a registered one
+    const addonMethod = method.toLowerCase() === 'delete' ? 'del' : method.toLowerCase();
+    (addon as any)[addonMethod](path, handlerInfo.handler, this.appId, JSON.stringify(handlerInfo.options ?? {}));
+    this.registry.handlers.set(`${method}:${path}`, handlerInfo);
+    logger.debug(`🔄 Live route registered: ${method} ${path}`, 'rnode_server::router');
+  }
+
+  // Middleware goes into the native chain right away, before or after listen()
+  protected onMiddlewareRegistered(entry: MiddlewareEntry): void {
+    this.registerNativeMiddleware(entry);
+  }
+
+  // Rust matches middleware and hands the ids back to executeMiddleware
+  private registerNativeMiddleware(entry: MiddlewareEntry): void {
+    const id = addon.use(entry.path, entry.handler, this.appId, JSON.stringify({ methods: entry.methods, error: entry.isError }));
+    this.registry.middlewares.set(id, entry);
+    logger.debug(`🔧 Middleware registered: ${entry.path} (id ${id})`, 'rnode_server::router');
+  }
+
+  protected onAfterHookRegistered(entry: AfterHookEntry): void {
+    this.registerNativeAfterHook(entry);
+  }
+
+  private registerNativeAfterHook(entry: AfterHookEntry): void {
+    const id = addon.after(entry.path, entry.handler, this.appId, JSON.stringify({ methods: entry.methods }));
+    this.registry.afterHooks.set(id, entry);
+    logger.debug(`🔧 Response hook registered: ${entry.path} (id ${id})`, 'rnode_server::router');
+  }
+
+  // Remove a route, also from a running server
+  removeRoute(method: string, path: string): boolean {
+    const key = `${method.toUpperCase()}:${path}`;
+    const existed = this.registry.handlers.delete(key);
+    this.getHandlers().delete(key);
+    const removed = addon.removeRoute(method, path, this.appId);
+    logger.info(`🗑️ Route ${removed || existed ? 'removed' : 'not found'}: ${key}`, 'rnode_server::router');
+    return removed || existed;
+  }
+
+  // Remove all middleware registered for a path
+  removeMiddleware(path: string): boolean {
+    let existed = false;
+    for (const [id, entry] of this.registry.middlewares) {
+      if (entry.path === path) {
+        this.registry.middlewares.delete(id);
+        existed = true;
+      }
+    }
+    const own = this.getMiddlewareEntries();
+    own.splice(0, own.length, ...own.filter((entry) => entry.path !== path));
+    const removed = addon.removeMiddleware(path, this.appId);
+    return removed || existed;
+  }
+
   // Router methods
   useRouter(path: string, router: Router): void {
     logger.info(`🔧 Registering router for path: ${path}`, 'rnode_server::router');
 
     // Register all routes from router with prefix
     const routerHandlers = router.getHandlers();
-    const routerMiddlewares = router.getMiddlewares();
-
-    logger.debug(`📝 Router contains ${routerHandlers.size} handlers and ${routerMiddlewares.size} middleware`, 'rnode_server::router');
+    logger.debug(`📝 Router contains ${routerHandlers.size} handlers and ${router.getMiddlewareEntries().length} middleware`, 'rnode_server::router');
 
-    // Register router middleware
-    for (const [routePath, middlewareArray] of routerMiddlewares) {
+    // Register router middleware in its registration order
+    for (const entry of router.getMiddlewareEntries()) {
       let fullPath: string;
 
-      if (routePath === '*') {
+      if (entry.path === '*') {
         // Глобальный middleware для роутера - регистрируем для всех путей роутера
         fullPath = `${path}/*`;
-        logger.debug(`🔐 Registering global router middleware for: ${fullPath}`, 'rnode_server::router');
       } else {
         // Обычный middleware с путем
-        fullPath = `${path}${routePath}`;
-        logger.debug(`🔐 Registering router middleware for: ${fullPath}`, 'rnode_server::router');
+        fullPath = `${path}${entry.path}`;
       }
 
-      logger.debug(`🔍 Full path: '${fullPath}', routePath: '${routePath}', path: '${path}'`, 'rnode_server::router');
-
-      // Add to global middlewares
-      const existing = middlewares.get(fullPath) || [];
-      middlewares.set(fullPath, [...existing, ...middlewareArray]);
-
-      // Register each middleware individually in Rust addon
-      for (const middleware of middlewareArray) {
-        logger.debug(`🔧 Calling addon.use('${fullPath}', middleware)`, 'rnode_server::router');
-        addon.use(fullPath, middleware);
-      }
+      this.registerNativeMiddleware({ ...entry, path: fullPath });
+      logger.info(`✅ Registered router middleware: ${fullPath}`, 'rnode_server::router');
+    }
 
-      logger.info(`✅ Registered router middleware: ${fullPath} (${middlewareArray.length} middleware functions)`, 'rnode_server::router');
-      logger.debug(`📊 Global middlewares after registration: ${Array.from(middlewares.keys()).join(', ')}`, 'rnode_server::router');
+    // Register router response hooks
+    for (const entry of router.getAfterHookEntries()) {
+      this.registerNativeAfterHook({ ...entry, path: entry.path === '*' ? `${path}/*` : `${path}${entry.path}` });
     }
 
     // Register router handlers
     for (const [methodPath, handlerInfo] of routerHandlers) {
       const [method, routePath] = methodPath.split(':', 2);
       const fullPath = `${path}${routePath}`;
-      const { handler } = handlerInfo;
+      const { handler, options } = handlerInfo;
 
       logger.debug(`🔧 Registering handler: ${method} ${fullPath} (original path: ${routePath})`, 'rnode_server::router');
 
-      // Add to global handlers with full path
-      handlers.set(`${method}:${fullPath}`, { method, handler });
+      // Add to app handlers with full path
+      this.registry.handlers.set(`${method}:${fullPath}`, { method, handler, options });
 
       // Register in Rust addon immediately
       const addonMethod = method.toLowerCase() === 'delete' ? 'del' : method.toLowerCase();
       try {
-        (addon as any)[addonMethod](fullPath, handler);
+        (addon as any)[addonMethod](fullPath, handler, this.appId, JSON.stringify(options ?? {}));
         logger.info(`✅ Router handler registered in Rust addon: ${method} ${fullPath}`, 'rnode_server::router');
       } catch (error) {
         logger.error(`❌ Failed to register router handler in Rust addon: ${method} ${fullPath}`, 'rnode_server::router');
@@ -293,7 +385,7 @@ export class RNodeApp extends Router {
 
     logger.info(`🎯 Router registered for path: ${path}`, 'rnode_server::router');
     logger.debug(`📊 Total handlers in system: ${router.getHandlers().size}`, 'rnode_server::router');
-    logger.debug(`🔧 Global handlers updated: ${Array.from(handlers.keys()).join(', ')}`, 'rnode_server::router');
+    logger.debug(`🔧 App handlers updated: ${Array.from(this.registry.handlers.keys()).join(', ')}`, 'rnode_server::router');
   }
 
   // WebSocket methods
@@ -315,10 +407,10 @@ export class RNodeApp extends Router {
       
       // Регистрируем WebSocket роут с включенными событиями
       // События НЕ в списке будут пропущены без обработки
-      addon.registerWebSocket(path, JSON.stringify(enabledEvents));
+      addon.registerWebSocket(path, JSON.stringify(enabledEvents), this.appId);
       
-      // Сохраняем колбеки в глобальном хранилище для последующего вызова
-      websocketCallbacks.set(path, options);
+      // Сохраняем колбеки в хранилище приложения для последующего вызова
+      this.registry.websocketCallbacks.set(path, options);
       
       logger.info(`✅ WebSocket route registered: ${path}`, 'rnode_server::websocket');
       logger.debug(`🔧 Events with callbacks: ${enabledEvents.join(', ')}`, 'rnode_server::websocket');
@@ -329,6 +421,17 @@ export class RNodeApp extends Router {
     }
   }
 
+  // Server-Sent Events route: GET path streams text/event-stream from handler
+  sse(path: string, handler: SseHandler, options: SseOptions = {}): void {
+    try {
+      addon.registerSse(path, handler, JSON.stringify(options), this.appId);
+      logger.info(`✅ SSE route registered: ${path}`, 'rnode_server::sse');
+    } catch (error) {
+      logger.error(`❌ Failed to register SSE route: ${path}`, 'rnode_server::sse');
+      logger.error(`Error: ${error}`, 'rnode_server::sse');
+    }
+  }
+
   createRoom(name: string, description?: string, maxConnections?: number): string {
     try {
       const roomId = addon.createRoom(name, description, maxConnections);
@@ -463,36 +566,24 @@ export class RNodeApp extends Router {
   }
 
   listen(port: number, hostOrCallback?: string | (() => void), callback?: () => void): void {
-    // Copy app-level handlers to global system (like in old version)
+    const { handlers, middlewares } = this.registry;
+
+    // Copy app-level handlers to the app registry (like in old version)
     for (const [key, handlerInfo] of this.getHandlers()) {
       handlers.set(key, handlerInfo);
     }
 
-    // Register all global handlers (including app and router handlers) in Rust addon
+    // Register all app handlers (including app and router handlers) in Rust addon
     for (const [key, handlerInfo] of handlers) {
       const [method, path] = key.split(':', 2);
       
       // Register in Rust addon through existing methods
       const addonMethod = method.toLowerCase() === 'delete' ? 'del' : method.toLowerCase();
-      (addon as any)[addonMethod](path, handlerInfo.handler);
-    }
-
-    // Copy app-level middlewares to global system (like in old version)
-    for (const [key, middlewareArray] of this.getMiddlewares()) {
-      const existing = middlewares.get(key) || [];
-      middlewares.set(key, [...existing, ...middlewareArray]);
-    }
-
-    // Register all global middlewares in Rust addon
-    for (const [key, middlewareArray] of middlewares) {
-      for (const middleware of middlewareArray) {
-        logger.debug(`🔧 Calling addon.use('${key}', middleware) in listen`, 'rnode_server::server');
-        addon.use(key, middleware);
-      }
+      (addon as any)[addonMethod](path, handlerInfo.handler, this.appId, JSON.stringify(handlerInfo.options ?? {}));
     }
 
-    logger.debug(`🔧 Global handlers updated: ${Array.from(handlers.keys()).join(', ')}`, 'rnode_server::server');
-    logger.debug(`🔧 Global middlewares updated: ${Array.from(middlewares.keys()).join(', ')}`, 'rnode_server::server');
+    logger.debug(`🔧 App ${this.appId} handlers updated: ${Array.from(handlers.keys()).join(', ')}`, 'rnode_server::server');
+    logger.debug(`🔧 App ${this.appId} middlewares registered: ${middlewares.size}`, 'rnode_server::server');
 
     // Determine host and callback
     let host: string = "127.0.0.1";
@@ -512,7 +603,13 @@ export class RNodeApp extends Router {
       host = '0.0.0.0';
     }
 
-    const options: AppOptions = {
+    const { cors } = this.getServerOptions();
+    const options = {
+      ...this.getServerOptions(),
+      cors: cors && {
+        ...serializeCors(cors),
+        routes: Object.fromEntries(Object.entries(cors.routes ?? {}).map(([path, route]) => [path, serializeCors(route)])),
+      },
       metrics: this.getMetrics(),
       ssl: this.getSslConfig(),
       logLevel: this.getLogLevel(),
@@ -520,15 +617,34 @@ export class RNodeApp extends Router {
       devMode: this.getDevMode(),
     }
 
-    addon.listen(port, host, options);
+    addon.listen(port, host, options, this.appId);
+    this.listening = true;
+
+    // Drain the server before the process exits on SIGINT/SIGTERM
+    registerShutdownHook(this.close);
 
     if (actualCallback) {
       actualCallback();
     }
   }
+
+  // Stop accepting connections, drain in-flight requests and WebSocket sessions
+  async close(timeout: number = 10000): Promise<void> {
+    unregisterShutdownHook(this.close);
+    this.listening = false;
+    logger.info(`🛑 Closing server (drain timeout: ${timeout}ms)`, 'rnode_server::server');
+    await addon.close(timeout, this.appId);
+    logger.info('✅ Server closed', 'rnode_server::server');
+  }
+
+  // Re-read the TLS certificates from disk; new handshakes use them, open connections stay up.
+  // Throws if a file cannot be loaded, keeping the current certificates
+  reloadTls(): void {
+    addon.reloadTls(this.appId);
+  }
 }
 
 // Legacy function for backward compatibility
-export function createRNodeApp(): RNodeApp {
-  return new RNodeApp();
+export function createRNodeApp(appId: number = 0): RNodeApp {
+  return new RNodeApp(appId);
 }
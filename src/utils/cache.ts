@@ -1,6 +1,7 @@
 import * as addon from '../load.cjs';
 import { CacheManager, CacheInitConfig, CacheOptions } from '../types/cache';
 import { logger } from './logger';
+import { currentTraceparent } from './tracing';
 
 /**
  * Утилиты для работы с упрощенной системой кэширования RNode Server
@@ -59,7 +60,7 @@ class RNodeCacheManager implements CacheManager {
   
   get<T = string>(key: string, tags?: string[]): T | null {
     try {
-      const value = addon.cacheGet(key, tags || []);
+      const value = addon.cacheGet(key, tags || [], currentTraceparent());
       if (value === null) {
         logger.debug(`❌ Cache miss: ${key}`, 'rnode_server::cache');
         return null;
@@ -87,7 +88,7 @@ class RNodeCacheManager implements CacheManager {
     try {
       const stringValue = typeof value === 'string' ? value : JSON.stringify(value);
       
-      const success = addon.cacheSet(key, stringValue, options?.tags || [], options?.ttl || 0);
+      const success = addon.cacheSet(key, stringValue, options?.tags || [], options?.ttl || 0, currentTraceparent());
       
       if (success) {
         logger.debug(`✅ Cache set: ${key}`, 'rnode_server::cache');
@@ -104,7 +105,7 @@ class RNodeCacheManager implements CacheManager {
 
   delete(key: string, tags?: string[]): boolean {
     try {
-      const deleted = addon.cacheDelete(key, tags || []);
+      const deleted = addon.cacheDelete(key, tags || [], currentTraceparent());
       
       if (deleted) {
         logger.debug(`✅ Cache delete: ${key}`, 'rnode_server::cache');
@@ -121,7 +122,7 @@ class RNodeCacheManager implements CacheManager {
 
   exists(key: string, tags?: string[]): boolean {
     try {
-      const exists = addon.cacheExists(key, tags || []);
+      const exists = addon.cacheExists(key, tags || [], currentTraceparent());
       logger.debug(`🔍 Cache exists check for key ${key}: ${exists}`, 'rnode_server::cache');
       return exists;
     } catch (error) {
@@ -132,7 +133,7 @@ class RNodeCacheManager implements CacheManager {
 
   clear(): boolean {
     try {
-      const success = addon.cacheClear();
+      const success = addon.cacheClear(currentTraceparent());
       
       if (success) {
         logger.info('✅ Cache cleared successfully', 'rnode_server::cache');
@@ -149,7 +150,7 @@ class RNodeCacheManager implements CacheManager {
 
   flushByTags(tags: string[]): number {
     try {
-      const count = addon.cacheFlushByTags(tags);
+      const count = addon.cacheFlushByTags(tags, currentTraceparent());
       logger.info(`🏷️ Flushed ${count} items by tags: ${tags.join(', ')}`, 'rnode_server::cache');
       return count;
     } catch (error) {
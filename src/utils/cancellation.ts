@@ -0,0 +1,25 @@
+import { logger } from './logger';
+
+// AbortControllers of requests JS is working on, by the cancelId Rust assigned
+const pending = new Map<number, AbortController>();
+
+// Keep a request abortable by Rust until the returned function is called
+export function trackRequest(cancelId: number | undefined, controller: AbortController): () => void {
+  if (!cancelId) return () => {};
+  pending.set(cancelId, controller);
+  return () => {
+    if (pending.get(cancelId) === controller) pending.delete(cancelId);
+  };
+}
+
+// Called by Rust once it stops waiting: the route timed out or the client disconnected
+export function abortRequest(cancelId: number, reason: string): void {
+  const controller = pending.get(cancelId);
+  if (!controller) return;
+  pending.delete(cancelId);
+
+  logger.debug(`🛑 Request ${cancelId} aborted: ${reason}`, 'rnode_server::handler');
+  controller.abort(reason === 'timeout'
+    ? new DOMException('Request timed out', 'TimeoutError')
+    : new DOMException('Client disconnected', 'AbortError'));
+}
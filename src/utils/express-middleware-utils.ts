@@ -33,6 +33,7 @@ export function createExpressMiddlewareWrapper(
 
 export function createExpressErrorMiddlewareWrapper(
   expressErrorMiddleware: ExpressErrorMiddleware,
+  err: any,
   req: Request,
   res: Response,
   next: (error?: any) => void
@@ -50,7 +51,7 @@ export function createExpressErrorMiddlewareWrapper(
     };
 
     // Execute Express error middleware
-    expressErrorMiddleware(null, expressReq, expressRes, expressNext);
+    expressErrorMiddleware(err, expressReq, expressRes, expressNext);
   } catch (error) {
     // If error middleware fails, continue
     next(error);
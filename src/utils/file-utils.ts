@@ -121,7 +121,7 @@ export function fileExists(filename: string, uploadsDir: string): boolean {
   }
 }
 
-export function loadStaticFiles(pathOrPaths: string | string[], options?: StaticOptions): void {
+export function loadStaticFiles(pathOrPaths: string | string[], options?: StaticOptions, appId?: number): void {
   // Default settings
   const defaultOptions: StaticOptions = {
     cache: options?.cache ?? true,
@@ -142,20 +142,20 @@ export function loadStaticFiles(pathOrPaths: string | string[], options?: Static
   if (Array.isArray(pathOrPaths)) {
     // Multiple paths
     for (const path of pathOrPaths) {
-      addon.loadStaticFiles(path, defaultOptions);
+      addon.loadStaticFiles(path, defaultOptions, appId);
       logger.debug(`Registered static files from: ${path} with secure options:`, JSON.stringify(defaultOptions));
     }
   } else {
     // Single path
-    addon.loadStaticFiles(pathOrPaths, defaultOptions);
+    addon.loadStaticFiles(pathOrPaths, defaultOptions, appId);
     logger.debug(`Registered static files from: ${pathOrPaths} with secure options:`, JSON.stringify(defaultOptions));
   }
 }
 
-export function initTemplates(pattern: string, options: TemplateOptions): string {
+export function initTemplates(pattern: string, options: TemplateOptions, appId?: number): string {
   try {
     // Call Rust addon to initialize templates
-    const result = addon.initTemplates(pattern, options);
+    const result = addon.initTemplates(pattern, options, appId);
     logger.debug(`✅ Templates initialized with pattern: ${pattern}`);
     return result;
   } catch (error) {
@@ -164,21 +164,21 @@ export function initTemplates(pattern: string, options: TemplateOptions): string
   }
 }
 
-export function renderTemplate(templateName: string, context: object): string {
+export function renderTemplate(templateName: string, context: object, appId?: number, cspNonce?: string, traceparent?: string): string {
   try {
     // Call Rust addon to render template
-    return addon.renderTemplate(templateName, JSON.stringify(context));
+    return addon.renderTemplate(templateName, JSON.stringify(context), appId, cspNonce, traceparent);
   } catch (error) {
     logger.error('❌ Error rendering template:', error instanceof Error ? error.message : String(error));
     return `<!-- Template rendering error: ${templateName} -->`;
   }
 }
 
-export function clearStaticCache(): void {
-  addon.clearStaticCache();
+export function clearStaticCache(appId?: number): void {
+  addon.clearStaticCache(appId);
   logger.info('🗑️ Static files cache cleared', 'rnode_server::static');
 }
 
-export function getStaticStats(): string {
-  return addon.getStaticStats();
+export function getStaticStats(appId?: number): string {
+  return addon.getStaticStats(appId);
 }
@@ -1,38 +1,66 @@
 import { logger } from './logger';
+import type { RouteOptions } from '../types/app-router';
 import { Request } from './request';
 import { Response } from './response';
-import { getHandler } from './handler-utils';
+import { executeHandler, pipeResponseStream } from './handler-utils';
 import { executeMiddleware } from './middleware-utils';
 import { executeWebSocketEvent } from './websocket-utils';
-import { setupGracefulShutdown } from './shutdown-utils';
+import { executeSse, closeSseConnection } from './sse-utils';
 import { WebSocketOptions } from '../types/websocket';
+import type { MiddlewareEntry, AfterHookEntry } from './middleware';
+import { executeResponseHooks } from './response-hooks-utils';
+import { abortRequest } from './cancellation';
 
 // Global variables for handlers and middlewares (like in old version)
-export const handlers = new Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any> }>();
-export const middlewares = new Map<string, ((req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>)[]>();
+export const handlers = new Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }>();
+// Middleware by the id the native chain assigned
+export const middlewares = new Map<number, MiddlewareEntry>();
+export const afterHooks = new Map<number, AfterHookEntry>();
 
 // Global variable for WebSocket callbacks
 export const websocketCallbacks = new Map<string, WebSocketOptions>();
 
+// Handlers, middlewares and WebSocket callbacks of one app instance
+export interface AppRegistry {
+  handlers: Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }>;
+  middlewares: Map<number, MiddlewareEntry>;
+  afterHooks: Map<number, AfterHookEntry>;
+  websocketCallbacks: Map<string, WebSocketOptions>;
+}
+
+// Registries by app id; app 0 uses the global maps above
+const appRegistries = new Map<number, AppRegistry>([[0, { handlers, middlewares, afterHooks, websocketCallbacks }]]);
+
+export function getAppRegistry(appId: number = 0): AppRegistry {
+  let registry = appRegistries.get(appId);
+  if (!registry) {
+    registry = { handlers: new Map(), middlewares: new Map(), afterHooks: new Map(), websocketCallbacks: new Map() };
+    appRegistries.set(appId, registry);
+  }
+  return registry;
+}
+
 export function setupGlobalFunctions(): void {
   // Export functions for Rust - they are synchronous but return promises
-  (global as any).getHandler = (requestJson: string, timeout: number) => {
-    logger.debug('🔍 Rust called getHandler with:', requestJson.substring(0, 100) + '...');
-    return getHandler(requestJson, timeout);
+  (global as any).executeHandler = (handler: (req: Request, res: Response) => void | Promise<any>, requestJson: string, timeout: number, readBody?: () => Promise<Buffer | null>) => {
+    logger.debug('🔍 Rust called executeHandler with:', requestJson.substring(0, 100) + '...');
+    return executeHandler(handler, requestJson, timeout, readBody);
   };
+  (global as any).pipeResponseStream = pipeResponseStream;
   (global as any).executeMiddleware = (middlewareJson: string, timeout: number) => {
     logger.debug('🔍 Rust called executeMiddleware with:', middlewareJson.substring(0, 100) + '...');
     return executeMiddleware(middlewareJson, timeout)
   };
+  (global as any).executeResponseHooks = executeResponseHooks;
+  (global as any).abortRequest = abortRequest;
 
   (global as any).executeWebSocketEvent = (eventJson: string, timeout: number) => {
     logger.debug('🔌 Rust called executeWebSocketEvent with:', eventJson.substring(0, 100) + '...');
     return executeWebSocketEvent(eventJson, timeout);
   };
 
-
-  // Setup graceful shutdown
-  setupGracefulShutdown();
+  (global as any).executeSse = executeSse;
+  (global as any).closeSseConnection = closeSseConnection;
 
   logger.debug('✅ Global functions and variables initialized', 'rnode_server::global');
 }
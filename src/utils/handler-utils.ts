@@ -1,15 +1,90 @@
 import { logger } from './logger';
 import { createRequestObject, createResponseObject } from './request-response-factory';
-import { handlers } from './global-utils';
+import { Request } from './request';
+import { Response } from './response';
+import { Readable } from 'stream';
+import { runErrorMiddleware } from './middleware-utils';
+import { runWithTrace } from './tracing';
+import { trackRequest } from './cancellation';
 
-export async function getHandler(requestJson: string, timeout: number): Promise<string> {
-  logger.debug('🔍 getHandler function called with requestJson length:' + requestJson.length);
+type RouteHandler = (req: Request, res: Response) => void | Promise<any>;
+
+type HandlerResult = string | { meta: string; body?: Buffer; stream?: AsyncIterable<any> };
+
+// Serialize the response; binary and streamed bodies are passed to Rust next to the JSON
+function buildHandlerResult(res: Response, customParams: any): HandlerResult {
+  const content = res.content;
+  const meta = {
+    content: typeof content === 'string' ? content : '',
+    contentType: res.contentType,
+    headers: res.headers,
+    status: res.currentStatus,
+    customParams: customParams
+  };
+
+  if (res.bodyStream) {
+    return { meta: JSON.stringify({ ...meta, contentLength: res.contentLength }), stream: res.bodyStream };
+  }
+  if (content instanceof Uint8Array) {
+    const body = Buffer.isBuffer(content) ? content : Buffer.from(content.buffer, content.byteOffset, content.byteLength);
+    return { meta: JSON.stringify(meta), body };
+  }
+  return JSON.stringify(meta);
+}
+
+// Pass a handler failure to the error middleware Rust matched for the route; null if none answered
+async function handleError(request: any, error: any, req: Request, res: Response, timeout: number, customParams: any): Promise<HandlerResult | null> {
+  const ids: number[] = request.errorMiddlewareIds || [];
+  if (ids.length === 0) return null;
+
+  const handled = await runErrorMiddleware(request.appId ?? 0, ids, error, req, res, timeout);
+  return handled ? buildHandlerResult(res, customParams) : null;
+}
+
+// Pump a response stream into Rust; write() resolves to false once the client is gone
+export async function pipeResponseStream(
+  stream: AsyncIterable<any>,
+  write: (chunk: Buffer | null, error?: string) => Promise<boolean>
+): Promise<void> {
+  try {
+    for await (const chunk of stream) {
+      const data = typeof chunk === 'string'
+        ? Buffer.from(chunk)
+        : Buffer.isBuffer(chunk) ? chunk : Buffer.from(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+      if (!(await write(data))) {
+        logger.debug('🔌 Client closed the response stream', 'rnode_server::handler');
+        (stream as any).destroy?.();
+        return;
+      }
+    }
+    await write(null);
+  } catch (error: any) {
+    logger.error(`❌ Response stream error: ${error}`, 'rnode_server::handler');
+    await write(null, error?.message || String(error));
+  }
+}
+
+// Readable over the native body reader: each read() resolves to a Buffer or null at the end
+export function createBodyStream(readBody: () => Promise<Buffer | null>): Readable {
+  return new Readable({
+    read() {
+      readBody().then(
+        (chunk) => this.push(chunk),
+        (error) => this.destroy(error instanceof Error ? error : new Error(String(error)))
+      );
+    }
+  });
+}
+
+// Runs the route handler Rust matched for the request
+export async function executeHandler(routeHandler: RouteHandler, requestJson: string, timeout: number, readBody?: () => Promise<Buffer | null>): Promise<HandlerResult> {
+  logger.debug('🔍 executeHandler function called with requestJson length:' + requestJson.length);
   
   try {
     const request = JSON.parse(requestJson);
     const { method, path, registeredPath, pathParams, queryParams, body, cookies, headers, ip, ips, ipSource } = request;
 
-    logger.debug('🔍 getHandler called:', 'rnode_server::handler');
+    logger.debug('🔍 executeHandler called:', 'rnode_server::handler');
     logger.debug(`  Method: ${method}`, 'rnode_server::handler');
     logger.debug(`  Path: ${path}`, 'rnode_server::handler');
     logger.debug(`  RegisteredPath: ${registeredPath}`, 'rnode_server::handler');
@@ -23,37 +98,22 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
     logger.debug(`  IPs: ${JSON.stringify(ips)}`, 'rnode_server::handler');
     logger.debug(`  IPSource: ${ipSource}`, 'rnode_server::handler');
 
-    // Access global handlers from global scope
-    if (!handlers) {
-      logger.error('❌ Global handlers not initialized.', 'rnode_server::handler');
-      return JSON.stringify({
-        content: 'Internal Server Error: Handlers not initialized',
-        contentType: 'text/plain'
-      });
-    }
-    logger.debug(`  Available handlers: ${Array.from(handlers.keys()).join(', ')}`, 'rnode_server::handler');
-
-    // Search for handler by registered path
-    const handlerKey = `${method}:${registeredPath}`;
-    const handler = handlers.get(handlerKey);
-
-    if (handler) {
-      logger.debug(`✅ Handler found for: ${handlerKey}`, 'rnode_server::handler');
+    if (typeof routeHandler === 'function') {
+      logger.debug(`✅ Handler received for: ${method}:${registeredPath}`, 'rnode_server::handler');
 
       // Get parameters from previous calls
       const customParams = request.customParams || {};
 
       const req = createRequestObject(request);
       const res = createResponseObject(cookies);
+      if (readBody) {
+        req.bodyStream = createBodyStream(readBody);
+      }
 
-      // Execute handler
+      // Execute handler; Rust aborts req.abortSignal if it times out or the client disconnects
+      const untrack = trackRequest(request.cancelId, req.abortController);
       try {
-        // Set timeout to abort the operation using existing abortController
-        const timeoutId = setTimeout(() => {
-          req.abortController?.abort();
-        }, timeout);
-        
-        const result = handler.handler(req, res);
+        const result = runWithTrace(req.traceparent, () => routeHandler(req, res));
         
         // Check if handler returned a promise
         if (result !== undefined && result !== null && typeof result === 'object' && typeof result.then === 'function') {
@@ -66,8 +126,7 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
             
             // Check if operation was aborted
             if (req.abortController?.signal.aborted) {
-              logger.warn('⚠️ Handler was aborted due to timeout', 'rnode_server::handler');
-              clearTimeout(timeoutId);
+              logger.warn(`⚠️ Handler was aborted: ${req.abortSignal?.reason?.message ?? 'timeout'}`, 'rnode_server::handler');
               return JSON.stringify({
                 content: `Handler timeout after ${timeout}ms`,
                 contentType: 'text/plain',
@@ -77,27 +136,17 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
               });
             }
             
-            // Clear timeout since operation completed successfully
-            clearTimeout(timeoutId);
-            
             // Update response with resolved result if it's a Response object
             if (resolvedResult && typeof resolvedResult === 'object') {
               // For now, just log the resolved result
               logger.debug(`✅ Promise resolved with result: ${JSON.stringify(resolvedResult)}`, 'rnode_server::handler');
             }
 
-            return JSON.stringify({
-              content: res.content,
-              contentType: res.contentType,
-              headers: res.headers,
-              status: res.currentStatus,
-              customParams: customParams
-            });
+            return buildHandlerResult(res, customParams);
           } catch (error: any) {
-            // Clear timeout on error
-            clearTimeout(timeoutId);
-            
             logger.error(`❌ Promise rejected: ${error}`, 'rnode_server::handler');
+            const handled = await handleError(request, error, req, res, timeout, customParams);
+            if (handled) return handled;
             return JSON.stringify({
               content: error.message || 'Handler execution failed',
               contentType: 'text/plain',
@@ -106,12 +155,9 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
             });
           }
         } else {
-          // Synchronous result - clear timeout
-          clearTimeout(timeoutId);
-          
           // Check if operation was aborted
           if (req.abortController?.signal.aborted) {
-            logger.warn('⚠️ Handler was aborted due to timeout', 'rnode_server::handler');
+            logger.warn(`⚠️ Handler was aborted: ${req.abortSignal?.reason?.message ?? 'timeout'}`, 'rnode_server::handler');
             return JSON.stringify({
               content: `Handler timeout after ${timeout}ms`,
               contentType: 'text/plain',
@@ -120,25 +166,23 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
           }
 
           // Synchronous result
-          return JSON.stringify({
-            content: res.content,
-            contentType: res.contentType,
-            headers: res.headers,
-            status: res.currentStatus,
-            customParams: customParams
-          });
+          return buildHandlerResult(res, customParams);
         }
       } catch (error: any) {
         logger.error(`❌ Handler execution error: ${error}`, 'rnode_server::handler');
+        const handled = await handleError(request, error, req, res, timeout, customParams);
+        if (handled) return handled;
         return JSON.stringify({
           content: 'Internal Server Error',
           contentType: 'text/plain',
           status: 500
         });
+      } finally {
+        untrack();
       }
     }
 
-    // Only return "Not Found" if no handler was found
+    // Only return "Not Found" if no handler was passed
     return JSON.stringify({
       content: 'Not Found',
       contentType: 'text/plain',
@@ -146,7 +190,7 @@ export async function getHandler(requestJson: string, timeout: number): Promise<
     });
     
   } catch (error: any) {
-    logger.error(`❌ getHandler error: ${error}`, 'rnode_server::handler');
+    logger.error(`❌ executeHandler error: ${error}`, 'rnode_server::handler');
     return JSON.stringify({
       content: 'Invalid request JSON',
       contentType: 'text/plain',
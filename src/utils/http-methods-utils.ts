@@ -23,12 +23,9 @@ export function registerMiddleware(
   path: string, 
   middleware: (req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>
 ): void {
-  // Add to global middlewares
-  const existing = middlewares.get(path) || [];
-  middlewares.set(path, [...existing, middleware]);
-
-  // Register in Rust addon
-  addon.use(path, middleware);
+  // Register in Rust addon and keep it under the id the native chain assigned
+  const id = addon.use(path, middleware);
+  middlewares.set(id, { path, handler: middleware, isError: false });
 
   logger.debug(`✅ Registered middleware for path: ${path}`, 'rnode_server::http_methods');
 }
@@ -36,12 +33,9 @@ export function registerMiddleware(
 export function registerGlobalMiddleware(
   middleware: (req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>
 ): void {
-  // Add to global middlewares with '*' pattern
-  const existing = middlewares.get('*') || [];
-  middlewares.set('*', [...existing, middleware]);
-
   // Register in Rust addon with '*' pattern
-  addon.use('*', middleware);
+  const id = addon.use('*', middleware);
+  middlewares.set(id, { path: '*', handler: middleware, isError: false });
 
   logger.debug(`✅ Registered global middleware`, 'rnode_server::http_methods');
 }
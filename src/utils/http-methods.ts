@@ -1,73 +1,85 @@
 import { logger } from './logger';
+import type { RouteOptions } from '../types/app-router';
 import type { Request } from './request';
 import type { Response } from './response';
 import { registerHttpMethod } from './http-methods-utils';
 
 export interface HttpMethodsUtils {
-  get(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  post(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  put(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  delete(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  patch(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  options(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  trace(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
-  any(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): HttpMethodsUtils;
+  get(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  post(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  put(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  delete(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  patch(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  options(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  trace(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
+  any(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): HttpMethodsUtils;
 }
 
-export function createHttpMethodsUtils(handlers: Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any> }>): HttpMethodsUtils {
+export function createHttpMethodsUtils(
+  handlers: Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }>,
+  onRegister?: (method: string, path: string) => void
+): HttpMethodsUtils {
   return {
-    get(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    get(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`GET:${path}`, { method: 'GET', handler });
+      handlers.set(`GET:${path}`, { method: 'GET', handler, options });
+      onRegister?.('GET', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    post(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    post(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`POST:${path}`, { method: 'POST', handler });
+      handlers.set(`POST:${path}`, { method: 'POST', handler, options });
+      onRegister?.('POST', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    put(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    put(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`PUT:${path}`, { method: 'PUT', handler });
+      handlers.set(`PUT:${path}`, { method: 'PUT', handler, options });
+      onRegister?.('PUT', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    delete(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    delete(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`DELETE:${path}`, { method: 'DELETE', handler });
+      handlers.set(`DELETE:${path}`, { method: 'DELETE', handler, options });
+      onRegister?.('DELETE', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    patch(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    patch(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`PATCH:${path}`, { method: 'PATCH', handler });
+      handlers.set(`PATCH:${path}`, { method: 'PATCH', handler, options });
+      onRegister?.('PATCH', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    options(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    options(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`OPTIONS:${path}`, { method: 'OPTIONS', handler });
+      handlers.set(`OPTIONS:${path}`, { method: 'OPTIONS', handler, options });
+      onRegister?.('OPTIONS', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    trace(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    trace(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`TRACE:${path}`, { method: 'TRACE', handler });
+      handlers.set(`TRACE:${path}`, { method: 'TRACE', handler, options });
+      onRegister?.('TRACE', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     },
 
-    any(path: string, handler: (req: Request, res: Response) => void | Promise<any>) {
+    any(path: string, handler: (req: Request, res: Response) => void | Promise<any>, options?: RouteOptions) {
       // Store in local handlers map (like in old version)
-      handlers.set(`ANY:${path}`, { method: 'ANY', handler });
+      handlers.set(`ANY:${path}`, { method: 'ANY', handler, options });
+      onRegister?.('ANY', path);
       // Don't register in global system here - it will be done in useRouter
       return this;
     }
@@ -1,177 +1,185 @@
 import { logger } from './logger';
 import { createRequestObject, createResponseObject } from './request-response-factory';
-import { middlewares } from './global-utils';
-import micromatch from "micromatch";
+import { getAppRegistry } from './global-utils';
+import type { MiddlewareEntry } from './middleware';
+import type { Middleware, ErrorMiddleware } from './router';
+import { Request } from './request';
+import { Response } from './response';
+import { runWithTrace } from './tracing';
+import { trackRequest } from './cancellation';
+
+// State of a chain once it stops or runs out of middleware
+interface ChainResult {
+  error: any; // Failure no error middleware handled, null if none
+  responded: boolean; // A middleware wrote a response without calling next()
+}
+
+// A middleware wrote something the client should get instead of the route's response
+function hasResponse(res: Response): boolean {
+  return res.bodyStream !== null || res.currentStatus !== 200 || res.content.length > 0;
+}
+
+// Error for a middleware that ran past its timeout; Rust counts it as a cancelled request
+function middlewareTimeout(limit: number): Error {
+  return Object.assign(new Error(`Middleware timeout after ${limit}ms`), { status: 408, code: 'ETIMEDOUT' });
+}
+
+// Run one middleware; resolves with the error it passed to next() or threw
+async function runMiddleware(
+  entry: MiddlewareEntry,
+  error: any,
+  req: Request,
+  res: Response,
+  timeout: number
+): Promise<{ nextCalled: boolean; error: any }> {
+  let nextCalled = false;
+  let nextError: any = null;
+  const next = (err?: any) => {
+    nextCalled = true;
+    if (err) {
+      logger.error(`❌ Middleware error: ${err?.message || err}`, 'rnode_server::middleware');
+      nextError = err;
+    }
+  };
+
+  // The middleware's own timeout, within what is left of the request's
+  const limit = Math.min(entry.timeout ?? timeout, timeout);
+  let timedOut = false;
+  let timeoutId: NodeJS.Timeout | undefined;
+  const deadline = new Promise<undefined>((resolve) => {
+    timeoutId = setTimeout(() => {
+      timedOut = true;
+      req.abortController?.abort(new DOMException(`Middleware timeout after ${limit}ms`, 'TimeoutError'));
+      resolve(undefined);
+    }, limit);
+  });
+
+  try {
+    const result = runWithTrace(req.traceparent, () => entry.isError
+      ? (entry.handler as ErrorMiddleware)(error, req, res, next)
+      : (entry.handler as Middleware)(req, res, next));
+
+    // Check if middleware returned a promise
+    if (result !== undefined && result !== null && typeof result === 'object' && typeof (result as any).then === 'function') {
+      // Stop waiting once the middleware is out of time; it sees the aborted req.abortSignal
+      const resolvedResult = await Promise.race([result, deadline]);
+
+      // If middleware returned a result object, update req and res
+      if (resolvedResult && typeof resolvedResult === 'object') {
+        if (resolvedResult.req) {
+          Object.assign(req, resolvedResult.req);
+        }
+        if (resolvedResult.res) {
+          Object.assign(res, resolvedResult.res);
+        }
+      }
+    }
+  } catch (thrown: any) {
+    logger.error(`❌ Middleware execution error: ${thrown instanceof Error ? thrown.message : String(thrown)}`, 'rnode_server::middleware');
+    return { nextCalled: true, error: thrown ?? new Error('Middleware error') };
+  } finally {
+    clearTimeout(timeoutId);
+  }
+
+  // Check if operation was aborted
+  if (timedOut) {
+    logger.warn(`⚠️ Middleware timed out after ${limit}ms: ${entry.path}`, 'rnode_server::middleware');
+    return { nextCalled: true, error: middlewareTimeout(limit) };
+  }
+  if (req.abortController?.signal.aborted) {
+    logger.warn(`⚠️ Middleware was aborted: ${req.abortSignal?.reason?.message ?? 'timeout'}`, 'rnode_server::middleware');
+    return { nextCalled: true, error: middlewareTimeout(timeout) };
+  }
+
+  return { nextCalled, error: nextError };
+}
+
+// Run middleware in order: regular middleware until something fails, then error middleware
+async function runChain(entries: MiddlewareEntry[], error: any, req: Request, res: Response, timeout: number): Promise<ChainResult> {
+  for (const entry of entries) {
+    // Error middleware only runs while there is an error, regular middleware only while there is none
+    if (entry.isError !== (error !== null)) continue;
 
+    logger.debug(`🔄 Executing ${entry.isError ? 'error ' : ''}middleware: ${entry.path}`, 'rnode_server::middleware');
+    const outcome = await runMiddleware(entry, error, req, res, timeout);
 
+    if (outcome.error !== null) {
+      error = outcome.error;
+      continue;
+    }
+    if (entry.isError && outcome.nextCalled) {
+      // next() without an error: the error is handled, carry on
+      error = null;
+      continue;
+    }
+    if (!outcome.nextCalled && hasResponse(res)) {
+      logger.debug(`🛑 Middleware answered the request: ${entry.path}`, 'rnode_server::middleware');
+      return { error: null, responded: true };
+    }
+  }
+
+  return { error, responded: false };
+}
+
+// Middleware of an app by the ids Rust matched, skipping removed ones
+function resolveEntries(appId: number, ids: number[]): MiddlewareEntry[] {
+  const { middlewares } = getAppRegistry(appId);
+  return ids
+    .map((id) => middlewares.get(id))
+    .filter((entry): entry is MiddlewareEntry => entry !== undefined);
+}
+
+// Pass a route handler failure to the error middleware Rust matched; true if one of them answered
+export async function runErrorMiddleware(appId: number, ids: number[], error: any, req: Request, res: Response, timeout: number): Promise<boolean> {
+  const result = await runChain(resolveEntries(appId, ids), error, req, res, timeout);
+  return result.responded;
+}
 
 export async function executeMiddleware(middlewareJson: string, timeout: number): Promise<string> {
   logger.debug('🔍 executeMiddleware function called with middlewareJson length:' + middlewareJson.length);
-  
+
   try {
-    const request = JSON.parse(middlewareJson);
+    const { middlewareIds = [], ...request } = JSON.parse(middlewareJson);
     logger.debug(`🔍 executeMiddleware called with path: ${request.path}`, 'rnode_server::middleware');
 
     // Create req and res objects at function level
     const req = createRequestObject(request);
     const res = createResponseObject(request.cookies);
 
-    // Search for suitable middleware
-    // Note: This function will be called from Rust, so we need to access global middlewares
-    if (!middlewares) {
-      logger.error('❌ Global middlewares not initialized.', 'rnode_server::middleware');
+    // Rust matched the middleware for this request; run them in registration order
+    const entries = resolveEntries(request.appId ?? 0, middlewareIds);
+    logger.debug(`✅ Executing ${entries.length} middleware for: ${request.path}`, 'rnode_server::middleware');
+
+    // Rust aborts req.abortSignal if the request times out or the client disconnects
+    const untrack = trackRequest(request.cancelId, req.abortController);
+    const { error, responded } = await runChain(entries, null, req, res, timeout).finally(untrack);
+
+    if (error !== null) {
+      logger.debug(`❌ Unhandled middleware error: ${error?.message || error}`, 'rnode_server::middleware');
       return JSON.stringify({
         shouldContinue: false,
-        error: 'Internal Server Error: Middlewares not initialized',
-        req: {...request},
-        res: { headers: {}, content: '', contentType: 'text/plain' }
+        error: error?.message || String(error),
+        status: error?.status ?? error?.statusCode,
+        timedOut: error?.code === 'ETIMEDOUT',
+        req: {...req},
+        res: {...res}
       });
     }
 
-    logger.debug(`🔍 Available middleware patterns: ${Array.from(middlewares.keys()).join(', ')}`, 'rnode_server::middleware');
-
-    for (const [middlewarePath, middlewareArray] of middlewares) {
-      logger.debug(`🔍 Checking middleware pattern: ${middlewarePath} against path: ${request.path}`);
-
-      let matches = false;
-
-      if (middlewarePath === '*') {
-        // Global middleware matches everything
-        matches = true;
-        logger.debug('✅ Global middleware (*) matches');
-      } else {
-        matches = micromatch.isMatch(request.path, middlewarePath);
-        logger.debug(`🔍 Micromatch check: ${request.path} matches ${middlewarePath} -> ${matches}`);
-      }
-
-      // Execute middleware for this pattern
-      if (matches) {
-        logger.debug(`✅ Executing ${middlewareArray.length} middleware for pattern: ${middlewarePath}`);
-
-        // Execute all middleware for this path
-        logger.debug(`🔧 Starting with params: ${JSON.stringify(req.customParams)}`, 'rnode_server::middleware');
-
-        for (let i = 0; i < middlewareArray.length; i++) {
-          const middleware = middlewareArray[i];
-          logger.debug(`🔄 Executing middleware ${i + 1} of ${middlewareArray.length}`);
-
-          try {
-            // Set timeout to abort the operation using existing abortController
-            const timeoutId = setTimeout(() => {
-              req.abortController?.abort();
-            }, timeout);
-            
-            // Call middleware function with req and res objects
-            let middlewareError: any = null;
-
-            logger.debug(`🔍 Executing middleware for pattern: ${middlewarePath}`);
-            logger.debug(`🔍 Request origin: ${req.headers.origin}`);
-
-            const result = middleware(req, res, (error?: any) => {
-              // Next function - continue to next middleware
-              if (error) {
-                // If middleware throws an error, stop execution and return error
-                logger.error('❌ Middleware error:', error);
-                middlewareError = error;
-              } else {
-                logger.debug('✅ Middleware completed without error');
-              }
-            });
-
-            // Check if middleware returned a promise
-            if (result !== undefined && result !== null && typeof result === 'object' && typeof (result as any).then === 'function') {
-              // Middleware returned a promise - wait for it to resolve
-              logger.debug('🔄 Middleware returned promise, waiting for resolution', 'rnode_server::middleware');
-              
-              try {
-                const resolvedResult = await result;
-                logger.debug('✅ Middleware promise resolved successfully', 'rnode_server::middleware');
-                
-                // Check if operation was aborted
-                if (req.abortController?.signal.aborted) {
-                  logger.warn('⚠️ Middleware was aborted due to timeout', 'rnode_server::middleware');
-                  clearTimeout(timeoutId);
-                  return JSON.stringify({
-                    shouldContinue: false,
-                    error: `Middleware timeout after ${timeout}ms`,
-                    status: 408, // Request Timeout
-                    errorType: 'timeout',
-                    timeout: timeout,
-                    req: {...req},
-                    res: {...res}
-                  });
-                }
-                
-                // Clear timeout since operation completed successfully
-                clearTimeout(timeoutId);
-                
-                // If middleware returned a result object, update req and res
-                if (resolvedResult && typeof resolvedResult === 'object') {
-                  if (resolvedResult.req) {
-                    Object.assign(req, resolvedResult.req);
-                  }
-                  if (resolvedResult.res) {
-                    Object.assign(res, resolvedResult.res);
-                  }
-                }
-              } catch (error: any) {
-                // Clear timeout on error
-                clearTimeout(timeoutId);
-                
-                logger.error(`❌ Middleware promise rejected: ${error}`, 'rnode_server::middleware');
-                return JSON.stringify({
-                  shouldContinue: false,
-                  error: error.message || 'Middleware timeout',
-                  req: {...req},
-                  res: {...res}
-                });
-              }
-            } else {
-              // Synchronous result - clear timeout
-              clearTimeout(timeoutId);
-              
-              // Check if operation was aborted
-              if (req.abortController?.signal.aborted) {
-                logger.warn('⚠️ Middleware was aborted due to timeout', 'rnode_server::middleware');
-                return JSON.stringify({
-                  shouldContinue: false,
-                  error: `Middleware timeout after ${timeout}ms`,
-                  req: {...req},
-                  res: {...res}
-                });
-              }
-            }
-
-            // Check if middleware returned an error
-            if (middlewareError) {
-              logger.debug('❌ Middleware returned error, stopping execution');
-              logger.debug(`❌ Error details: ${middlewareError.message || middlewareError}`);
-              return JSON.stringify({
-                shouldContinue: false,
-                error: middlewareError.message || middlewareError.toString(),
-                req: {...req},
-                res: {...res}
-              });
-            }
-
-            // Update accumulated params for next middleware
-            logger.debug(`🔧 Updated params: ${JSON.stringify(req.customParams)}`, 'rnode_server::middleware');
-          } catch (error: any) {
-            logger.error(`❌ Middleware execution error: ${error instanceof Error ? error.message : String(error)}`, 'rnode_server::middleware');
-            return JSON.stringify({
-              shouldContinue: false,
-              error: error instanceof Error ? error.message : String(error),
-              req: {...req},
-              res: {...res}
-            });
-          }
-        }
-      }
+    if (responded) {
+      const content = res.content;
+      return JSON.stringify({
+        shouldContinue: false,
+        content: typeof content === 'string' ? content : content.toString(),
+        contentType: res.contentType,
+        status: res.currentStatus,
+        headers: res.getHeaders()
+      });
     }
 
     // All middleware executed successfully
     logger.debug('✅ All middleware executed successfully, continuing');
-    
+
     // Always return accumulated parameters, even when continuing
     // Create req and res objects with accumulated data
     const finalReq = {
@@ -193,13 +201,13 @@ export async function executeMiddleware(middlewareJson: string, timeout: number)
       req: finalReq,
       res: finalRes
     });
-    
+
     logger.debug(`🔧 Returning middleware result: ${result.substring(0, 200)}...`, 'rnode_server::middleware');
     return result;
-    
+
   } catch (error: any) {
     logger.error(`❌ executeMiddleware error: ${error instanceof Error ? error.message : String(error)}`, 'rnode_server::middleware');
-    
+
     return JSON.stringify({
       shouldContinue: false,
       error: error instanceof Error ? error.message : String(error),
@@ -1,28 +1,67 @@
 import { logger } from './logger';
-import { Request } from './request';
-import { Response } from './response';
-import { registerMiddleware, registerGlobalMiddleware } from './http-methods-utils';
+import type { MiddlewareOptions } from '../types/app-router';
+import type { Middleware, ErrorMiddleware, AfterHook } from './router';
+
+// Middleware registered with use(), kept in registration order
+export interface MiddlewareEntry {
+  path: string;
+  handler: Middleware | ErrorMiddleware;
+  methods?: string[]; // Runs for every method when unset
+  isError: boolean; // (err, req, res, next) middleware only runs after a failure
+  timeout?: number; // Limit for this middleware in ms, within the request's timeout
+}
+
+// Response hook registered with after()
+export interface AfterHookEntry {
+  path: string;
+  handler: AfterHook;
+  methods?: string[];
+}
+
+// Upper-case method list of a `method` option
+export function normalizeMethods(method?: string | string[]): string[] | undefined {
+  return method === undefined ? undefined : (Array.isArray(method) ? method : [method]).map((m) => m.toUpperCase());
+}
 
 export interface MiddlewareUtils {
-  use(pathOrMiddleware: string | ((req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>), middleware?: (req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>): void;
+  use(
+    pathOrMiddleware: string | Middleware | ErrorMiddleware,
+    middlewareOrOptions?: Middleware | ErrorMiddleware | MiddlewareOptions,
+    options?: MiddlewareOptions
+  ): void;
 }
 
-export function createMiddlewareUtils(middlewares: Map<string, ((req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>)[]>): MiddlewareUtils {
+export function createMiddlewareEntry(path: string, handler: Middleware | ErrorMiddleware, options: MiddlewareOptions = {}): MiddlewareEntry {
   return {
-    use(pathOrMiddleware: string | ((req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>), middleware?: (req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>) {
+    path,
+    handler,
+    methods: normalizeMethods(options.method),
+    // Express convention: four parameters make an error handler
+    isError: options.error ?? handler.length === 4,
+    timeout: options.timeout,
+  };
+}
+
+export function createMiddlewareUtils(
+  middlewares: MiddlewareEntry[],
+  onRegister?: (entry: MiddlewareEntry) => void
+): MiddlewareUtils {
+  return {
+    use(pathOrMiddleware, middlewareOrOptions, options) {
+      let entry: MiddlewareEntry;
       if (typeof pathOrMiddleware === 'function') {
-        // Global middleware: router.use(middleware)
-        const existing = middlewares.get('*') || [];
-        middlewares.set('*', [...existing, pathOrMiddleware]);
-        // Don't register in global system here - it will be done in useRouter
-      } else if (typeof pathOrMiddleware === 'string' && middleware) {
-        // Middleware with path: router.use(path, middleware)
-        const existing = middlewares.get(pathOrMiddleware) || [];
-        middlewares.set(pathOrMiddleware, [...existing, middleware]);
-        // Don't register in global system here - it will be done in useRouter
+        // Global middleware: router.use(middleware, options?)
+        entry = createMiddlewareEntry('*', pathOrMiddleware, middlewareOrOptions as MiddlewareOptions | undefined);
+      } else if (typeof pathOrMiddleware === 'string' && typeof middlewareOrOptions === 'function') {
+        // Middleware with path: router.use(path, middleware, options?)
+        entry = createMiddlewareEntry(pathOrMiddleware, middlewareOrOptions, options);
       } else {
         throw new Error('Invalid middleware registration: use(path, middleware) or use(middleware)');
       }
+
+      middlewares.push(entry);
+      logger.debug(`🔧 Middleware added: ${entry.path}${entry.methods ? ` [${entry.methods.join(', ')}]` : ''}`, 'rnode_server::middleware');
+      onRegister?.(entry);
     }
   };
 }
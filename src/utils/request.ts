@@ -1,5 +1,6 @@
 import { UploadedFile } from '../types/request-response';
 import {logger} from "./logger";
+import { Readable } from 'stream';
 
 // Union type for different body types
 export type RequestBody = 
@@ -16,6 +17,14 @@ export interface BinaryData {
   size: number;
 }
 
+// Verified client certificate of a mutual TLS connection
+export interface ClientCertificate {
+  subject: string; // RFC 4514, e.g. 'CN=billing,O=Acme'
+  issuer: string;
+  subjectAltNames: string[]; // 'DNS:…', 'URI:…', 'IP:…', 'email:…'
+  fingerprint: string; // SHA-256 of the certificate, 'AB:CD:…'
+}
+
 export class Request {
   public method: string;
   public url: string;
@@ -30,15 +39,28 @@ export class Request {
   public ip?: string;
   public ips?: string[];
   public ipSource?: string;
+  public cspNonce?: string; // Nonce of the response's Content-Security-Policy
+  public requestId: string; // Correlation ID, also sent back in X-Request-Id
+  public traceparent?: string; // W3C trace context of the span handling the request, when tracing is on
+  public httpVersion: string; // Negotiated protocol version: '1.1' or '2.0'
+  public protocol: 'http' | 'https';
+  public clientCert?: ClientCertificate; // Verified client certificate of a mutual TLS connection
   public abortController: AbortController = new AbortController();
+  // Raw body for routes registered with { streamBody: true }
+  public bodyStream?: Readable;
 
   // Getter for abort signal
   get abortSignal(): AbortSignal | undefined {
     return this.abortController.signal;
   }
 
+  // Whether the request arrived over TLS
+  get secure(): boolean {
+    return this.protocol === 'https';
+  }
+
   constructor(requestData: any) {
-    const { method, path, registeredPath, pathParams, queryParams, body, cookies, headers, ip, ips, ipSource, customParams = {} } = requestData;
+    const { method, path, registeredPath, pathParams, queryParams, body, cookies, headers, ip, ips, ipSource, cspNonce, requestId, traceparent, httpVersion, protocol, clientCert, customParams = {} } = requestData;
     
     this.method = method;
     this.url = path;
@@ -51,6 +73,12 @@ export class Request {
     this.ip = ip || '127.0.0.1';
     this.ips = ips || ['127.0.0.1'];
     this.ipSource = ipSource || 'default';
+    this.cspNonce = cspNonce;
+    this.requestId = requestId || '';
+    this.traceparent = traceparent;
+    this.httpVersion = httpVersion || '1.1';
+    this.protocol = protocol === 'https' ? 'https' : 'http';
+    this.clientCert = clientCert;
     this.contentType = 'text/plain'
   }
 
@@ -0,0 +1,75 @@
+import { logger } from './logger';
+import { createRequestObject, createResponseObject } from './request-response-factory';
+import { getAppRegistry } from './global-utils';
+import type { ResponseInfo } from './router';
+
+// Runs the response hooks Rust matched for a request on the response it built
+export async function executeResponseHooks(hooksJson: string, timeout: number): Promise<string> {
+  try {
+    const { hookIds = [], request, response } = JSON.parse(hooksJson);
+
+    const req = createRequestObject(request);
+    const res = createResponseObject(request.cookies);
+    res.status(response.status);
+    res.setContentType(response.contentType ?? '');
+
+    // Keep Set-Cookie under the key res.setCookie() appends to
+    const { 'set-cookie': setCookie, ...headers } = response.headers ?? {};
+    Object.assign(res.headers, headers);
+    if (setCookie !== undefined) {
+      res.headers['Set-Cookie'] = Array.isArray(setCookie) ? setCookie : [setCookie];
+    }
+
+    if (typeof response.content === 'string') {
+      res.send(response.content);
+    }
+    const original = res.content;
+    const info: ResponseInfo = { bodySize: response.bodySize ?? null, durationMs: response.durationMs };
+
+    // Set timeout to abort the operation using existing abortController
+    const timeoutId = setTimeout(() => {
+      req.abortController?.abort();
+    }, timeout);
+
+    const { afterHooks } = getAppRegistry(request.appId ?? 0);
+    try {
+      for (const id of hookIds) {
+        const entry = afterHooks.get(id);
+        if (!entry) continue;
+
+        try {
+          await entry.handler(req, res, info);
+        } catch (error: any) {
+          // A failing hook must not lose the response
+          logger.error(`❌ Response hook error (${entry.path}): ${error instanceof Error ? error.message : String(error)}`, 'rnode_server::hooks');
+        }
+      }
+    } finally {
+      clearTimeout(timeoutId);
+    }
+
+    const content = res.content;
+    const replaced = content !== original
+      ? (typeof content === 'string' ? { content } : { content: Buffer.from(content).toString('base64'), base64: true })
+      : {};
+
+    // Header names are case-insensitive: setHeader('X-Foo') replaces the original x-foo
+    const merged: Record<string, string | string[]> = {};
+    for (const [name, value] of Object.entries(res.getHeaders())) {
+      for (const key of Object.keys(merged)) {
+        if (key.toLowerCase() === name.toLowerCase()) delete merged[key];
+      }
+      merged[name] = value;
+    }
+
+    return JSON.stringify({
+      status: res.currentStatus,
+      contentType: res.contentType,
+      headers: merged,
+      ...replaced
+    });
+  } catch (error: any) {
+    logger.error(`❌ executeResponseHooks error: ${error instanceof Error ? error.message : String(error)}`, 'rnode_server::hooks');
+    return JSON.stringify({ error: error instanceof Error ? error.message : String(error) });
+  }
+}
@@ -7,6 +7,8 @@ export class Response {
   private responseStatus: number = 200;
   private responseHeaders: Record<string, string | string[]> = {};
   private cookies: string = '';
+  private responseStream: AsyncIterable<any> | null = null;
+  private responseLength: number | undefined = undefined;
 
   constructor(cookies: string = '') {
     this.cookies = cookies;
@@ -25,6 +27,16 @@ export class Response {
     return this.responseContentType;
   }
 
+  // Readable stream set with stream(), pumped to the client chunk by chunk
+  get bodyStream(): AsyncIterable<any> | null {
+    return this.responseStream;
+  }
+
+  // Known length of the streamed body, if given
+  get contentLength(): number | undefined {
+    return this.responseLength;
+  }
+
   // Methods
   status(code: number): Response {
     this.responseStatus = code;
@@ -152,13 +164,18 @@ export class Response {
   }
 
   sendBuffer(buffer: Buffer, contentType: string = 'application/octet-stream', size?: number): Response {
-    // For binary data use special format
-    this.responseData = {
-      type: 'binary',
-      data: buffer.toString('base64'),
-      contentType: contentType,
-      contentLength: size || buffer.length
-    };
+    // Binary data is passed to Rust as a Buffer, Content-Length is taken from its size
+    this.responseData = size !== undefined ? buffer.subarray(0, size) : buffer;
+    this.responseContentType = contentType;
+    return this;
+  }
+
+  // Stream the body from a Node.js Readable (or any async iterable of Buffers/strings).
+  // Sent chunked unless contentLength is given.
+  stream(source: AsyncIterable<any>, options: { contentType?: string; contentLength?: number } = {}): Response {
+    this.responseStream = source;
+    this.responseLength = options.contentLength;
+    this.responseContentType = options.contentType ?? 'application/octet-stream';
     return this;
   }
 
@@ -1,30 +1,40 @@
 import { Request } from './request';
 import { Response } from './response';
 import { createHttpMethodsUtils, type HttpMethodsUtils } from './http-methods';
-import { createMiddlewareUtils, type MiddlewareUtils } from './middleware';
+import { createMiddlewareUtils, normalizeMethods, type MiddlewareUtils, type MiddlewareEntry, type AfterHookEntry } from './middleware';
 import { listFiles, saveFile, deleteFile, getFileContent, fileExists, loadStaticFiles, initTemplates, renderTemplate } from './file-utils';
-import { TemplateOptions } from '../types/app-router';
+import { TemplateOptions, RouteOptions, MiddlewareOptions } from '../types/app-router';
 import { createExpressMiddlewareWrapper, createExpressErrorMiddlewareWrapper } from './express-middleware-utils';
 import * as addon from "../load.cjs";
 import { CacheManager, CacheInitConfig } from '../types/cache';
 import { initCacheSystem, createCacheManager } from './cache';
 import { logger } from './logger';
+import { currentTraceparent } from './tracing';
 
 export type Middleware = (req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>;
+export type ErrorMiddleware = (err: any, req: Request, res: Response, next: (error?: any) => void) => void | Promise<any>;
+
+// What a response hook knows about the response besides res
+export interface ResponseInfo {
+  bodySize: number | null; // Null for streamed bodies of unknown length
+  durationMs: number; // Time spent producing the response
+}
+export type AfterHook = (req: Request, res: Response, info: ResponseInfo) => void | Promise<void>;
 
 export class Router {
-  private routerHandlers: Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any> }>;
-  private routerMiddlewares: Map<string, Middleware[]>;
+  private routerHandlers: Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }>;
+  private routerMiddlewares: MiddlewareEntry[];
+  private routerAfterHooks: AfterHookEntry[] = [];
   private httpMethods: HttpMethodsUtils;
   private middlewareUtils: MiddlewareUtils;
   private cacheManager: CacheManager | null = null;
 
   constructor() {
-    this.routerHandlers = new Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any> }>();
-    this.routerMiddlewares = new Map<string, Middleware[]>();
+    this.routerHandlers = new Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }>();
+    this.routerMiddlewares = [];
     
-    this.httpMethods = createHttpMethodsUtils(this.routerHandlers);
-    this.middlewareUtils = createMiddlewareUtils(this.routerMiddlewares);
+    this.httpMethods = createHttpMethodsUtils(this.routerHandlers, (method, path) => this.onRouteRegistered(method, path));
+    this.middlewareUtils = createMiddlewareUtils(this.routerMiddlewares, (entry) => this.onMiddlewareRegistered(entry));
     
     // Bind methods to preserve 'this' context
     this.useExpress = this.useExpress.bind(this);
@@ -32,41 +42,63 @@ export class Router {
     this.cache = this.cache.bind(this);
   }
 
+  // Hooks called after a route or middleware is added (used by the app to register live routes)
+  protected onRouteRegistered(_method: string, _path: string): void {}
+
+  protected onMiddlewareRegistered(_entry: MiddlewareEntry): void {}
+
+  protected onAfterHookRegistered(_entry: AfterHookEntry): void {}
+
   // HTTP Methods
-  get(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.get(path, handler);
+  get(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.get(path, handler, options);
   }
 
-  post(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.post(path, handler);
+  post(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.post(path, handler, options);
   }
 
-  put(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.put(path, handler);
+  put(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.put(path, handler, options);
   }
 
-  delete(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.delete(path, handler);
+  delete(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.delete(path, handler, options);
   }
 
-  patch(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.patch(path, handler);
+  patch(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.patch(path, handler, options);
   }
 
-  options(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.options(path, handler);
+  options(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.options(path, handler, options);
   }
 
-  trace(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.trace(path, handler);
+  trace(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.trace(path, handler, options);
   }
-  any(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>): any {
-    return this.httpMethods.any(path, handler);
+  any(path: string, handler: (req: Request, res: Response) => void | Promise<any> | Response | Promise<Response>, options?: RouteOptions): any {
+    return this.httpMethods.any(path, handler, options);
   }
 
   // Middleware
-  use(pathOrMiddleware: string | Middleware, middleware?: Middleware): void {
-    return this.middlewareUtils.use(pathOrMiddleware, middleware);
+  use(pathOrMiddleware: string | Middleware | ErrorMiddleware, middlewareOrOptions?: Middleware | ErrorMiddleware | MiddlewareOptions, options?: MiddlewareOptions): void {
+    return this.middlewareUtils.use(pathOrMiddleware, middlewareOrOptions, options);
+  }
+
+  // Response hooks: run after the handler with the response it built
+  after(pathOrHook: string | AfterHook, hookOrOptions?: AfterHook | MiddlewareOptions, options?: MiddlewareOptions): void {
+    let entry: AfterHookEntry;
+    if (typeof pathOrHook === 'function') {
+      entry = { path: '*', handler: pathOrHook, methods: normalizeMethods((hookOrOptions as MiddlewareOptions | undefined)?.method) };
+    } else if (typeof pathOrHook === 'string' && typeof hookOrOptions === 'function') {
+      entry = { path: pathOrHook, handler: hookOrOptions, methods: normalizeMethods(options?.method) };
+    } else {
+      throw new Error('Invalid hook registration: after(path, hook) or after(hook)');
+    }
+
+    this.routerAfterHooks.push(entry);
+    this.onAfterHookRegistered(entry);
   }
 
   // File listing method
@@ -104,29 +136,42 @@ export class Router {
     return initTemplates(pattern, options);
   }
 
-  // Template rendering method
-  renderTemplate(templateName: string, context: object): string {
-    return renderTemplate(templateName, context);
+  // Template rendering method; pass the request to expose its CSP nonce as {{ cspNonce }}
+  renderTemplate(templateName: string, context: object, req?: Request): string {
+    return renderTemplate(templateName, context, undefined, req?.cspNonce, req?.traceparent ?? currentTraceparent());
   }
 
-  getHandlers(): Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any> }> {
+  getHandlers(): Map<string, { method: string; handler: (req: Request, res: Response) => void | Promise<any>; options?: RouteOptions }> {
     return this.routerHandlers;
   }
 
-  getMiddlewares(): Map<string, Middleware[]> {
+  // Middleware grouped by path, in registration order
+  getMiddlewares(): Map<string, (Middleware | ErrorMiddleware)[]> {
+    const byPath = new Map<string, (Middleware | ErrorMiddleware)[]>();
+    for (const { path, handler } of this.routerMiddlewares) {
+      byPath.set(path, [...(byPath.get(path) || []), handler]);
+    }
+    return byPath;
+  }
+
+  getMiddlewareEntries(): MiddlewareEntry[] {
     return this.routerMiddlewares;
   }
 
+  getAfterHookEntries(): AfterHookEntry[] {
+    return this.routerAfterHooks;
+  }
+
   // HTTP utility methods
   async httpRequest(method: string, url: string, headers: Record<string, string> = {}, body: string = '', timeout: number = 30000): Promise<any> {
     const headersJson = JSON.stringify(headers);
-    const result = (addon as any).httpRequest(method, url, headersJson, body, timeout);
+    const result = (addon as any).httpRequest(method, url, headersJson, body, timeout, currentTraceparent());
     return JSON.parse(result);
   }
 
   async httpBatch(requests: Array<{method: string, url: string, headers?: Record<string, string>, body?: string}>, timeout: number = 30000): Promise<any> {
     const requestsJson = JSON.stringify(requests);
-    const result = (addon as any).httpBatch(requestsJson, timeout);
+    const result = (addon as any).httpBatch(requestsJson, timeout, currentTraceparent());
     return JSON.parse(result);
   }
 
@@ -152,8 +197,8 @@ export class Router {
 
   useExpressError(middleware: any): void {
     // Error middleware - will be called when errors occur
-    this.use((req: Request, res: Response, next: (error?: any) => void) => {
-      createExpressErrorMiddlewareWrapper(middleware, req, res, next);
+    this.use((err: any, req: Request, res: Response, next: (error?: any) => void) => {
+      createExpressErrorMiddlewareWrapper(middleware, err, req, res, next);
     });
   }
 
@@ -1,24 +1,51 @@
 import { logger } from './logger';
 
+// Hooks executed before the process exits (e.g. app.close())
+const shutdownHooks = new Set<() => Promise<void>>();
+
+export function registerShutdownHook(hook: () => Promise<void>): void {
+  shutdownHooks.add(hook);
+}
+
+export function unregisterShutdownHook(hook: () => Promise<void>): void {
+  shutdownHooks.delete(hook);
+}
+
+// Shared by every signal so a repeated Ctrl+C waits for the same drain
+let shutdownPromise: Promise<void> | null = null;
+let gracefulShutdownSetUp = false;
+
+export function runShutdownHooks(): Promise<void> {
+  if (!shutdownPromise) {
+    const hooks = Array.from(shutdownHooks);
+    shutdownHooks.clear();
+    shutdownPromise = Promise.allSettled(hooks.map(hook => hook())).then(() => undefined);
+  }
+  return shutdownPromise;
+}
+
+function onSignal(signal: NodeJS.Signals): void {
+  logger.info(`🛑 Received ${signal}, shutting down gracefully...`, 'rnode_server::shutdown');
+
+  // Force exit after a short delay if graceful shutdown fails
+  setTimeout(() => {
+    logger.warn('⚠️ Force exit after timeout', 'rnode_server::shutdown');
+    process.exit(1);
+  }, 15000).unref();
+
+  // Try to exit gracefully
+  runShutdownHooks().finally(() => process.exit(0));
+}
+
 export function setupGracefulShutdown(): void {
-  // Graceful shutdown handling
-  process.on('SIGINT', (signal) => {
-    logger.info(`🛑 Received ${signal}, shutting down gracefully...`, 'rnode_server::shutdown');
-    
-    // Force exit after a short delay if graceful shutdown fails
-    setTimeout(() => {
-      logger.warn('⚠️ Force exit after timeout', 'rnode_server::shutdown');
-      process.exit(1);
-    }, 5000);
-    
-    // Try to exit gracefully
-    process.exit(0);
-  });
+  // Listeners are process-wide; registering them twice would exit before the drain
+  if (gracefulShutdownSetUp) {
+    return;
+  }
+  gracefulShutdownSetUp = true;
 
-  process.on('SIGTERM', (signal) => {
-    logger.info(`🛑 Received ${signal}, shutting down gracefully...`, 'rnode_server::shutdown');
-    process.exit(0);
-  });
+  process.on('SIGINT', onSignal);
+  process.on('SIGTERM', onSignal);
 
   // Handle uncaught exceptions
   process.on('uncaughtException', (error) => {
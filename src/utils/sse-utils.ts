@@ -0,0 +1,110 @@
+import { logger } from './logger';
+import { createRequestObject } from './request-response-factory';
+import { Request } from './request';
+
+export interface SseEvent {
+  // Event payload; non-string values are sent as JSON
+  data: any;
+  // Event id, remembered for Last-Event-ID resumption
+  id?: string | number;
+  // Event name (the `event:` field)
+  event?: string;
+  // Client reconnection delay in milliseconds
+  retry?: number;
+}
+
+export interface SseOptions {
+  // Interval between keep-alive comments in milliseconds (default 15000)
+  keepAlive?: number;
+  // Number of events with an id kept for resumption (default 100)
+  replayBuffer?: number;
+}
+
+export type SseHandler = (req: Request, stream: SseStream) => void | Promise<void>;
+
+// One open event stream
+export class SseStream {
+  private closed = false;
+  private closeHandlers: (() => void)[] = [];
+
+  constructor(
+    readonly id: string,
+    // Id of the last event the client received, when it reconnects
+    readonly lastEventId: string | null,
+    private readonly sendNative: (eventJson: string | null) => boolean
+  ) {}
+
+  get isClosed(): boolean {
+    return this.closed;
+  }
+
+  // Push an event; returns false once the client is gone or not keeping up
+  send(event: SseEvent | string): boolean {
+    if (this.closed) return false;
+
+    const { data, id, event: name, retry } = typeof event === 'string' ? { data: event } as SseEvent : event;
+    return this.sendNative(JSON.stringify({
+      data: typeof data === 'string' ? data : JSON.stringify(data),
+      id: id !== undefined ? String(id) : undefined,
+      event: name,
+      retry,
+    }));
+  }
+
+  // End the stream from the server side
+  close(): void {
+    if (this.closed) return;
+    this.sendNative(null);
+    this.markClosed();
+  }
+
+  // Called when the client disconnects or the stream is closed
+  onClose(handler: () => void): void {
+    if (this.closed) {
+      handler();
+      return;
+    }
+    this.closeHandlers.push(handler);
+  }
+
+  markClosed(): void {
+    if (this.closed) return;
+    this.closed = true;
+    for (const handler of this.closeHandlers.splice(0)) {
+      try {
+        handler();
+      } catch (error) {
+        logger.error(`❌ SSE close handler failed: ${error}`, 'rnode_server::sse');
+      }
+    }
+  }
+}
+
+// Open streams by connection id
+const connections = new Map<string, SseStream>();
+
+export function executeSse(handler: SseHandler, requestJson: string, connectionId: string, send: (eventJson: string | null) => boolean): void {
+  const req = createRequestObject(JSON.parse(requestJson));
+  const stream = new SseStream(connectionId, req.getHeader('last-event-id'), send);
+
+  connections.set(connectionId, stream);
+  stream.onClose(() => connections.delete(connectionId));
+
+  const fail = (error: any) => {
+    logger.error(`❌ SSE handler failed for ${req.path}: ${error}`, 'rnode_server::sse');
+    stream.close();
+  };
+
+  try {
+    const result = handler(req, stream);
+    if (result && typeof (result as Promise<void>).catch === 'function') {
+      (result as Promise<void>).catch(fail);
+    }
+  } catch (error) {
+    fail(error);
+  }
+}
+
+export function closeSseConnection(connectionId: string): void {
+  connections.get(connectionId)?.markClosed();
+}
@@ -0,0 +1,14 @@
+import { AsyncLocalStorage } from 'async_hooks';
+
+// traceparent of the handler or middleware span the current async call chain runs in
+const traceStorage = new AsyncLocalStorage<string | undefined>();
+
+// Run a handler or middleware so native calls it makes join the request's trace
+export function runWithTrace<T>(traceparent: string | undefined, fn: () => T): T {
+  return traceStorage.run(traceparent, fn);
+}
+
+// Passed to cache, template and HTTP client calls; undefined outside a traced request
+export function currentTraceparent(): string | undefined {
+  return traceStorage.getStore();
+}
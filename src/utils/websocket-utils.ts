@@ -1,5 +1,5 @@
 import { logger } from './logger';
-import { websocketCallbacks } from './global-utils';
+import { getAppRegistry } from './global-utils';
 
 export interface WebSocketEventResult {
   shouldContinue: boolean;
@@ -42,7 +42,7 @@ export async function executeWebSocketEvent(eventJson: string, timeout: number):
     logger.debug(`🔌 executeWebSocketEvent called with type: ${eventType}, path: ${path}`, 'rnode_server::websocket');
 
     // Получаем колбеки для данного пути
-    const callbacks = websocketCallbacks.get(path);
+    const callbacks = getAppRegistry(eventData.appId ?? 0).websocketCallbacks.get(path);
     if (!callbacks) {
       logger.warn(`⚠️ No WebSocket callbacks found for path: ${path}`, 'rnode_server::websocket');
       return JSON.stringify({
@@ -0,0 +1,58 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('Request IDs and access log', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should propagate request IDs and write one log line per request', async () => {
+    const logPath = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'rnode-access-')), 'access.log');
+    const app = createApp({ accessLog: { format: 'json', path: logPath } });
+    const seen: string[] = [];
+
+    app.use((req: Request, res: Response, next: () => void) => {
+      seen.push(req.requestId);
+      next();
+    });
+    app.get('/items', (req: Request, res: Response) => {
+      seen.push(req.requestId);
+      res.json({ requestId: req.requestId });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const request = (headers: Record<string, string> = {}) =>
+      makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/items?page=2', method: 'GET', headers });
+
+    try {
+      // Generated ID, the same for middleware and handler
+      const generated = await request({ 'User-Agent': 'vitest' });
+      const id = generated.headers['x-request-id'];
+      expect(id).toMatch(/^[0-9a-f]{32}$/);
+      expect(generated.body.requestId).toBe(id);
+      expect(seen).toEqual([id, id]);
+
+      // Client ID is kept
+      const provided = await request({ 'X-Request-Id': 'client-42' });
+      expect(provided.headers['x-request-id']).toBe('client-42');
+      expect(provided.body.requestId).toBe('client-42');
+
+      // Lines are written on a background thread
+      await new Promise((resolve) => setTimeout(resolve, 200));
+      const lines = fs.readFileSync(logPath, 'utf8').trim().split('\n').map((line) => JSON.parse(line));
+      expect(lines).toHaveLength(2);
+      expect(lines[0]).toMatchObject({ requestId: id, method: 'GET', url: '/items?page=2', status: 200, userAgent: 'vitest' });
+      expect(lines[0].bytes).toBeGreaterThan(0);
+      expect(lines[1].requestId).toBe('client-42');
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
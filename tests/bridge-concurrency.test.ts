@@ -0,0 +1,50 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { cpus } from 'os';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// Send `count` requests at once and return how long the slowest one took
+async function concurrentRequests(port: number, path: string, count: number) {
+  const started = Date.now();
+  const responses = await Promise.all(
+    Array.from({ length: count }, () => makeHttpRequest({ hostname: '127.0.0.1', port, path, method: 'GET' }))
+  );
+  return { elapsed: Date.now() - started, failed: responses.filter((response) => response.statusCode !== 200).length };
+}
+
+describe('JavaScript Bridge Concurrency', () => {
+  let app: ReturnType<typeof createApp>;
+  let testPort: number;
+
+  beforeEach(() => {
+    app = createApp();
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should overlap slow async handlers and middleware instead of serializing them', async () => {
+    const delay = 200;
+    // A blocking bridge holds one Tokio worker per pending promise, so this many requests
+    // would need at least `count / cpus` rounds of `delay`
+    const count = cpus().length * 8;
+
+    app.use('/slow', async (req: Request, res: Response, next: () => void) => {
+      await new Promise((resolve) => setTimeout(resolve, delay));
+      next();
+    });
+    app.get('/slow', async (req: Request, res: Response) => {
+      await new Promise((resolve) => setTimeout(resolve, delay));
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const slow = await concurrentRequests(testPort, '/slow', count);
+      expect(slow.failed).toBe(0);
+      // Middleware and handler take two delays; all requests together should too
+      expect(slow.elapsed).toBeLessThan(4 * delay);
+    } finally {
+      await app.close(1000);
+    }
+  }, 30000);
+});
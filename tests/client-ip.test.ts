@@ -0,0 +1,56 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('Client IP', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  const startApp = async (options: Parameters<typeof createApp>[0]) => {
+    const app = createApp(options);
+    app.get('/ip', (req: Request, res: Response) => {
+      res.json({ ip: req.ip, ips: req.ips, source: req.ipSource });
+    });
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+    return app;
+  };
+
+  const request = async (headers: Record<string, string> = {}) => {
+    const response = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/ip', method: 'GET', headers });
+    return response.body;
+  };
+
+  it('should walk forwarding headers from a trusted local proxy', async () => {
+    const app = await startApp({ trustedProxies: ['loopback', '10.0.0.0/8'] });
+
+    try {
+      expect(await request()).toEqual({ ip: '127.0.0.1', ips: ['127.0.0.1'], source: 'socket' });
+
+      // The spoofed first entry is ignored, trusted hops are skipped
+      const forwarded = await request({ 'X-Forwarded-For': '6.6.6.6, 198.51.100.7, 10.0.0.1' });
+      expect(forwarded.ip).toBe('198.51.100.7');
+      expect(forwarded.ips).toEqual(['198.51.100.7', '10.0.0.1', '127.0.0.1']);
+      expect(forwarded.source).toBe('x-forwarded-for');
+
+      const rfc7239 = await request({ Forwarded: 'for=192.0.2.60;proto=https, for="[2001:db8::17]:4711"' });
+      expect(rfc7239.ip).toBe('2001:db8::17');
+      expect(rfc7239.source).toBe('forwarded');
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should ignore forwarding headers from untrusted peers', async () => {
+    const app = await startApp({ trustedProxies: [] });
+
+    try {
+      const result = await request({ 'X-Forwarded-For': '6.6.6.6', 'X-Real-IP': '6.6.6.6', 'CF-Connecting-IP': '6.6.6.6' });
+      expect(result).toEqual({ ip: '127.0.0.1', ips: ['127.0.0.1'], source: 'socket' });
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
@@ -0,0 +1,74 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as http from 'http';
+import * as zlib from 'zlib';
+import { createApp, type Request, type Response } from '../';
+
+interface RawResponse {
+  statusCode: number;
+  headers: http.IncomingHttpHeaders;
+  body: Buffer;
+}
+
+// Raw body: the shared helper decodes it as text
+function fetchRaw(port: number, path: string, headers: Record<string, string>): Promise<RawResponse> {
+  return new Promise((resolve, reject) => {
+    const req = http.request({ hostname: '127.0.0.1', port, path, method: 'GET', headers }, (res) => {
+      const chunks: Buffer[] = [];
+      res.on('data', (chunk: Buffer) => chunks.push(chunk));
+      res.on('end', () => resolve({ statusCode: res.statusCode || 200, headers: res.headers, body: Buffer.concat(chunks) }));
+    });
+    req.on('error', reject);
+    req.end();
+  });
+}
+
+describe('Compression', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should compress dynamic responses with the negotiated encoding', async () => {
+    const app = createApp({ compression: { minSize: 512 } });
+    const items = Array.from({ length: 200 }, (_, i) => ({ id: i, name: `Item ${i}` }));
+
+    app.get('/items', (req: Request, res: Response) => {
+      res.json({ items });
+    });
+    app.get('/small', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+    app.get('/binary', (req: Request, res: Response) => {
+      res.setContentType('application/octet-stream');
+      res.send(Buffer.alloc(4096, 1));
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const br = await fetchRaw(testPort, '/items', { 'Accept-Encoding': 'gzip, br' });
+      expect(br.statusCode).toBe(200);
+      expect(br.headers['content-encoding']).toBe('br');
+      expect(br.headers['vary']).toContain('accept-encoding');
+      expect(JSON.parse(zlib.brotliDecompressSync(br.body).toString()).items).toHaveLength(200);
+
+      const gzip = await fetchRaw(testPort, '/items', { 'Accept-Encoding': 'gzip;q=1, br;q=0.5' });
+      expect(gzip.headers['content-encoding']).toBe('gzip');
+      expect(JSON.parse(zlib.gunzipSync(gzip.body).toString()).items).toHaveLength(200);
+
+      const plain = await fetchRaw(testPort, '/items', {});
+      expect(plain.headers['content-encoding']).toBeUndefined();
+      expect(JSON.parse(plain.body.toString()).items).toHaveLength(200);
+
+      const small = await fetchRaw(testPort, '/small', { 'Accept-Encoding': 'gzip' });
+      expect(small.headers['content-encoding']).toBeUndefined();
+
+      const binary = await fetchRaw(testPort, '/binary', { 'Accept-Encoding': 'gzip' });
+      expect(binary.headers['content-encoding']).toBeUndefined();
+      expect(binary.body.length).toBe(4096);
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
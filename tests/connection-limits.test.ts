@@ -0,0 +1,191 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as http from 'http';
+import * as https from 'https';
+import * as net from 'net';
+import * as os from 'os';
+import * as path from 'path';
+import * as tls from 'tls';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// Keep-alive socket that stays open between requests
+function connect(port: number): Promise<net.Socket> {
+  return new Promise((resolve, reject) => {
+    const socket = net.connect(port, '127.0.0.1', () => resolve(socket));
+    socket.on('error', reject);
+  });
+}
+
+// Resolves once the TLS handshake completes; rejects if the server closes the connection first
+function connectTls(port: number): Promise<tls.TLSSocket> {
+  return new Promise((resolve, reject) => {
+    const socket = tls.connect({ port, host: '127.0.0.1', rejectUnauthorized: false }, () => resolve(socket));
+    socket.on('error', reject);
+    socket.on('close', () => reject(new Error('closed before the handshake')));
+  });
+}
+
+// Write raw request bytes and resolve with the first chunk of the response
+function send(socket: net.Socket, request: string): Promise<string> {
+  return new Promise((resolve) => {
+    socket.once('data', (chunk) => resolve(chunk.toString()));
+    socket.write(request);
+  });
+}
+
+// Resolves with the time it took the server to close the socket
+function closed(socket: net.Socket): Promise<number> {
+  const started = Date.now();
+  return new Promise((resolve) => socket.once('close', () => resolve(Date.now() - started)));
+}
+
+describe('Connection limits', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should reject connections over maxConnections with 503', async () => {
+    const app = createApp({ maxConnections: 1, metrics: true });
+
+    app.get('/ping', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+    const request = (path: string) =>
+      makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method: 'GET', headers: { Connection: 'close' } });
+
+    try {
+      const held = await connect(testPort);
+      const first = await send(held, 'GET /ping HTTP/1.1\r\nHost: localhost\r\n\r\n');
+      expect(first).toMatch(/^HTTP\/1\.1 200/);
+
+      const rejected = await request('/ping');
+      expect(rejected.statusCode).toBe(503);
+      expect(rejected.headers['retry-after']).toBe('1');
+
+      held.destroy();
+      await new Promise((resolve) => setTimeout(resolve, 100));
+
+      const accepted = await request('/ping');
+      expect(accepted.statusCode).toBe(200);
+
+      const metrics = await request('/metrics');
+      expect(metrics.body).toMatch(/rnode_server_connections_rejected_total [1-9]/);
+      expect(metrics.body).toMatch(/rnode_server_active_connections \d/);
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should close TLS connections over maxConnections and count them', async () => {
+    const fixtures = path.join(__dirname, 'fixtures', 'tls');
+    const app = createApp({
+      maxConnections: 1,
+      metrics: true,
+      ssl: { certPath: path.join(fixtures, 'default.crt'), keyPath: path.join(fixtures, 'default.key'), strict: true },
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    // The counter is process-wide, so compare before and after
+    const rejectedTotal = () =>
+      new Promise<number>((resolve, reject) => {
+        https
+          .get({ hostname: '127.0.0.1', port: testPort, path: '/metrics', rejectUnauthorized: false, agent: false }, (res) => {
+            let data = '';
+            res.on('data', (chunk) => (data += chunk));
+            res.on('end', () => resolve(Number(data.match(/rnode_server_connections_rejected_total (\d+)/)?.[1] ?? 0)));
+          })
+          .on('error', reject);
+      });
+
+    try {
+      const before = await rejectedTotal();
+      await new Promise((resolve) => setTimeout(resolve, 100));
+
+      const held = await connectTls(testPort);
+      // No handshake and no 503 for the second connection
+      await expect(connectTls(testPort)).rejects.toThrow();
+      held.destroy();
+      await new Promise((resolve) => setTimeout(resolve, 100));
+
+      expect(await rejectedTotal()).toBe(before + 1);
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should close idle and slow connections and limit headers', async () => {
+    const app = createApp({ idleTimeout: 300, headerReadTimeout: 300, maxHeaders: 5, maxHeaderSize: 8192 });
+
+    app.get('/ping', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      // Keep-alive connection without a second request
+      const idle = await connect(testPort);
+      const response = await send(idle, 'GET /ping HTTP/1.1\r\nHost: localhost\r\n\r\n');
+      expect(response).toMatch(/^HTTP\/1\.1 200/);
+      expect(await closed(idle)).toBeLessThan(2000);
+
+      // Slowloris: headers that never finish
+      const slow = await connect(testPort);
+      slow.on('data', () => {});
+      slow.write('GET /ping HTTP/1.1\r\nHost: localhost\r\n');
+      expect(await closed(slow)).toBeLessThan(2000);
+
+      const headers = Object.fromEntries(Array.from({ length: 10 }, (_, i) => [`x-extra-${i}`, 'value']));
+      const tooMany = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/ping', method: 'GET', headers });
+      expect(tooMany.statusCode).toBe(431);
+
+      const tooLarge = await makeHttpRequest({
+        hostname: '127.0.0.1',
+        port: testPort,
+        path: '/ping',
+        method: 'GET',
+        headers: { 'x-large': 'a'.repeat(10000) },
+      });
+      expect(tooLarge.statusCode).toBe(431);
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should count pending requests and connections on Unix sockets too', async () => {
+    const socketPath = path.join(os.tmpdir(), `rnode-limits-${process.pid}-${testPort}.sock`);
+    const app = createApp({ metrics: true, listen: [`unix:${socketPath}`] });
+
+    app.get('/slow', async (req: Request, res: Response) => {
+      await new Promise((resolve) => setTimeout(resolve, 300));
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const slow = new Promise<number>((resolve, reject) => {
+        http.get({ socketPath, path: '/slow' }, (res) => {
+          res.resume();
+          res.on('end', () => resolve(res.statusCode || 0));
+        }).on('error', reject);
+      });
+      await new Promise((resolve) => setTimeout(resolve, 100));
+
+      // The slow request and the metrics request itself
+      const metrics = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/metrics', method: 'GET' });
+      expect(metrics.body).toMatch(/rnode_server_pending_requests ([2-9]|\d{2,})/);
+      expect(metrics.body).toMatch(/rnode_server_active_connections ([2-9]|\d{2,})/);
+      expect(metrics.body).toMatch(/rnode_server_total_connections [1-9]/);
+
+      expect(await slow).toBe(200);
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
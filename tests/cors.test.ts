@@ -0,0 +1,84 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('CORS', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should answer preflights natively and add headers for allowed origins', async () => {
+    let middlewareCalls = 0;
+    const app = createApp({
+      cors: {
+        origins: ['https://app.example.com', /^https:\/\/[a-z]+\.preview\.example\.com$/],
+        credentials: true,
+        exposedHeaders: ['X-Total-Count'],
+        maxAge: 600,
+        routes: {
+          '/public/*': { origins: '*', credentials: false },
+        },
+      },
+    });
+
+    app.use((req: Request, res: Response, next: () => void) => {
+      middlewareCalls++;
+      next();
+    });
+    app.get('/api/users', (req: Request, res: Response) => {
+      res.json({ users: [] });
+    });
+    app.get('/public/feed', (req: Request, res: Response) => {
+      res.json({ feed: [] });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const request = (path: string, method: string, headers: Record<string, string>) =>
+      makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method, headers });
+
+    try {
+      const preflight = await request('/api/users', 'OPTIONS', {
+        Origin: 'https://app.example.com',
+        'Access-Control-Request-Method': 'GET',
+        'Access-Control-Request-Headers': 'content-type, authorization',
+      });
+      expect(preflight.statusCode).toBe(204);
+      expect(preflight.headers['access-control-allow-origin']).toBe('https://app.example.com');
+      expect(preflight.headers['access-control-allow-credentials']).toBe('true');
+      expect(preflight.headers['access-control-allow-headers']).toBe('content-type, authorization');
+      expect(preflight.headers['access-control-max-age']).toBe('600');
+      expect(middlewareCalls).toBe(0);
+
+      const actual = await request('/api/users', 'GET', { Origin: 'https://pr.preview.example.com' });
+      expect(actual.statusCode).toBe(200);
+      expect(actual.headers['access-control-allow-origin']).toBe('https://pr.preview.example.com');
+      expect(actual.headers['access-control-expose-headers']).toBe('X-Total-Count');
+      expect(actual.headers['vary']).toContain('Origin');
+
+      const rejectedPreflight = await request('/api/users', 'OPTIONS', {
+        Origin: 'https://evil.com',
+        'Access-Control-Request-Method': 'GET',
+      });
+      expect(rejectedPreflight.statusCode).toBe(403);
+
+      const rejected = await request('/api/users', 'GET', { Origin: 'https://evil.com' });
+      expect(rejected.statusCode).toBe(200);
+      expect(rejected.headers['access-control-allow-origin']).toBeUndefined();
+      expect(rejected.headers['vary']).toContain('Origin');
+
+      const sameOrigin = await request('/api/users', 'GET', {});
+      expect(sameOrigin.headers['vary']).toContain('Origin');
+
+      // Route override allows any origin without credentials
+      const publicFeed = await request('/public/feed', 'GET', { Origin: 'https://evil.com' });
+      expect(publicFeed.headers['access-control-allow-origin']).toBe('*');
+      expect(publicFeed.headers['access-control-allow-credentials']).toBeUndefined();
+      expect(publicFeed.headers['vary'] ?? '').not.toContain('Origin');
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
@@ -0,0 +1,13 @@
+// Child process for the SIGTERM test: a slow route on the port given as the first argument
+const { createApp } = require('../../..');
+
+const app = createApp();
+
+app.get('/slow', async (req, res) => {
+  await new Promise((resolve) => setTimeout(resolve, 500));
+  res.json({ done: true });
+});
+
+app.listen(Number(process.argv[2]), () => {
+  process.send('ready');
+});
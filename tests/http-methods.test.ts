@@ -219,4 +219,51 @@ describe('HTTP Methods Tests', () => {
       });
     });
   });
+
+  describe('Method Semantics', () => {
+    it('should answer HEAD, OPTIONS and 405 with the allowed methods', async () => {
+      app.get('/items', (req: Request, res: Response) => {
+        res.json({ items: [1, 2, 3] });
+      });
+      app.post('/items', (req: Request, res: Response) => {
+        res.status(201).json({ created: true });
+      });
+
+      await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+      try {
+        const head = await fetch(`http://127.0.0.1:${testPort}/items`, { method: 'HEAD' });
+        expect(head.status).toBe(200);
+        expect(head.headers.get('content-type')).toContain('application/json');
+        expect(await head.text()).toBe('');
+
+        const options = await fetch(`http://127.0.0.1:${testPort}/items`, { method: 'OPTIONS' });
+        expect(options.status).toBe(204);
+        expect(options.headers.get('allow')?.split(',').sort()).toEqual(['GET', 'HEAD', 'OPTIONS', 'POST']);
+
+        const notAllowed = await fetch(`http://127.0.0.1:${testPort}/items`, { method: 'DELETE' });
+        expect(notAllowed.status).toBe(405);
+        expect(notAllowed.headers.get('allow')?.split(',').sort()).toEqual(['GET', 'HEAD', 'OPTIONS', 'POST']);
+      } finally {
+        await app.close(1000);
+      }
+    });
+
+    it('should keep the legacy 404 when methodNotAllowedAs404 is set', async () => {
+      const legacy = createApp({ methodNotAllowedAs404: true });
+      legacy.get('/items', (req: Request, res: Response) => {
+        res.json({ items: [] });
+      });
+
+      await new Promise<void>((resolve) => legacy.listen(testPort, () => resolve()));
+
+      try {
+        const response = await fetch(`http://127.0.0.1:${testPort}/items`, { method: 'DELETE' });
+        expect(response.status).toBe(404);
+        expect(response.headers.get('allow')).toBeNull();
+      } finally {
+        await legacy.close(1000);
+      }
+    });
+  });
 });
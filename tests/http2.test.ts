@@ -0,0 +1,57 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as http2 from 'http2';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// GET over a prior-knowledge HTTP/2 connection
+function h2Get(session: http2.ClientHttp2Session, path: string): Promise<{ status: number; body: any }> {
+  return new Promise((resolve, reject) => {
+    const stream = session.request({ ':path': path, ':method': 'GET' });
+    let status = 0;
+    let data = '';
+    stream.on('response', (headers) => {
+      status = Number(headers[':status']);
+    });
+    stream.setEncoding('utf8');
+    stream.on('data', (chunk) => (data += chunk));
+    stream.on('end', () => resolve({ status, body: JSON.parse(data) }));
+    stream.on('error', reject);
+    stream.end();
+  });
+}
+
+describe('HTTP/2', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should serve h2c and HTTP/1.1 on the same plain listener', async () => {
+    const app = createApp({ http2: { h2c: true, maxConcurrentStreams: 10 } });
+
+    app.get('/version', (req: Request, res: Response) => {
+      res.json({ httpVersion: req.httpVersion, protocol: req.protocol, secure: req.secure });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+    const session = http2.connect(`http://127.0.0.1:${testPort}`);
+    session.on('error', () => {});
+
+    try {
+      // Multiplexed streams on one connection
+      const responses = await Promise.all([h2Get(session, '/version'), h2Get(session, '/version')]);
+      for (const response of responses) {
+        expect(response.status).toBe(200);
+        expect(response.body).toEqual({ httpVersion: '2.0', protocol: 'http', secure: false });
+      }
+
+      const http1 = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/version', method: 'GET' });
+      expect(http1.statusCode).toBe(200);
+      expect(http1.body).toEqual({ httpVersion: '1.1', protocol: 'http', secure: false });
+    } finally {
+      session.close();
+      await app.close(1000);
+    }
+  });
+});
@@ -0,0 +1,63 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+const fixtures = path.join(__dirname, 'fixtures', 'tls');
+
+describe('HTTPS redirect', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should redirect plain requests to HTTPS except excluded paths', async () => {
+    const redirectPort = testPort + 1;
+    const app = createApp({
+      ssl: { certPath: path.join(fixtures, 'default.crt'), keyPath: path.join(fixtures, 'default.key'), strict: true },
+      httpsRedirect: { port: redirectPort, status: 308, exclude: ['/.well-known/acme-challenge'] },
+    });
+
+    app.get('/.well-known/acme-challenge/{token}', (req: Request, res: Response) => {
+      res.json({ token: req.params.token, protocol: req.protocol });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+    const request = (urlPath: string, method = 'GET') =>
+      makeHttpRequest({ hostname: '127.0.0.1', port: redirectPort, path: urlPath, method, headers: { Host: `example.test:${redirectPort}` } });
+
+    try {
+      const redirected = await request('/orders/42?expand=items');
+      expect(redirected.statusCode).toBe(308);
+      expect(redirected.headers.location).toBe(`https://example.test:${testPort}/orders/42?expand=items`);
+
+      const post = await request('/orders', 'POST');
+      expect(post.statusCode).toBe(308);
+
+      const challenge = await request('/.well-known/acme-challenge/abc123');
+      expect(challenge.statusCode).toBe(200);
+      expect(challenge.body).toEqual({ token: 'abc123', protocol: 'http' });
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should bind next to a host:port listen address and point at its port', async () => {
+    const redirectPort = testPort + 1;
+    const app = createApp({
+      ssl: { certPath: path.join(fixtures, 'default.crt'), keyPath: path.join(fixtures, 'default.key'), strict: true },
+      httpsRedirect: { port: redirectPort },
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, `127.0.0.1:${testPort}`, () => resolve()));
+
+    try {
+      const redirected = await makeHttpRequest({ hostname: '127.0.0.1', port: redirectPort, path: '/', method: 'GET', headers: { Host: 'example.test' } });
+      expect(redirected.statusCode).toBe(301);
+      expect(redirected.headers.location).toBe(`https://example.test:${testPort}/`);
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
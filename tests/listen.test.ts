@@ -0,0 +1,55 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as fs from 'fs';
+import * as http from 'http';
+import * as os from 'os';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// GET over a Unix socket
+function unixRequest(socketPath: string, urlPath: string): Promise<{ statusCode: number; body: any }> {
+  return new Promise((resolve, reject) => {
+    http
+      .get({ socketPath, path: urlPath }, (res) => {
+        let data = '';
+        res.on('data', (chunk) => (data += chunk));
+        res.on('end', () => resolve({ statusCode: res.statusCode || 0, body: JSON.parse(data) }));
+      })
+      .on('error', reject);
+  });
+}
+
+describe('Listen addresses', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should serve on Unix sockets without deleting other files', async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'rnode-listen-'));
+    const socketPath = path.join(dir, 'app.sock');
+    const configPath = path.join(dir, 'app.conf');
+    fs.writeFileSync(configPath, 'keep me');
+
+    const app = createApp({ listen: [`unix:${socketPath}`, `unix:${configPath}`] });
+
+    app.get('/ping', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      expect(await unixRequest(socketPath, '/ping')).toEqual({ statusCode: 200, body: { ok: true } });
+
+      // The regular file is not replaced by a socket, and the TCP listener still serves
+      expect(fs.readFileSync(configPath, 'utf8')).toBe('keep me');
+      const tcp = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/ping', method: 'GET' });
+      expect(tcp.statusCode).toBe(200);
+    } finally {
+      await app.close(1000);
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
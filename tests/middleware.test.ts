@@ -201,5 +201,92 @@ describe('Middleware Tests', () => {
         });
       });
     });
+
+    it('should run every matching middleware in registration order', async () => {
+      const app = createApp();
+      const calls: string[] = [];
+
+      app.use((req: Request, res: Response, next: () => void) => {
+        calls.push('global');
+        next();
+      });
+      app.use('/api/*', (req: Request, res: Response, next: () => void) => {
+        calls.push('api-1');
+        next();
+      });
+      app.use('/api/*', async (req: Request, res: Response, next: () => void) => {
+        calls.push('api-2');
+        next();
+      });
+      app.use('/api/*', (req: Request, res: Response, next: () => void) => {
+        calls.push('api-post');
+        next();
+      }, { method: 'POST' });
+      app.use('/api/private', (req: Request, res: Response) => {
+        // Answers without calling next(): the handler does not run
+        res.status(401).json({ error: 'unauthorized' });
+      });
+
+      app.get('/api/items', (req: Request, res: Response) => {
+        res.json({ calls });
+      });
+      app.get('/api/private', (req: Request, res: Response) => {
+        res.json({ secret: true });
+      });
+
+      await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+      try {
+        const response = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/api/items', method: 'GET' });
+        expect(response.statusCode).toBe(200);
+        expect(response.body.calls).toEqual(['global', 'api-1', 'api-2']);
+
+        calls.length = 0;
+        const blocked = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/api/private', method: 'GET' });
+        expect(blocked.statusCode).toBe(401);
+        expect(blocked.body.error).toBe('unauthorized');
+        expect(calls).toEqual(['global', 'api-1', 'api-2']);
+      } finally {
+        await app.close(1000);
+      }
+    });
+
+    it('should pass middleware and handler failures to error middleware', async () => {
+      const app = createApp();
+      const skipped: string[] = [];
+
+      app.use('/fail/*', (req: Request, res: Response, next: (error?: any) => void) => {
+        next(Object.assign(new Error('middleware failed'), { status: 400 }));
+      });
+      app.use('/fail/*', (req: Request, res: Response, next: () => void) => {
+        skipped.push('regular');
+        next();
+      });
+      app.use((err: any, req: Request, res: Response, next: (error?: any) => void) => {
+        res.status(err.status ?? 500).json({ handled: err.message });
+      });
+
+      app.get('/fail/middleware', (req: Request, res: Response) => {
+        res.json({ reached: true });
+      });
+      app.get('/handler', async () => {
+        throw new Error('handler failed');
+      });
+
+      await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+      try {
+        const fromMiddleware = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/fail/middleware', method: 'GET' });
+        expect(fromMiddleware.statusCode).toBe(400);
+        expect(fromMiddleware.body.handled).toBe('middleware failed');
+        expect(skipped).toEqual([]);
+
+        const fromHandler = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/handler', method: 'GET' });
+        expect(fromHandler.statusCode).toBe(500);
+        expect(fromHandler.body.handled).toBe('handler failed');
+      } finally {
+        await app.close(1000);
+      }
+    });
   });
 });
@@ -0,0 +1,108 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as fs from 'fs';
+import * as https from 'https';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+
+const fixtures = path.join(__dirname, 'fixtures', 'tls');
+const fixture = (name: string) => path.join(fixtures, name);
+
+// GET over TLS, optionally presenting the test client certificate
+function request(port: number, urlPath: string, withCertificate: boolean): Promise<{ statusCode: number; body: any }> {
+  return new Promise((resolve, reject) => {
+    const req = https.request(
+      {
+        hostname: '127.0.0.1',
+        port,
+        path: urlPath,
+        method: 'GET',
+        rejectUnauthorized: false,
+        agent: false,
+        ...(withCertificate ? { cert: fs.readFileSync(fixture('client.crt')), key: fs.readFileSync(fixture('client.key')) } : {}),
+      },
+      (res) => {
+        let data = '';
+        res.on('data', (chunk) => (data += chunk));
+        res.on('end', () => resolve({ statusCode: res.statusCode || 0, body: data ? JSON.parse(data) : null }));
+      }
+    );
+    req.on('error', reject);
+    req.end();
+  });
+}
+
+describe('Mutual TLS', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should expose the verified client certificate to middleware and handlers', async () => {
+    const app = createApp({
+      ssl: {
+        certPath: fixture('default.crt'),
+        keyPath: fixture('default.key'),
+        clientCaPath: fixture('client-ca.crt'),
+        clientAuth: 'optional',
+        strict: true,
+      },
+    });
+
+    // Authorize internal routes by the caller's SPIFFE ID
+    app.use('/internal', (req: Request, res: Response, next: () => void) => {
+      if (!req.clientCert?.subjectAltNames.includes('URI:spiffe://example.test/billing')) {
+        return res.status(403).json({ error: 'forbidden' });
+      }
+      next();
+    });
+    app.get('/internal/identity', (req: Request, res: Response) => {
+      res.json(req.clientCert);
+    });
+    app.get('/public', (req: Request, res: Response) => {
+      res.json({ clientCert: req.clientCert ?? null });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const identity = await request(testPort, '/internal/identity', true);
+      expect(identity.statusCode).toBe(200);
+      expect(identity.body).toEqual({
+        subject: 'CN=billing-service,OU=Billing,O=RNode Test',
+        issuer: 'CN=RNode Test Client CA,O=RNode Test',
+        subjectAltNames: ['DNS:billing.internal', 'URI:spiffe://example.test/billing', 'IP:10.0.0.7'],
+        fingerprint: '98:59:DD:F3:05:65:12:76:97:5E:92:28:77:1D:39:E9:5A:63:FE:7C:26:BA:E1:4E:68:43:69:4B:67:BB:16:76',
+      });
+
+      const anonymous = await request(testPort, '/internal/identity', false);
+      expect(anonymous.statusCode).toBe(403);
+
+      const publicRoute = await request(testPort, '/public', false);
+      expect(publicRoute.body).toEqual({ clientCert: null });
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should reject clients without a certificate when one is required', async () => {
+    const app = createApp({
+      ssl: { certPath: fixture('default.crt'), keyPath: fixture('default.key'), clientCaPath: fixture('client-ca.crt'), strict: true },
+    });
+
+    app.get('/', (req: Request, res: Response) => {
+      res.json({ subject: req.clientCert?.subject });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      await expect(request(testPort, '/', false)).rejects.toThrow();
+
+      const authenticated = await request(testPort, '/', true);
+      expect(authenticated.body).toEqual({ subject: 'CN=billing-service,OU=Billing,O=RNode Test' });
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
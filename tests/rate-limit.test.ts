@@ -0,0 +1,73 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('Rate Limiting', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should answer 429 with Retry-After and RateLimit headers', async () => {
+    let handlerCalls = 0;
+    const app = createApp({
+      rateLimit: {
+        limit: 2,
+        windowMs: 60000,
+        routes: {
+          '/keys/*': { algorithm: 'tokenBucket', limit: 1, key: { header: 'x-api-key' } },
+          '/health': false,
+        },
+      },
+    });
+
+    app.get('/api', (req: Request, res: Response) => {
+      handlerCalls++;
+      res.json({ ok: true });
+    });
+    app.get('/keys/{id}', (req: Request, res: Response) => {
+      res.json({ id: req.params.id });
+    });
+    app.get('/health', (req: Request, res: Response) => {
+      res.send('OK');
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const request = (path: string, headers: Record<string, string> = {}) =>
+      makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method: 'GET', headers });
+
+    try {
+      const first = await request('/api');
+      expect(first.statusCode).toBe(200);
+      expect(first.headers['ratelimit-limit']).toBe('2');
+      expect(first.headers['ratelimit-remaining']).toBe('1');
+
+      expect((await request('/api')).statusCode).toBe(200);
+
+      const limited = await request('/api');
+      expect(limited.statusCode).toBe(429);
+      expect(limited.headers['ratelimit-remaining']).toBe('0');
+      expect(Number(limited.headers['retry-after'])).toBeGreaterThan(0);
+      expect(handlerCalls).toBe(2);
+
+      // Another client IP has its own counter
+      expect((await request('/api', { 'X-Forwarded-For': '203.0.113.7' })).statusCode).toBe(200);
+
+      // Route rule counted by API key
+      expect((await request('/keys/1', { 'X-Api-Key': 'a' })).statusCode).toBe(200);
+      expect((await request('/keys/2', { 'X-Api-Key': 'a' })).statusCode).toBe(429);
+      expect((await request('/keys/2', { 'X-Api-Key': 'b' })).statusCode).toBe(200);
+
+      // Disabled route
+      for (let i = 0; i < 3; i++) {
+        const health = await request('/health');
+        expect(health.statusCode).toBe(200);
+        expect(health.headers['ratelimit-limit']).toBeUndefined();
+      }
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
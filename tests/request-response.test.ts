@@ -1,5 +1,6 @@
 import { describe, it, expect, beforeEach, afterEach } from 'vitest';
 import { createApp, type Request, type Response } from '../';
+import { Readable } from 'stream';
 import { makeHttpRequest } from './helpers';
 
 describe('Request/Response Tests', () => {
@@ -261,4 +262,115 @@ describe('Request/Response Tests', () => {
       });
     });
   });
+
+  describe('Binary and Streamed Bodies', () => {
+    it('should send Buffers with Content-Length and streams chunked', async () => {
+      const bytes = Buffer.from([0, 1, 2, 253, 254, 255]);
+
+      app.get('/binary', (req: Request, res: Response) => {
+        res.sendBuffer(bytes, 'application/octet-stream');
+      });
+
+      app.get('/stream', (req: Request, res: Response) => {
+        res.stream(Readable.from(['chunk-1,', 'chunk-2,', 'chunk-3']), { contentType: 'text/plain' });
+      });
+
+      return new Promise<void>(async (resolve, reject) => {
+        app.listen(testPort, async () => {
+          try {
+            const binary = await fetch(`http://127.0.0.1:${testPort}/binary`);
+            expect(binary.status).toBe(200);
+            expect(binary.headers.get('content-length')).toBe(String(bytes.length));
+            expect(Buffer.from(await binary.arrayBuffer())).toEqual(bytes);
+
+            const streamed = await fetch(`http://127.0.0.1:${testPort}/stream`);
+            expect(streamed.status).toBe(200);
+            expect(streamed.headers.get('transfer-encoding')).toBe('chunked');
+            expect(await streamed.text()).toBe('chunk-1,chunk-2,chunk-3');
+            resolve();
+          } catch (error) {
+            reject(error);
+          }
+        });
+      });
+    });
+  });
+
+  describe('Body Size Limits', () => {
+    it('should reject oversized bodies and stream raw bodies', async () => {
+      const limited = createApp({ maxBodySize: 64 });
+
+      limited.post('/echo', (req: Request, res: Response) => {
+        res.json({ body: req.body });
+      });
+
+      limited.post('/large', (req: Request, res: Response) => {
+        res.json({ size: JSON.stringify(req.body).length });
+      }, { maxBodySize: 1024 });
+
+      limited.post('/raw', async (req: Request, res: Response) => {
+        const chunks: Buffer[] = [];
+        for await (const chunk of req.bodyStream!) {
+          chunks.push(chunk);
+        }
+        res.json({ parsed: req.body, size: Buffer.concat(chunks).length });
+      }, { streamBody: true, maxBodySize: 4096 });
+
+      await new Promise<void>((resolve) => limited.listen(testPort, () => resolve()));
+
+      try {
+        const small = await fetch(`http://127.0.0.1:${testPort}/echo`, { method: 'POST', body: 'ok' });
+        expect(small.status).toBe(200);
+
+        const tooLarge = await fetch(`http://127.0.0.1:${testPort}/echo`, { method: 'POST', body: 'x'.repeat(65) });
+        expect(tooLarge.status).toBe(413);
+
+        // Chunked body without Content-Length is cut off while reading
+        const chunked = await fetch(`http://127.0.0.1:${testPort}/echo`, {
+          method: 'POST',
+          body: Readable.toWeb(Readable.from([Buffer.alloc(40), Buffer.alloc(40)])) as any,
+          duplex: 'half',
+        } as any);
+        expect(chunked.status).toBe(413);
+
+        const routeLimit = await fetch(`http://127.0.0.1:${testPort}/large`, { method: 'POST', body: 'x'.repeat(500) });
+        expect(routeLimit.status).toBe(200);
+
+        const raw = await fetch(`http://127.0.0.1:${testPort}/raw`, {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify({ data: 'y'.repeat(3000) }),
+        });
+        expect(raw.status).toBe(200);
+        expect(await raw.json()).toEqual({ parsed: {}, size: 3013 });
+
+        const rawTooLarge = await fetch(`http://127.0.0.1:${testPort}/raw`, { method: 'POST', body: 'z'.repeat(5000) });
+        expect(rawTooLarge.status).toBe(413);
+      } finally {
+        await limited.close(1000);
+      }
+    });
+
+    it('should apply the limit to multipart and unrouted requests', async () => {
+      const limited = createApp({ maxBodySize: 64 });
+
+      limited.post('/form', (req: Request, res: Response) => {
+        res.json({ ok: true });
+      });
+
+      await new Promise<void>((resolve) => limited.listen(testPort, () => resolve()));
+
+      try {
+        const form = new FormData();
+        form.append('file', new Blob([Buffer.alloc(1024)]), 'large.bin');
+        const multipart = await fetch(`http://127.0.0.1:${testPort}/form`, { method: 'POST', body: form });
+        expect(multipart.status).toBe(413);
+
+        const unrouted = await fetch(`http://127.0.0.1:${testPort}/missing`, { method: 'POST', body: 'x'.repeat(65) });
+        expect(unrouted.status).toBe(413);
+      } finally {
+        await limited.close(1000);
+      }
+    });
+  });
 });
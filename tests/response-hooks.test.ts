@@ -0,0 +1,59 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('Response Hooks', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should pass the built response to matching hooks', async () => {
+    const app = createApp({ responseTime: true });
+    const seen: Array<{ url: string; status: number; bodySize: number | null }> = [];
+
+    app.after((req: Request, res: Response, info) => {
+      seen.push({ url: req.url, status: res.currentStatus, bodySize: info.bodySize });
+      res.setHeader('X-Frame-Options', 'DENY');
+    });
+    app.after('/api/*', (req: Request, res: Response) => {
+      // Rewrite error bodies
+      if (res.currentStatus >= 400) {
+        res.json({ error: true, status: res.currentStatus });
+      }
+      res.setCookie('seen', '1');
+    }, { method: 'GET' });
+
+    app.get('/api/ok', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+    app.get('/api/missing', (req: Request, res: Response) => {
+      res.status(404).send('not here');
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const request = (path: string) => makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method: 'GET' });
+
+    try {
+      const ok = await request('/api/ok');
+      expect(ok.statusCode).toBe(200);
+      expect(ok.body.ok).toBe(true);
+      expect(ok.headers['x-frame-options']).toBe('DENY');
+      expect(ok.headers['x-response-time']).toMatch(/ms$/);
+      expect(ok.headers['set-cookie']?.[0]).toContain('seen=1');
+
+      const missing = await request('/api/missing');
+      expect(missing.statusCode).toBe(404);
+      expect(missing.body).toEqual({ error: true, status: 404 });
+
+      expect(seen).toEqual([
+        { url: '/api/ok', status: 200, bodySize: 11 },
+        { url: '/api/missing', status: 404, bodySize: 8 },
+      ]);
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
@@ -82,4 +82,168 @@ describe('Router Integration Tests', () => {
       });
     });
   });
+
+  describe('Handler Binding', () => {
+    it('should keep separate handlers for paths differing only by / and _', async () => {
+      app.get('/a/b', (req: Request, res: Response) => {
+        res.json({ route: 'slash' });
+      });
+      app.get('/a_b', (req: Request, res: Response) => {
+        res.json({ route: 'underscore' });
+      });
+
+      return new Promise<void>(async (resolve, reject) => {
+        app.listen(testPort, async () => {
+          try {
+            const slash = await makeHttpRequest({
+              hostname: '127.0.0.1',
+              port: testPort,
+              path: '/a/b',
+              method: 'GET'
+            });
+            expect(slash.body.route).toBe('slash');
+
+            const underscore = await makeHttpRequest({
+              hostname: '127.0.0.1',
+              port: testPort,
+              path: '/a_b',
+              method: 'GET'
+            });
+            expect(underscore.body.route).toBe('underscore');
+            resolve();
+          } catch (error) {
+            reject(error);
+          }
+        });
+      });
+    });
+  });
+
+  describe('Hot Route Table', () => {
+    it('should apply routes added and removed after listen', async () => {
+      return new Promise<void>(async (resolve, reject) => {
+        app.listen(testPort, async () => {
+          try {
+            // Register a route on the running server
+            app.get('/late', (req: Request, res: Response) => {
+              res.json({ late: true });
+            });
+
+            const added = await makeHttpRequest({
+              hostname: '127.0.0.1',
+              port: testPort,
+              path: '/late',
+              method: 'GET'
+            });
+            expect(added.statusCode).toBe(200);
+            expect(added.body.late).toBe(true);
+
+            // Remove it again
+            expect(app.removeRoute('GET', '/late')).toBe(true);
+
+            const removed = await makeHttpRequest({
+              hostname: '127.0.0.1',
+              port: testPort,
+              path: '/late',
+              method: 'GET'
+            });
+            expect(removed.statusCode).toBe(404);
+            resolve();
+          } catch (error) {
+            reject(error);
+          }
+        });
+      });
+    });
+
+    it('should reject routes that conflict with registered ones', async () => {
+      app.websocket('/chat', { onMessage: () => {} });
+      app.sse('/events', (req, stream) => {
+        stream.send('hello');
+      });
+      app.get('/users/{id}', (req: Request, res: Response) => {
+        res.json({ id: req.params.id });
+      });
+
+      await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+      try {
+        const handler = (req: Request, res: Response) => res.json({ ok: true });
+        expect(() => app.any('/chat', handler)).toThrow('ANY /chat is already registered');
+        expect(() => app.get('/events', handler)).toThrow('GET /events is already registered');
+        expect(() => app.post('/users/{name}', handler)).toThrow(/conflicts with another route/);
+
+        // The running router is unchanged
+        const user = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/users/7', method: 'GET' });
+        expect(user.statusCode).toBe(200);
+        expect(user.body).toEqual({ id: '7' });
+      } finally {
+        await app.close(1000);
+      }
+    });
+  });
+
+  describe('Multiple Apps', () => {
+    it('should serve independent routes per app on separate ports', async () => {
+      const other = createApp();
+      const otherPort = testPort + 1;
+
+      app.get('/whoami', (req: Request, res: Response) => {
+        res.json({ app: 'first' });
+      });
+      other.get('/whoami', (req: Request, res: Response) => {
+        res.json({ app: 'second' });
+      });
+      other.get('/only-second', (req: Request, res: Response) => {
+        res.json({ ok: true });
+      });
+
+      expect(other.getAppId()).not.toBe(app.getAppId());
+
+      return new Promise<void>(async (resolve, reject) => {
+        app.listen(testPort, () => {
+          other.listen(otherPort, async () => {
+            try {
+              const first = await makeHttpRequest({
+                hostname: '127.0.0.1',
+                port: testPort,
+                path: '/whoami',
+                method: 'GET'
+              });
+              expect(first.body.app).toBe('first');
+
+              const second = await makeHttpRequest({
+                hostname: '127.0.0.1',
+                port: otherPort,
+                path: '/whoami',
+                method: 'GET'
+              });
+              expect(second.body.app).toBe('second');
+
+              const missing = await makeHttpRequest({
+                hostname: '127.0.0.1',
+                port: testPort,
+                path: '/only-second',
+                method: 'GET'
+              });
+              expect(missing.statusCode).toBe(404);
+
+              // Closing one app leaves the other running
+              await other.close(1000);
+              const stillUp = await makeHttpRequest({
+                hostname: '127.0.0.1',
+                port: testPort,
+                path: '/whoami',
+                method: 'GET'
+              });
+              expect(stillUp.statusCode).toBe(200);
+              resolve();
+            } catch (error) {
+              reject(error);
+            }
+          });
+        });
+      });
+    });
+  });
 });
@@ -0,0 +1,87 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+const templatesDir = path.join(__dirname, 'templates');
+
+describe('Security headers', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should add headers natively with a per-request CSP nonce and route overrides', async () => {
+    const app = createApp({
+      securityHeaders: {
+        contentSecurityPolicy: { nonce: true, directives: { imgSrc: ["'self'", 'https://img.example.com'] } },
+        frameOptions: 'DENY',
+        routes: {
+          '/embed/*': { frameOptions: false, contentSecurityPolicy: false },
+          '/raw/*': false,
+        },
+      },
+    });
+    app.initTemplates(`${templatesDir}/**/*.html`, { autoescape: true });
+
+    app.get('/page', (req: Request, res: Response) => {
+      const result = JSON.parse(app.renderTemplate('csp.html', {}, req));
+      res.json({ nonce: req.cspNonce, html: result.content });
+    });
+    app.get('/custom', (req: Request, res: Response) => {
+      res.setHeader('X-Frame-Options', 'SAMEORIGIN');
+      res.json({ ok: true });
+    });
+    app.get('/embed/widget', (req: Request, res: Response) => {
+      res.json({ nonce: req.cspNonce ?? null });
+    });
+    app.get('/raw/data', (req: Request, res: Response) => {
+      res.json({ ok: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const request = (path: string) => makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method: 'GET' });
+
+    try {
+      const page = await request('/page');
+      expect(page.statusCode).toBe(200);
+      const { nonce, html } = page.body;
+      expect(nonce).toBeTruthy();
+      expect(html).toContain(`nonce="${nonce}"`);
+
+      const csp = page.headers['content-security-policy'] as string;
+      expect(csp).toContain(`script-src 'self' 'nonce-${nonce}'`);
+      expect(csp).toContain("img-src 'self' https://img.example.com");
+      expect(page.headers['x-frame-options']).toBe('DENY');
+      expect(page.headers['strict-transport-security']).toBe('max-age=31536000; includeSubDomains');
+      expect(page.headers['x-content-type-options']).toBe('nosniff');
+      expect(page.headers['referrer-policy']).toBe('no-referrer');
+      expect(page.headers['x-powered-by']).toBeUndefined();
+
+      // Every request gets its own nonce
+      const again = (await request('/page')).body;
+      expect(again.nonce).not.toBe(nonce);
+
+      // Headers set by the handler win
+      const custom = await request('/custom');
+      expect(custom.headers['x-frame-options']).toBe('SAMEORIGIN');
+
+      const embed = await request('/embed/widget');
+      expect(embed.headers['x-frame-options']).toBeUndefined();
+      expect(embed.headers['content-security-policy']).toBeUndefined();
+      expect(embed.headers['x-content-type-options']).toBe('nosniff');
+      expect(embed.body.nonce).toBeNull();
+
+      const raw = await request('/raw/data');
+      expect(raw.headers['x-content-type-options']).toBeUndefined();
+
+      // Responses not produced by a handler get the headers too
+      const missing = await request('/missing.css');
+      expect(missing.headers['x-frame-options']).toBe('DENY');
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
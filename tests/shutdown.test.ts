@@ -0,0 +1,132 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { fork } from 'child_process';
+import * as net from 'net';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// Open a WebSocket over a raw socket; `closeFrame` resolves with the payload of the server's close frame
+function openWebSocket(port: number, path: string): Promise<{ socket: net.Socket; closeFrame: Promise<Buffer> }> {
+  return new Promise((resolve, reject) => {
+    const socket = net.connect(port, '127.0.0.1');
+    let buffer = Buffer.alloc(0);
+    let upgraded = false;
+    let onClose: (payload: Buffer) => void;
+    const closeFrame = new Promise<Buffer>((resolveClose) => (onClose = resolveClose));
+
+    socket.on('error', reject);
+    socket.on('data', (chunk) => {
+      buffer = Buffer.concat([buffer, chunk]);
+      if (!upgraded) {
+        const end = buffer.indexOf('\r\n\r\n');
+        if (end < 0) return;
+        if (!buffer.subarray(0, end).toString().startsWith('HTTP/1.1 101')) {
+          return reject(new Error(buffer.toString()));
+        }
+        upgraded = true;
+        buffer = buffer.subarray(end + 4);
+        resolve({ socket, closeFrame });
+      }
+      // Unmasked server frames: FIN/opcode, length, payload
+      while (buffer.length >= 2) {
+        let length = buffer[1] & 0x7f;
+        let offset = 2;
+        if (length === 126) {
+          if (buffer.length < 4) return;
+          length = buffer.readUInt16BE(2);
+          offset = 4;
+        } else if (length === 127) {
+          if (buffer.length < 10) return;
+          length = Number(buffer.readBigUInt64BE(2));
+          offset = 10;
+        }
+        if (buffer.length < offset + length) return;
+        if ((buffer[0] & 0x0f) === 0x8) {
+          onClose(buffer.subarray(offset, offset + length));
+        }
+        buffer = buffer.subarray(offset + length);
+      }
+    });
+
+    socket.write(
+      `GET ${path} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n` +
+        'Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n'
+    );
+  });
+}
+
+// Resolves with the error code of a refused connection, or null if it was accepted
+function tryConnect(port: number): Promise<string | null> {
+  return new Promise((resolve) => {
+    const socket = net.connect(port, '127.0.0.1', () => {
+      socket.destroy();
+      resolve(null);
+    });
+    socket.on('error', (error: NodeJS.ErrnoException) => resolve(error.code ?? 'error'));
+  });
+}
+
+describe('Graceful shutdown', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should finish in-flight requests, refuse new connections and close WebSockets', async () => {
+    const app = createApp();
+
+    app.get('/slow', async (req: Request, res: Response) => {
+      await new Promise((resolve) => setTimeout(resolve, 300));
+      res.json({ done: true });
+    });
+    app.websocket('/ws', { onMessage: () => {} });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const { socket, closeFrame } = await openWebSocket(testPort, '/ws');
+    const slow = makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/slow', method: 'GET', headers: { Connection: 'close' } });
+    await new Promise((resolve) => setTimeout(resolve, 100));
+
+    const closing = app.close(2000);
+    await new Promise((resolve) => setTimeout(resolve, 50));
+
+    try {
+      expect(await tryConnect(testPort)).toBe('ECONNREFUSED');
+
+      const response = await slow;
+      expect(response.statusCode).toBe(200);
+      expect(response.body).toEqual({ done: true });
+
+      // Close frame with status 1001 (going away)
+      const payload = await closeFrame;
+      expect(payload.readUInt16BE(0)).toBe(1001);
+
+      await expect(closing).resolves.toBeUndefined();
+    } finally {
+      socket.destroy();
+    }
+  });
+
+  it('should drain in-flight requests before exiting on SIGTERM', async () => {
+    const child = fork(path.join(__dirname, 'fixtures', 'shutdown', 'server.cjs'), [String(testPort)], { stdio: ['ignore', 'ignore', 'ignore', 'ipc'] });
+    const exited = new Promise<number | null>((resolve) => child.once('exit', (code) => resolve(code)));
+
+    try {
+      await new Promise<void>((resolve) => child.once('message', () => resolve()));
+
+      const slow = makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/slow', method: 'GET', headers: { Connection: 'close' } });
+      await new Promise((resolve) => setTimeout(resolve, 100));
+      // A second signal must wait for the same drain instead of exiting early
+      child.kill('SIGTERM');
+      child.kill('SIGTERM');
+
+      const response = await slow;
+      expect(response.statusCode).toBe(200);
+      expect(response.body).toEqual({ done: true });
+      expect(await exited).toBe(0);
+    } finally {
+      child.kill('SIGKILL');
+    }
+  }, 20000);
+});
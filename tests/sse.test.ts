@@ -0,0 +1,91 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as http from 'http';
+import { createApp, type Request, type SseStream } from '../';
+
+// Open an event stream and collect raw text until `until` matches
+function readEventStream(port: number, path: string, until: (text: string) => boolean, headers: Record<string, string> = {}) {
+  return new Promise<{ headers: http.IncomingHttpHeaders; text: string }>((resolve, reject) => {
+    const req = http.get({ hostname: '127.0.0.1', port, path, headers }, (res) => {
+      let text = '';
+      res.setEncoding('utf8');
+      res.on('data', (chunk: string) => {
+        text += chunk;
+        if (until(text)) {
+          req.destroy();
+          resolve({ headers: res.headers, text });
+        }
+      });
+      res.on('end', () => resolve({ headers: res.headers, text }));
+    });
+    req.on('error', reject);
+  });
+}
+
+describe('Server-Sent Events', () => {
+  let app: ReturnType<typeof createApp>;
+  let testPort: number;
+
+  beforeEach(() => {
+    app = createApp();
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should stream events, resume from Last-Event-ID and send keep-alives', async () => {
+    let closed = 0;
+
+    app.sse('/events', (req: Request, stream: SseStream) => {
+      stream.onClose(() => closed++);
+      if (stream.lastEventId) return;
+      stream.send({ id: 1, event: 'tick', data: { n: 1 } });
+      stream.send({ id: 2, data: 'two\nlines' });
+      stream.send({ id: 3, data: 'three', retry: 500 });
+    }, { keepAlive: 100 });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const first = await readEventStream(testPort, '/events', (text) => text.includes('data: three'));
+      expect(first.headers['content-type']).toBe('text/event-stream');
+      expect(first.headers['cache-control']).toBe('no-cache');
+      expect(first.text).toContain('id: 1\nevent: tick\ndata: {"n":1}\n\n');
+      expect(first.text).toContain('id: 2\ndata: two\ndata: lines\n\n');
+      expect(first.text).toContain('id: 3\nretry: 500\ndata: three\n\n');
+
+      // Reconnect after event 1: events 2 and 3 are replayed
+      const resumed = await readEventStream(
+        testPort, '/events', (text) => text.includes('data: three'), { 'Last-Event-ID': '1' }
+      );
+      expect(resumed.text).not.toContain('id: 1\n');
+      expect(resumed.text).toContain('id: 2\n');
+
+      // Idle stream gets keep-alive comments
+      const idle = await readEventStream(
+        testPort, '/events', (text) => text.includes(': keep-alive'), { 'Last-Event-ID': '3' }
+      );
+      expect(idle.text).toBe(': keep-alive\n\n');
+
+      // Client disconnects reach onClose
+      await new Promise(resolve => setTimeout(resolve, 100));
+      expect(closed).toBe(3);
+    } finally {
+      await app.close(1000);
+    }
+  }, 10000);
+
+  it('should end the stream when the handler closes it', async () => {
+    app.sse('/once', (req: Request, stream: SseStream) => {
+      stream.send('hello');
+      stream.close();
+      expect(stream.send('late')).toBe(false);
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const result = await readEventStream(testPort, '/once', () => false);
+      expect(result.text).toBe('data: hello\n\n');
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
@@ -0,0 +1,94 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import * as http from 'http';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+// Resolves with the reason once the request's signal is aborted
+function abortReason(req: Request): Promise<any> {
+  return new Promise((resolve) => req.abortSignal?.addEventListener('abort', () => resolve(req.abortSignal?.reason)));
+}
+
+describe('Timeouts and cancellation', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should apply route and middleware timeouts and abort the JS side', async () => {
+    const app = createApp({ timeout: 200, metrics: true });
+    const reasons: Promise<any>[] = [];
+
+    app.get('/slow', async (req: Request, res: Response) => {
+      const reason = abortReason(req);
+      reasons.push(reason);
+      await reason;
+      res.json({ done: true });
+    });
+    // Longer than the app's timeout
+    app.get('/report', async (req: Request, res: Response) => {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+      res.json({ done: true });
+    }, { timeout: 2000 });
+
+    app.use('/guarded', async (req: Request, res: Response, next: () => void) => {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      next();
+    }, { timeout: 50 });
+    app.get('/guarded', (req: Request, res: Response) => {
+      res.json({ reached: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+    const request = (path: string) => makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path, method: 'GET' });
+
+    try {
+      const slow = await request('/slow');
+      expect(slow.statusCode).toBe(408);
+      const reason = await reasons[0];
+      expect(reason.name).toBe('TimeoutError');
+
+      const report = await request('/report');
+      expect(report.statusCode).toBe(200);
+      expect(report.body).toEqual({ done: true });
+
+      const started = Date.now();
+      const guarded = await request('/guarded');
+      expect(guarded.statusCode).toBe(408);
+      expect(Date.now() - started).toBeLessThan(500);
+
+      const metrics = await makeHttpRequest({ hostname: '127.0.0.1', port: testPort, path: '/metrics', method: 'GET' });
+      expect(metrics.body).toMatch(/rnode_server_requests_cancelled_total\{path="\/slow",reason="timeout"\} 1/);
+      expect(metrics.body).toMatch(/rnode_server_requests_cancelled_total\{path="\/guarded",reason="timeout"\} 1/);
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should abort the handler when the client disconnects', async () => {
+    const app = createApp({ timeout: 5000 });
+    let aborted: Promise<any> | undefined;
+
+    app.get('/wait', async (req: Request, res: Response) => {
+      aborted = abortReason(req);
+      await aborted;
+      res.json({ done: true });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      const client = http.request({ hostname: '127.0.0.1', port: testPort, path: '/wait', method: 'GET' });
+      client.on('error', () => {});
+      client.end();
+      await new Promise((resolve) => setTimeout(resolve, 200));
+      client.destroy();
+
+      expect(aborted).toBeDefined();
+      const reason = await Promise.race([aborted, new Promise((resolve) => setTimeout(() => resolve(null), 2000))]);
+      expect(reason?.name).toBe('AbortError');
+    } finally {
+      await app.close(1000);
+    }
+  });
+});
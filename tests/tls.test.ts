@@ -0,0 +1,87 @@
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import * as fs from 'fs';
+import * as https from 'https';
+import * as os from 'os';
+import * as path from 'path';
+import { createApp, type Request, type Response } from '../';
+
+const fixtures = path.join(__dirname, 'fixtures', 'tls');
+
+// Common name of the certificate the server presents for `servername`
+function certificateFor(port: number, servername: string): Promise<{ cn: string; protocol: string }> {
+  return new Promise((resolve, reject) => {
+    const req = https.request(
+      { hostname: '127.0.0.1', port, path: '/', method: 'GET', servername, rejectUnauthorized: false, agent: false },
+      (res) => {
+        const cn = String((res.socket as any).getPeerCertificate().subject.CN);
+        let data = '';
+        res.on('data', (chunk) => (data += chunk));
+        res.on('end', () => resolve({ cn, protocol: JSON.parse(data).protocol }));
+      }
+    );
+    req.on('error', reject);
+    req.end();
+  });
+}
+
+describe('TLS', () => {
+  let testPort: number;
+  let dir: string;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'rnode-tls-'));
+    for (const file of fs.readdirSync(fixtures)) {
+      fs.copyFileSync(path.join(fixtures, file), path.join(dir, file));
+    }
+  });
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('should pick certificates by SNI and reload them without restarting', async () => {
+    const app = createApp({
+      ssl: {
+        certPath: path.join(dir, 'default.crt'),
+        keyPath: path.join(dir, 'default.key'),
+        certificates: [
+          { serverNames: ['api.example.test'], certPath: path.join(dir, 'api.crt'), keyPath: path.join(dir, 'api.key') },
+        ],
+        strict: true,
+      },
+    });
+
+    app.get('/', (req: Request, res: Response) => {
+      res.json({ protocol: req.protocol });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    try {
+      expect(await certificateFor(testPort, 'localhost')).toEqual({ cn: 'localhost', protocol: 'https' });
+      expect((await certificateFor(testPort, 'api.example.test')).cn).toBe('api.example.test');
+
+      // Swap the default certificate on disk
+      fs.copyFileSync(path.join(fixtures, 'api.crt'), path.join(dir, 'default.crt'));
+      fs.copyFileSync(path.join(fixtures, 'api.key'), path.join(dir, 'default.key'));
+      app.reloadTls();
+      expect((await certificateFor(testPort, 'localhost')).cn).toBe('api.example.test');
+
+      // A broken key keeps the loaded certificates
+      fs.writeFileSync(path.join(dir, 'default.key'), 'not a key');
+      expect(() => app.reloadTls()).toThrow(/Failed to reload TLS certificates/);
+      expect((await certificateFor(testPort, 'localhost')).cn).toBe('api.example.test');
+    } finally {
+      await app.close(1000);
+    }
+  });
+
+  it('should fail listen() in strict mode when certificates cannot be loaded', () => {
+    const app = createApp({
+      ssl: { certPath: path.join(dir, 'missing.crt'), keyPath: path.join(dir, 'missing.key'), strict: true },
+    });
+
+    expect(() => app.listen(testPort)).toThrow(/Failed to load TLS certificates/);
+  });
+});
@@ -0,0 +1,76 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createServer, type IncomingMessage, type ServerResponse } from 'http';
+import type { AddressInfo } from 'net';
+import { createApp, type Request, type Response } from '../';
+import { makeHttpRequest } from './helpers';
+
+describe('Tracing', () => {
+  let testPort: number;
+
+  beforeEach(() => {
+    testPort = Math.floor(Math.random() * 10000) + 3000;
+  });
+
+  it('should continue incoming traces, propagate them and export spans', async () => {
+    // Stands in for both the OTLP collector and a downstream service
+    const spans: any[] = [];
+    const downstreamTraceparents: string[] = [];
+    const collector = createServer((req: IncomingMessage, res: ServerResponse) => {
+      let data = '';
+      req.on('data', (chunk: Buffer) => (data += chunk));
+      req.on('end', () => {
+        if (req.url === '/v1/traces') {
+          for (const resourceSpans of JSON.parse(data).resourceSpans) {
+            for (const scopeSpans of resourceSpans.scopeSpans) spans.push(...scopeSpans.spans);
+          }
+        } else {
+          downstreamTraceparents.push(String(req.headers['traceparent']));
+        }
+        res.writeHead(200, { 'Content-Type': 'application/json' });
+        res.end('{}');
+      });
+    });
+    await new Promise<void>((resolve) => collector.listen(0, '127.0.0.1', () => resolve()));
+    const collectorUrl = `http://127.0.0.1:${(collector.address() as AddressInfo).port}`;
+
+    const app = createApp({ tracing: { endpoint: collectorUrl, serviceName: 'tracing-test', flushIntervalMs: 50 } });
+    app.get('/orders', async (req: Request, res: Response) => {
+      await app.httpRequest('GET', `${collectorUrl}/downstream`);
+      res.json({ traceparent: req.traceparent });
+    });
+
+    await new Promise<void>((resolve) => app.listen(testPort, () => resolve()));
+
+    const traceId = '4bf92f3577b34da6a3ce929d0e0e4736';
+    try {
+      const response = await makeHttpRequest({
+        hostname: '127.0.0.1',
+        port: testPort,
+        path: '/orders',
+        method: 'GET',
+        headers: { traceparent: `00-${traceId}-00f067aa0ba902b7-01` }
+      });
+      expect(response.statusCode).toBe(200);
+      expect(response.body.traceparent).toMatch(new RegExp(`^00-${traceId}-[0-9a-f]{16}-01$`));
+
+      // The outgoing request carries the trace, one level below the handler
+      expect(downstreamTraceparents).toHaveLength(1);
+      expect(downstreamTraceparents[0]).toMatch(new RegExp(`^00-${traceId}-[0-9a-f]{16}-01$`));
+
+      await new Promise((resolve) => setTimeout(resolve, 300));
+      const trace = spans.filter((span) => span.traceId === traceId);
+      const server = trace.find((span) => span.kind === 2);
+      const handler = trace.find((span) => span.name.startsWith('handler'));
+      const client = trace.find((span) => span.name === 'HTTP GET');
+
+      expect(server.parentSpanId).toBe('00f067aa0ba902b7');
+      expect(handler.parentSpanId).toBe(server.spanId);
+      expect(response.body.traceparent).toContain(handler.spanId);
+      expect(client.parentSpanId).toBe(handler.spanId);
+      expect(downstreamTraceparents[0]).toContain(client.spanId);
+    } finally {
+      await app.close(1000);
+      collector.close();
+    }
+  });
+});